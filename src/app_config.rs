@@ -1,9 +1,85 @@
 //! 应用程序配置模块
 
-use serde::{Deserialize, Serialize};
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
-use crate::hotkey_config::HotkeyConfig;
+use crate::clipboard_format::{CaptureSelection, ClipboardFormat, NonTextClipboard};
+use crate::history::HistorySort;
+use crate::hotkey_config::{
+    HotkeyAction, HotkeyConfig, MainHotkeyAction, MouseTrigger, PausedHotkeyAction,
+};
+use crate::macros::Macro;
+use crate::sound::SoundCueEvents;
+use crate::text_transform::TransformConfig;
+
+/// 剪贴板历史条数上限
+pub const HISTORY_MAX_ITEMS_LIMIT: u32 = 1000;
+/// 一天的分钟数
+pub const MINUTES_PER_DAY: u32 = 24 * 60;
+/// 窗口不透明度下限，避免窗口完全看不见
+pub const MIN_WINDOW_OPACITY: f32 = 0.3;
+
+/// emoji 的输入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EmojiMode {
+    /// 与普通字符一样直接输入
+    #[default]
+    Verbatim,
+    /// 通过剪贴板粘贴 emoji，其余字符照常输入
+    PasteFallback,
+    /// 跳过 emoji
+    Skip,
+}
+
+impl EmojiMode {
+    /// 获取所有选项
+    pub fn all() -> Vec<EmojiMode> {
+        vec![EmojiMode::Verbatim, EmojiMode::PasteFallback, EmojiMode::Skip]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            EmojiMode::Verbatim => "ui.app.emoji_verbatim",
+            EmojiMode::PasteFallback => "ui.app.emoji_paste_fallback",
+            EmojiMode::Skip => "ui.app.emoji_skip",
+        }
+    }
+}
+
+/// 输入过程中会话被锁定时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SessionLockAction {
+    /// 暂停输入，解锁后需手动继续
+    #[default]
+    Pause,
+    /// 暂停输入，解锁后自动继续
+    PauseAndResume,
+    /// 取消输入
+    Cancel,
+}
+
+impl SessionLockAction {
+    /// 获取所有选项
+    pub fn all() -> Vec<SessionLockAction> {
+        vec![
+            SessionLockAction::Pause,
+            SessionLockAction::PauseAndResume,
+            SessionLockAction::Cancel,
+        ]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            SessionLockAction::Pause => "ui.app.session_lock_pause",
+            SessionLockAction::PauseAndResume => "ui.app.session_lock_pause_and_resume",
+            SessionLockAction::Cancel => "ui.app.session_lock_cancel",
+        }
+    }
+}
 
 /// 关闭窗口时的行为
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -30,6 +106,309 @@ impl CloseAction {
     }
 }
 
+/// 窗口停靠的屏幕角落
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DockCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl DockCorner {
+    /// 获取所有角落
+    pub fn all() -> Vec<DockCorner> {
+        vec![
+            DockCorner::TopLeft,
+            DockCorner::TopRight,
+            DockCorner::BottomLeft,
+            DockCorner::BottomRight,
+        ]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            DockCorner::TopLeft => "ui.dock.top_left",
+            DockCorner::TopRight => "ui.dock.top_right",
+            DockCorner::BottomLeft => "ui.dock.bottom_left",
+            DockCorner::BottomRight => "ui.dock.bottom_right",
+        }
+    }
+}
+
+/// 托盘菜单中可显示的项目
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrayMenuEntry {
+    Show,
+    Toggle,
+    TypeNow,
+    History,
+    Snooze,
+    TypeWithSpeed,
+    Separator,
+    Exit,
+}
+
+impl TrayMenuEntry {
+    /// 获取所有项目
+    pub fn all() -> Vec<TrayMenuEntry> {
+        vec![
+            TrayMenuEntry::Show,
+            TrayMenuEntry::Toggle,
+            TrayMenuEntry::TypeNow,
+            TrayMenuEntry::History,
+            TrayMenuEntry::Snooze,
+            TrayMenuEntry::TypeWithSpeed,
+            TrayMenuEntry::Separator,
+            TrayMenuEntry::Exit,
+        ]
+    }
+
+    /// 默认的托盘菜单，与之前固定的菜单相同
+    pub fn default_menu() -> Vec<TrayMenuEntry> {
+        vec![
+            TrayMenuEntry::Show,
+            TrayMenuEntry::Toggle,
+            TrayMenuEntry::Snooze,
+            TrayMenuEntry::TypeWithSpeed,
+            TrayMenuEntry::Separator,
+            TrayMenuEntry::Exit,
+        ]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            TrayMenuEntry::Show => "tray.menu_show",
+            TrayMenuEntry::Toggle => "tray.menu_toggle",
+            TrayMenuEntry::TypeNow => "tray.menu_type_now",
+            TrayMenuEntry::History => "tray.menu_history",
+            TrayMenuEntry::Snooze => "tray.menu_snooze",
+            TrayMenuEntry::TypeWithSpeed => "tray.menu_type_with_speed",
+            TrayMenuEntry::Separator => "tray.menu_separator",
+            TrayMenuEntry::Exit => "tray.menu_exit",
+        }
+    }
+}
+
+/// 来源程序过滤方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SourceFilterMode {
+    /// 不过滤
+    #[default]
+    Off,
+    /// 只捕获列表中的程序复制的内容
+    Allowlist,
+    /// 忽略列表中的程序复制的内容
+    Blocklist,
+}
+
+impl SourceFilterMode {
+    /// 获取所有过滤方式
+    pub fn all() -> Vec<SourceFilterMode> {
+        vec![
+            SourceFilterMode::Off,
+            SourceFilterMode::Allowlist,
+            SourceFilterMode::Blocklist,
+        ]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            SourceFilterMode::Off => "ui.app.source_filter_off",
+            SourceFilterMode::Allowlist => "ui.app.source_filter_allowlist",
+            SourceFilterMode::Blocklist => "ui.app.source_filter_blocklist",
+        }
+    }
+}
+
+/// 按来源程序过滤剪贴板捕获
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SourceFilter {
+    /// 过滤方式
+    #[serde(default)]
+    pub mode: SourceFilterMode,
+    /// 程序的可执行文件名（如 `notepad.exe`），不区分大小写
+    #[serde(default)]
+    pub apps: Vec<String>,
+}
+
+impl SourceFilter {
+    /// 是否允许捕获来自 `exe` 的内容
+    ///
+    /// 无法确定来源程序时（例如非 Windows 平台）始终允许。
+    pub fn allows(&self, exe: Option<&str>) -> bool {
+        let Some(exe) = exe else {
+            return true;
+        };
+        let listed = self.apps.iter().any(|app| app.eq_ignore_ascii_case(exe));
+        match self.mode {
+            SourceFilterMode::Off => true,
+            SourceFilterMode::Allowlist => listed,
+            SourceFilterMode::Blocklist => !listed,
+        }
+    }
+}
+
+/// 字符类别，用于按类别调整输入延迟
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    /// 字母（包括中文等各类文字）
+    Letter,
+    /// 数字
+    Digit,
+    /// 标点和其他符号
+    Symbol,
+    /// 空格、换行等空白字符
+    Whitespace,
+}
+
+impl CharClass {
+    /// 获取所有类别
+    pub fn all() -> Vec<CharClass> {
+        vec![
+            CharClass::Letter,
+            CharClass::Digit,
+            CharClass::Symbol,
+            CharClass::Whitespace,
+        ]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            CharClass::Letter => "ui.app.char_class_letter",
+            CharClass::Digit => "ui.app.char_class_digit",
+            CharClass::Symbol => "ui.app.char_class_symbol",
+            CharClass::Whitespace => "ui.app.char_class_whitespace",
+        }
+    }
+
+    /// 字符所属的类别
+    pub fn of(c: char) -> CharClass {
+        if c.is_whitespace() {
+            CharClass::Whitespace
+        } else if c.is_numeric() {
+            CharClass::Digit
+        } else if c.is_alphabetic() {
+            CharClass::Letter
+        } else {
+            CharClass::Symbol
+        }
+    }
+}
+
+/// 按字符类别额外增加的输入延迟，模拟更自然的打字节奏
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharClassDelays {
+    /// 是否启用
+    pub enabled: bool,
+    /// 字母的额外延迟 (毫秒)
+    pub letter: u64,
+    /// 数字的额外延迟 (毫秒)
+    pub digit: u64,
+    /// 符号的额外延迟 (毫秒)
+    pub symbol: u64,
+    /// 空白字符的额外延迟 (毫秒)
+    pub whitespace: u64,
+}
+
+impl Default for CharClassDelays {
+    /// 字母最快，数字稍慢，符号更慢，空白（单词之间）停顿最长
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            letter: 0,
+            digit: 10,
+            symbol: 30,
+            whitespace: 50,
+        }
+    }
+}
+
+impl CharClassDelays {
+    /// 某个类别的额外延迟
+    pub fn get_mut(&mut self, class: CharClass) -> &mut u64 {
+        match class {
+            CharClass::Letter => &mut self.letter,
+            CharClass::Digit => &mut self.digit,
+            CharClass::Symbol => &mut self.symbol,
+            CharClass::Whitespace => &mut self.whitespace,
+        }
+    }
+
+    /// 输入 `c` 后的额外延迟 (毫秒)，未启用时为 0
+    pub fn char_class_delay(&self, c: char) -> u64 {
+        if !self.enabled {
+            return 0;
+        }
+        match CharClass::of(c) {
+            CharClass::Letter => self.letter,
+            CharClass::Digit => self.digit,
+            CharClass::Symbol => self.symbol,
+            CharClass::Whitespace => self.whitespace,
+        }
+    }
+}
+
+/// 定时启用的一个时间段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleWindow {
+    /// 生效的星期，下标 0 为周一
+    pub days: [bool; 7],
+    /// 开始时间，从 0 点起的分钟数
+    pub start_minute: u32,
+    /// 结束时间，从 0 点起的分钟数；不晚于开始时间时表示跨越午夜，到次日结束
+    pub end_minute: u32,
+}
+
+impl Default for ScheduleWindow {
+    /// 周一至周五 9:00–17:00
+    fn default() -> Self {
+        Self {
+            days: [true, true, true, true, true, false, false],
+            start_minute: 9 * 60,
+            end_minute: 17 * 60,
+        }
+    }
+}
+
+impl ScheduleWindow {
+    /// `weekday` 为从周一起的下标，`minute` 为从 0 点起的分钟数
+    fn contains(&self, weekday: usize, minute: u32) -> bool {
+        if self.start_minute < self.end_minute {
+            return self.days[weekday] && (self.start_minute..self.end_minute).contains(&minute);
+        }
+        // 跨越午夜：开始当天的后半段，以及次日的前半段
+        let yesterday = (weekday + 6) % 7;
+        (self.days[weekday] && minute >= self.start_minute)
+            || (self.days[yesterday] && minute < self.end_minute)
+    }
+}
+
+/// 按时间段自动启用或禁用
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Schedule {
+    /// 是否启用定时
+    #[serde(default)]
+    pub enabled: bool,
+    /// 启用的时间段，任一时间段内即为启用
+    #[serde(default)]
+    pub windows: Vec<ScheduleWindow>,
+}
+
+impl Schedule {
+    /// `now` 是否在某个时间段内
+    pub fn is_active_at(&self, now: NaiveDateTime) -> bool {
+        let weekday = now.weekday().num_days_from_monday() as usize;
+        let minute = now.hour() * 60 + now.minute();
+        self.windows.iter().any(|window| window.contains(weekday, minute))
+    }
+}
+
 /// 应用程序配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -39,39 +418,262 @@ pub struct AppConfig {
     pub auto_start: bool,
     /// 是否启动时最小化
     pub start_minimized: bool,
+    /// 启动时是否处于启用状态
+    #[serde(default = "default_true")]
+    pub start_enabled: bool,
+    /// 是否记住上次的启用/禁用状态（切换时写入 `start_enabled`）
+    #[serde(default)]
+    pub persist_enabled_state: bool,
     /// 是否已经询问过开机自启（用于首次启动询问）
     #[serde(default)]
     pub autostart_asked: bool,
+    /// 是否已完成首次启动引导（旧版本的配置文件视为已完成）
+    #[serde(default = "default_true")]
+    pub onboarded: bool,
     /// 是否显示调试控制台
     #[serde(default)]
     pub show_console: bool,
+    /// 上次选择的窗口停靠角落，启动时恢复
+    #[serde(default)]
+    pub dock_corner: Option<DockCorner>,
+    /// 以置顶的迷你部件显示，只保留输入和展开按钮
+    #[serde(default)]
+    pub mini_mode: bool,
+    /// 托盘菜单显示的项目及顺序
+    #[serde(default = "TrayMenuEntry::default_menu")]
+    pub tray_menu: Vec<TrayMenuEntry>,
+    /// 窗口背景的不透明度，1.0 为完全不透明
+    #[serde(default = "default_window_opacity")]
+    pub window_opacity: f32,
     /// 模拟输入时的按键延迟 (毫秒)
     #[serde(default = "default_typing_delay")]
     pub typing_delay: u64,
+    /// 是否按目标 WPM（每分钟单词数）计算按键延迟，而不是直接设置毫秒
+    #[serde(default)]
+    pub delay_from_wpm: bool,
+    /// 目标每分钟单词数，按每个单词约 5 个字符换算为按键延迟
+    #[serde(default = "default_target_wpm")]
+    pub target_wpm: u32,
     /// 模拟输入时的随机偏差 (毫秒)
     #[serde(default = "default_typing_variance")]
     pub typing_variance: u64,
     /// 是否启用随机偏差
     #[serde(default)]
     pub typing_variance_enabled: bool,
+    /// 随机偏差的随机数种子，设置后相同文本每次的输入节奏都相同，便于复现问题
+    #[serde(default)]
+    pub variance_seed: Option<u64>,
+    /// 每次换行后额外等待的时间 (毫秒)，便于目标编辑器完成自动缩进
+    #[serde(default)]
+    pub line_delay_ms: u64,
+    /// 单行过长时每输入一定数量的字符就额外等待一次换行延迟，避免目标程序来不及处理而丢字
+    #[serde(default)]
+    pub split_long_lines: bool,
+    /// 长行中每隔多少个字符额外等待一次
+    #[serde(default = "default_split_long_lines_at")]
+    pub split_long_lines_at: usize,
+    /// 按字符类别额外增加的延迟
+    #[serde(default)]
+    pub char_class_delays: CharClassDelays,
+    /// 开始输入前等待修饰键松开的最长时间 (毫秒，最多 10000)，非 Windows 平台固定等待 250ms
+    #[serde(default = "default_modifier_release_timeout_ms")]
+    pub modifier_release_timeout_ms: u64,
+    /// 强制的最小按键延迟 (毫秒)，无论使用何种预设都不会低于此值
+    #[serde(default)]
+    pub min_enforced_delay_ms: u64,
+    /// 单次输入的最长时间 (毫秒)，暂停的时间不计入，超过后自动停止；0 表示不限制
+    #[serde(default)]
+    pub max_type_duration_ms: u64,
+    /// 输入成功完成后的冷却时间 (毫秒)，期间忽略新的输入请求，避免按键抖动导致重复输入；0 表示不限制
+    #[serde(default)]
+    pub post_type_cooldown_ms: u64,
+    /// 是否将文本中的 `<<TAB>>`、`<<ENTER>>`、`<<ESC>>`、`<<DELAY:毫秒>>`、`<<PAUSE>>` 解析为按键、停顿和暂停
+    #[serde(default)]
+    pub key_tokens_enabled: bool,
+    /// 列模式：换行时发送 ↓ + Home 而不是回车，使每行依次填入下一行单元格
+    #[serde(default)]
+    pub column_paste: bool,
+    /// 逐行输入：每次触发只输入剪贴板的下一行（跳过空行），便于逐个点击表单字段填写
+    #[serde(default)]
+    pub line_by_line: bool,
+    /// emoji 的输入方式
+    #[serde(default)]
+    pub emoji_mode: EmojiMode,
+    /// 剪贴板带有 HTML/RTF 等富文本格式时，直接发送系统粘贴快捷键以保留格式，而不是逐字输入
+    #[serde(default)]
+    pub paste_rich: bool,
+    /// 输入前的文本转换
+    #[serde(default)]
+    pub transform: TransformConfig,
+    /// 焦点位于密码框时拒绝输入（目前仅 Windows 支持，无法判断时照常输入）
+    #[serde(default)]
+    pub block_password_fields: bool,
+    /// 触发输入后先等待一次鼠标单击，在点击的位置开始输入（目前仅 Windows 支持）
+    #[serde(default)]
+    pub wait_for_click_before_type: bool,
+    /// 等待鼠标单击的最长时间 (毫秒)，超时则放弃输入
+    #[serde(default = "default_click_wait_timeout_ms")]
+    pub click_wait_timeout_ms: u64,
+    /// 从本程序窗口触发输入时，是否先切换回最近使用的外部窗口（目前仅 Windows 支持）
+    #[serde(default = "default_true")]
+    pub refocus_external_window: bool,
+    /// 从本程序窗口触发的输入成功完成后，是否把窗口隐藏到托盘
+    #[serde(default)]
+    pub auto_hide_after_type: bool,
+    /// 从本程序窗口触发的输入成功完成后，是否把本程序窗口切回前台（与自动隐藏互斥）
+    #[serde(default)]
+    pub refocus_self_after_type: bool,
+    /// 输入成功完成后把输入的文本写回剪贴板，之后手动粘贴的内容与输入的一致
+    #[serde(default)]
+    pub restore_clipboard_to_typed: bool,
+    /// 从本程序窗口输入前，若文本首尾带有空格或制表符，先显示确认窗口
+    #[serde(default)]
+    pub confirm_edge_whitespace: bool,
+    /// 终端安全模式下，文本像多行命令时先确认再输入
+    #[serde(default = "default_true")]
+    pub confirm_shell_commands: bool,
+    /// 输入剪贴板文本时，若当前前台程序与复制时的来源程序不同，先确认再输入（目前仅 Windows 支持）
+    #[serde(default)]
+    pub confirm_cross_app: bool,
+    /// 输入前用于预处理文本的外部命令：文本写入其标准输入，用其标准输出作为要输入的文本；为空时不使用
+    #[serde(default)]
+    pub preprocess_command: String,
+    /// 预处理命令的超时时间 (毫秒)，超时或失败时输入原文本
+    #[serde(default = "default_preprocess_timeout_ms")]
+    pub preprocess_timeout_ms: u64,
+    /// 监视的文件，内容变化后输入其内容；为 None 时不监视
+    #[serde(default)]
+    pub watch_file: Option<PathBuf>,
+    /// 输入监视文件的内容前是否先确认
+    #[serde(default = "default_true")]
+    pub watch_file_confirm: bool,
+    /// 监视文件变化后需要保持不变的时间 (毫秒)，避免在写入过程中读取
+    #[serde(default = "default_watch_file_debounce_ms")]
+    pub watch_file_debounce_ms: u64,
+    /// 捕获到新的剪贴板内容时，在状态栏和托盘提示中显示预览
+    #[serde(default)]
+    pub notify_on_capture: bool,
+    /// 启动时是否立即捕获剪贴板中已有的内容，关闭时只捕获之后的变化
+    #[serde(default = "default_true")]
+    pub capture_on_startup: bool,
+    /// 监控剪贴板还是主选区（仅 Linux 有效）
+    #[serde(default)]
+    pub capture_selection: CaptureSelection,
+    /// 使用电池供电时检查剪贴板的间隔 (毫秒)，无法检测电源状态时按正常间隔检查
+    #[serde(default = "default_battery_poll_interval_ms")]
+    pub battery_poll_interval_ms: u64,
+    /// 剪贴板中只有图片、文件等非文本内容时的处理方式
+    #[serde(default)]
+    pub non_text_clipboard: NonTextClipboard,
+    /// 累积模式下连续复制的内容之间的分隔符，支持 `\n`、`\t` 转义
+    #[serde(default = "default_accumulate_separator")]
+    pub accumulate_separator: String,
+    /// 输入过程中会话被锁定时的处理方式（目前仅 Windows 支持）
+    #[serde(default)]
+    pub on_session_lock: SessionLockAction,
+    /// 是否在开始、暂停、完成和出错时播放提示音
+    #[serde(default)]
+    pub sound_cues: bool,
+    /// 各事件是否播放提示音
+    #[serde(default)]
+    pub sound_cue_events: SoundCueEvents,
     /// 是否保存剪贴板历史
     #[serde(default)]
     pub history_enabled: bool,
     /// 剪贴板历史最多保存条数
     #[serde(default = "default_history_max_items")]
     pub history_max_items: u32,
+    /// 是否把剪贴板历史（含使用次数）保存到磁盘，重启后恢复
+    #[serde(default)]
+    pub persist_history: bool,
+    /// 是否用口令加密保存的历史（需要启用 `encrypted-history` 特性）
+    #[serde(default)]
+    pub encrypt_history: bool,
+    /// 历史列表的排序方式
+    #[serde(default)]
+    pub history_sort: HistorySort,
+    /// 模拟输入期间（及结束后片刻）暂停捕获剪贴板，避免记录本程序自身的剪贴板操作
+    #[serde(default = "default_true")]
+    pub pause_monitor_while_typing: bool,
     /// 快捷键配置
     #[serde(default)]
     pub hotkey: HotkeyConfig,
+    /// 是否注册全局快捷键，关闭后只能通过窗口按钮或托盘菜单触发输入
+    #[serde(default = "default_true")]
+    pub hotkey_enabled: bool,
+    /// 代替快捷键触发输入的鼠标按键（目前仅 Windows 支持）
+    #[serde(default)]
+    pub mouse_trigger: Option<MouseTrigger>,
+    /// 主快捷键在未输入时执行的操作
+    #[serde(default)]
+    pub main_hotkey_action: MainHotkeyAction,
+    /// 输入暂停时再次按下主快捷键执行的操作
+    #[serde(default)]
+    pub paused_hotkey_action: PausedHotkeyAction,
+    /// 在该时间 (毫秒) 内连按两次取消快捷键时直接退出程序；0 表示不启用
+    #[serde(default = "default_double_cancel_quit_ms")]
+    pub double_cancel_quit_ms: u64,
+    /// 附加操作的快捷键（未绑定的操作不出现在表中）
+    #[serde(default)]
+    pub action_hotkeys: BTreeMap<HotkeyAction, HotkeyConfig>,
+    /// 宏列表
+    #[serde(default)]
+    pub macros: Vec<Macro>,
+    /// 上次保存配置的操作系统，保存时总是写入当前系统
+    #[serde(default, serialize_with = "serialize_current_os")]
+    pub saved_on_os: String,
+    /// 加载在其他系统上保存的配置时，若快捷键使用了 Meta 键则给出提示
+    #[serde(default = "default_true")]
+    pub warn_meta_platform_change: bool,
+    /// 创建快捷键管理器的最大尝试次数
+    #[serde(default = "default_hotkey_init_attempts")]
+    pub hotkey_init_attempts: u32,
+    /// 创建快捷键管理器失败后首次重试前的等待时间 (毫秒)，之后每次翻倍
+    #[serde(default = "default_hotkey_init_backoff_ms")]
+    pub hotkey_init_backoff_ms: u64,
+    /// 打开剪贴板的最大尝试次数
+    #[serde(default = "default_clipboard_init_attempts")]
+    pub clipboard_init_attempts: u32,
+    /// 打开剪贴板失败后首次重试前的等待时间 (毫秒)，之后每次翻倍
+    #[serde(default = "default_clipboard_init_backoff_ms")]
+    pub clipboard_init_backoff_ms: u64,
+    /// 调试日志中输入进度最多每隔多少个字符输出一次
+    #[serde(default = "default_progress_log_every_chars")]
+    pub progress_log_every_chars: u32,
+    /// 调试日志中输入进度最多每隔多少毫秒输出一次
+    #[serde(default = "default_progress_log_interval_ms")]
+    pub progress_log_interval_ms: u64,
     /// 界面语言
     #[serde(default = "default_language")]
     pub language: String,
+    /// 自定义中文字体文件路径，为空时自动查找系统字体
+    #[serde(default)]
+    pub cjk_font_path: String,
+    /// 剪贴板格式读取优先级（纯文本始终作为最终回退）
+    #[serde(default = "ClipboardFormat::default_priority")]
+    pub clipboard_format_priority: Vec<ClipboardFormat>,
+    /// 剪贴板含有 HTML 时转换为 Markdown，保留列表、标题等结构
+    #[serde(default)]
+    pub markdown_mode: bool,
+    /// 按来源程序过滤剪贴板捕获（目前仅 Windows 支持）
+    #[serde(default)]
+    pub source_filter: SourceFilter,
+    /// 这些程序在前台时不捕获剪贴板变化（可执行文件名，不区分大小写，目前仅 Windows 支持）
+    #[serde(default)]
+    pub capture_blocklist: Vec<String>,
+    /// 只在指定时间段内启用
+    #[serde(default)]
+    pub schedule: Schedule,
 }
 
 fn default_typing_delay() -> u64 {
     20 // 默认稍微带点延迟，更像人
 }
 
+fn default_target_wpm() -> u32 {
+    60
+}
+
 fn default_typing_variance() -> u64 {
     0
 }
@@ -80,33 +682,179 @@ fn default_language() -> String {
     "zh-CN".to_string()
 }
 
+fn default_split_long_lines_at() -> usize {
+    200
+}
+
+fn default_modifier_release_timeout_ms() -> u64 {
+    1000
+}
+
+fn default_preprocess_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_watch_file_debounce_ms() -> u64 {
+    500
+}
+
+fn default_double_cancel_quit_ms() -> u64 {
+    400
+}
+
+fn default_window_opacity() -> f32 {
+    1.0
+}
+
+fn default_battery_poll_interval_ms() -> u64 {
+    2000
+}
+
+fn default_click_wait_timeout_ms() -> u64 {
+    10000
+}
+
 fn default_history_max_items() -> u32 {
     20
 }
 
+fn default_hotkey_init_attempts() -> u32 {
+    4
+}
+
+fn default_hotkey_init_backoff_ms() -> u64 {
+    250
+}
+
+fn default_clipboard_init_attempts() -> u32 {
+    4
+}
+
+fn default_clipboard_init_backoff_ms() -> u64 {
+    250
+}
+
+fn default_accumulate_separator() -> String {
+    "\\n".to_string()
+}
+
+fn default_progress_log_every_chars() -> u32 {
+    100
+}
+
+fn default_progress_log_interval_ms() -> u64 {
+    1000
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn serialize_current_os<S: Serializer>(_: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(std::env::consts::OS)
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             close_action: CloseAction::MinimizeToTray,
             auto_start: false,
             start_minimized: false,
+            start_enabled: true,
+            persist_enabled_state: false,
             autostart_asked: false,
+            onboarded: false,
             show_console: false,
+            dock_corner: None,
+            mini_mode: false,
+            tray_menu: TrayMenuEntry::default_menu(),
+            window_opacity: default_window_opacity(),
             typing_delay: default_typing_delay(),
+            delay_from_wpm: false,
+            target_wpm: default_target_wpm(),
             typing_variance: default_typing_variance(),
             typing_variance_enabled: false,
+            variance_seed: None,
+            line_delay_ms: 0,
+            split_long_lines: false,
+            split_long_lines_at: default_split_long_lines_at(),
+            char_class_delays: CharClassDelays::default(),
+            modifier_release_timeout_ms: default_modifier_release_timeout_ms(),
+            min_enforced_delay_ms: 0,
+            max_type_duration_ms: 0,
+            post_type_cooldown_ms: 0,
+            key_tokens_enabled: false,
+            column_paste: false,
+            line_by_line: false,
+            emoji_mode: EmojiMode::default(),
+            paste_rich: false,
+            transform: TransformConfig::default(),
+            block_password_fields: false,
+            wait_for_click_before_type: false,
+            click_wait_timeout_ms: default_click_wait_timeout_ms(),
+            refocus_external_window: true,
+            auto_hide_after_type: false,
+            refocus_self_after_type: false,
+            restore_clipboard_to_typed: false,
+            confirm_edge_whitespace: false,
+            confirm_shell_commands: true,
+            confirm_cross_app: false,
+            preprocess_command: String::new(),
+            preprocess_timeout_ms: default_preprocess_timeout_ms(),
+            watch_file: None,
+            watch_file_confirm: true,
+            watch_file_debounce_ms: default_watch_file_debounce_ms(),
+            notify_on_capture: false,
+            capture_on_startup: true,
+            capture_selection: CaptureSelection::default(),
+            battery_poll_interval_ms: default_battery_poll_interval_ms(),
+            non_text_clipboard: NonTextClipboard::default(),
+            accumulate_separator: default_accumulate_separator(),
+            on_session_lock: SessionLockAction::default(),
+            sound_cues: false,
+            sound_cue_events: SoundCueEvents::default(),
             history_enabled: false,
             history_max_items: default_history_max_items(),
+            persist_history: false,
+            encrypt_history: false,
+            history_sort: HistorySort::default(),
+            pause_monitor_while_typing: true,
             hotkey: HotkeyConfig::default(),
+            hotkey_enabled: true,
+            mouse_trigger: None,
+            main_hotkey_action: MainHotkeyAction::default(),
+            paused_hotkey_action: PausedHotkeyAction::default(),
+            double_cancel_quit_ms: default_double_cancel_quit_ms(),
+            action_hotkeys: BTreeMap::new(),
+            macros: Vec::new(),
+            saved_on_os: String::new(),
+            warn_meta_platform_change: true,
+            hotkey_init_attempts: default_hotkey_init_attempts(),
+            clipboard_init_attempts: default_clipboard_init_attempts(),
+            clipboard_init_backoff_ms: default_clipboard_init_backoff_ms(),
+            progress_log_every_chars: default_progress_log_every_chars(),
+            progress_log_interval_ms: default_progress_log_interval_ms(),
+            hotkey_init_backoff_ms: default_hotkey_init_backoff_ms(),
             language: default_language(),
+            cjk_font_path: String::new(),
+            clipboard_format_priority: ClipboardFormat::default_priority(),
+            markdown_mode: false,
+            source_filter: SourceFilter::default(),
+            capture_blocklist: Vec::new(),
+            schedule: Schedule::default(),
         }
     }
 }
 
 impl AppConfig {
+    /// 获取配置目录（配置、历史、便签等文件都保存在这里）
+    pub fn config_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("copy-type"))
+    }
+
     /// 获取配置文件路径
-    fn config_path() -> Option<PathBuf> {
-        dirs::config_dir().map(|p| p.join("copy-type").join("config.json"))
+    pub fn config_path() -> Option<PathBuf> {
+        Self::config_dir().map(|p| p.join("config.json"))
     }
 
     /// 从文件加载配置
@@ -131,11 +879,90 @@ impl AppConfig {
         Ok(())
     }
 
+    /// 配置在另一种 Meta 键含义不同的系统上保存过，且有快捷键使用了 Meta 键时，返回保存时的系统
+    ///
+    /// macOS 上 Meta 对应 ⌘ 键，Windows 和 Linux 上对应 Win/Super 键，两者在键盘上的位置不同。
+    pub fn meta_platform_change(&self) -> Option<&str> {
+        let saved_on_macos = self.saved_on_os == "macos";
+        let running_on_macos = std::env::consts::OS == "macos";
+        if !self.warn_meta_platform_change
+            || self.saved_on_os.is_empty()
+            || saved_on_macos == running_on_macos
+        {
+            return None;
+        }
+
+        let uses_meta = self.hotkey.meta
+            || self.action_hotkeys.values().any(|hotkey| hotkey.meta)
+            || self
+                .macros
+                .iter()
+                .any(|m| m.hotkey.as_ref().is_some_and(|hotkey| hotkey.meta));
+        uses_meta.then_some(self.saved_on_os.as_str())
+    }
+
+    /// 按目标 WPM 计算每个字符的按键延迟 (毫秒)，按每个单词 5 个字符计算
+    pub fn wpm_to_delay_ms(wpm: u32) -> u64 {
+        60000 / (wpm.max(1) as u64 * 5)
+    }
+
     fn normalize(&mut self) {
+        self.target_wpm = self.target_wpm.clamp(1, 1000);
+        if self.delay_from_wpm {
+            self.typing_delay = Self::wpm_to_delay_ms(self.target_wpm);
+        }
+
+        self.split_long_lines_at = self.split_long_lines_at.max(1);
+        self.preprocess_timeout_ms = self.preprocess_timeout_ms.clamp(100, 60000);
+        self.modifier_release_timeout_ms = self.modifier_release_timeout_ms.min(10000);
+        self.watch_file_debounce_ms = self.watch_file_debounce_ms.clamp(100, 10000);
+        self.double_cancel_quit_ms = self.double_cancel_quit_ms.min(2000);
+        self.battery_poll_interval_ms = self.battery_poll_interval_ms.clamp(500, 60000);
+        self.post_type_cooldown_ms = self.post_type_cooldown_ms.min(60000);
+        // 输入后隐藏窗口与切回本程序互斥，以隐藏为准
+        if self.auto_hide_after_type {
+            self.refocus_self_after_type = false;
+        }
+        self.click_wait_timeout_ms = self.click_wait_timeout_ms.clamp(1000, 120000);
+        self.window_opacity = if self.window_opacity.is_finite() {
+            self.window_opacity.clamp(MIN_WINDOW_OPACITY, 1.0)
+        } else {
+            default_window_opacity()
+        };
+
+        for window in &mut self.schedule.windows {
+            window.start_minute = window.start_minute.min(MINUTES_PER_DAY - 1);
+            window.end_minute = window.end_minute.min(MINUTES_PER_DAY - 1);
+        }
+
         if self.history_max_items == 0 {
             self.history_max_items = default_history_max_items();
-        } else if self.history_max_items > 100 {
-            self.history_max_items = 100;
+        } else if self.history_max_items > HISTORY_MAX_ITEMS_LIMIT {
+            self.history_max_items = HISTORY_MAX_ITEMS_LIMIT;
+        }
+
+        // 去除重复的格式，保留首次出现的位置
+        let mut seen = Vec::new();
+        self.clipboard_format_priority.retain(|format| {
+            if seen.contains(format) {
+                false
+            } else {
+                seen.push(*format);
+                true
+            }
+        });
+        if self.clipboard_format_priority.is_empty() {
+            self.clipboard_format_priority = ClipboardFormat::default_priority();
         }
+
+        let mut seen = Vec::new();
+        self.tray_menu.retain(|entry| {
+            if seen.contains(entry) {
+                false
+            } else {
+                seen.push(*entry);
+                true
+            }
+        });
     }
 }
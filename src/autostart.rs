@@ -0,0 +1,187 @@
+//! 开机自启模块
+//!
+//! Windows 写入当前用户的 Run 注册表项，Linux 在 XDG autostart 目录中创建 .desktop 文件，
+//! macOS 在 ~/Library/LaunchAgents 中创建 plist。
+
+use std::error::Error;
+
+/// 开启或关闭开机自启
+pub fn set_enabled(enabled: bool) -> Result<(), Box<dyn Error>> {
+    if enabled {
+        platform::register()
+    } else {
+        platform::unregister()
+    }
+}
+
+/// 系统中当前是否已注册开机自启
+///
+/// 注册表项或启动文件可能被其他程序或用户删除，因此配置中的开关不一定与实际状态一致。
+pub fn is_registered() -> Result<bool, Box<dyn Error>> {
+    platform::is_registered()
+}
+
+/// 当前可执行文件路径
+fn current_exe() -> Result<String, Box<dyn Error>> {
+    Ok(std::env::current_exe()?.to_string_lossy().into_owned())
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::current_exe;
+    use std::error::Error;
+    use windows::core::{w, PCWSTR};
+    use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+    use windows::Win32::System::Registry::{
+        RegCloseKey, RegDeleteValueW, RegOpenKeyExW, RegQueryValueExW, RegSetValueExW, HKEY,
+        HKEY_CURRENT_USER, KEY_QUERY_VALUE, KEY_SET_VALUE, REG_SAM_FLAGS, REG_SZ,
+    };
+
+    const RUN_KEY: PCWSTR = w!("Software\\Microsoft\\Windows\\CurrentVersion\\Run");
+    const VALUE_NAME: PCWSTR = w!("CopyType");
+
+    /// 打开 Run 注册表项，调用方负责关闭
+    fn open_run_key(access: REG_SAM_FLAGS) -> windows::core::Result<HKEY> {
+        let mut key = HKEY::default();
+        unsafe { RegOpenKeyExW(HKEY_CURRENT_USER, RUN_KEY, 0, access, &mut key).ok()? };
+        Ok(key)
+    }
+
+    pub fn register() -> Result<(), Box<dyn Error>> {
+        let command = format!("\"{}\"", current_exe()?);
+        // REG_SZ 数据为以 NUL 结尾的 UTF-16
+        let data: Vec<u8> = command
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+
+        let key = open_run_key(KEY_SET_VALUE)?;
+        let result = unsafe { RegSetValueExW(key, VALUE_NAME, 0, REG_SZ, Some(&data)) };
+        unsafe {
+            let _ = RegCloseKey(key);
+        }
+        result.ok()?;
+        Ok(())
+    }
+
+    pub fn unregister() -> Result<(), Box<dyn Error>> {
+        let key = open_run_key(KEY_SET_VALUE)?;
+        let result = unsafe { RegDeleteValueW(key, VALUE_NAME) };
+        unsafe {
+            let _ = RegCloseKey(key);
+        }
+        if result != ERROR_FILE_NOT_FOUND {
+            result.ok()?;
+        }
+        Ok(())
+    }
+
+    pub fn is_registered() -> Result<bool, Box<dyn Error>> {
+        let key = open_run_key(KEY_QUERY_VALUE)?;
+        let result = unsafe { RegQueryValueExW(key, VALUE_NAME, None, None, None, None) };
+        unsafe {
+            let _ = RegCloseKey(key);
+        }
+        if result == ERROR_FILE_NOT_FOUND {
+            return Ok(false);
+        }
+        result.ok()?;
+        Ok(true)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::current_exe;
+    use std::error::Error;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn desktop_file_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("autostart").join("copy-type.desktop"))
+    }
+
+    pub fn register() -> Result<(), Box<dyn Error>> {
+        let path = desktop_file_path().ok_or("config directory not found")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName=Copy&Type\nExec=\"{}\"\nIcon=copy-type\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+            current_exe()?
+        );
+        fs::write(&path, content)?;
+        Ok(())
+    }
+
+    pub fn unregister() -> Result<(), Box<dyn Error>> {
+        if let Some(path) = desktop_file_path().filter(|p| p.exists()) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_registered() -> Result<bool, Box<dyn Error>> {
+        let path = desktop_file_path().ok_or("config directory not found")?;
+        Ok(path.exists())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::current_exe;
+    use std::error::Error;
+    use std::fs;
+    use std::path::PathBuf;
+
+    const LABEL: &str = "com.cncoresteb.copy-type";
+
+    fn plist_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|p| {
+            p.join("Library")
+                .join("LaunchAgents")
+                .join(format!("{}.plist", LABEL))
+        })
+    }
+
+    pub fn register() -> Result<(), Box<dyn Error>> {
+        let path = plist_path().ok_or("home directory not found")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            LABEL,
+            current_exe()?
+        );
+        fs::write(&path, content)?;
+        Ok(())
+    }
+
+    pub fn unregister() -> Result<(), Box<dyn Error>> {
+        if let Some(path) = plist_path().filter(|p| p.exists()) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_registered() -> Result<bool, Box<dyn Error>> {
+        let path = plist_path().ok_or("home directory not found")?;
+        Ok(path.exists())
+    }
+}
@@ -0,0 +1,855 @@
+//! 剪贴板格式读取模块
+//!
+//! 按用户配置的格式优先级依次尝试读取剪贴板，取第一个能得到文本的格式，
+//! 纯文本始终作为最终回退。
+
+use arboard::Clipboard;
+use encoding_rs::Encoding;
+use serde::{Deserialize, Serialize};
+
+/// 可读取的剪贴板格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClipboardFormat {
+    /// 富文本 (RTF)，提取其中的纯文本，目前仅 Windows 支持
+    Rtf,
+    /// HTML，去除标签后得到纯文本
+    Html,
+    /// 纯文本
+    PlainText,
+}
+
+impl ClipboardFormat {
+    /// 获取所有格式
+    pub fn all() -> Vec<ClipboardFormat> {
+        vec![
+            ClipboardFormat::Rtf,
+            ClipboardFormat::Html,
+            ClipboardFormat::PlainText,
+        ]
+    }
+
+    /// 默认优先级：仅读取纯文本，与旧版本行为一致
+    pub fn default_priority() -> Vec<ClipboardFormat> {
+        vec![ClipboardFormat::PlainText]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            ClipboardFormat::Rtf => "ui.app.clipboard_format_rtf",
+            ClipboardFormat::Html => "ui.app.clipboard_format_html",
+            ClipboardFormat::PlainText => "ui.app.clipboard_format_plain_text",
+        }
+    }
+}
+
+/// 要监控的选区（仅 Linux 区分剪贴板和主选区）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CaptureSelection {
+    /// 剪贴板（显式复制的内容）
+    #[default]
+    Clipboard,
+    /// 主选区（选中即复制、中键粘贴的内容）
+    Primary,
+    /// 同时监控两者
+    Both,
+}
+
+impl CaptureSelection {
+    /// 获取所有选项
+    pub fn all() -> Vec<CaptureSelection> {
+        vec![
+            CaptureSelection::Clipboard,
+            CaptureSelection::Primary,
+            CaptureSelection::Both,
+        ]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            CaptureSelection::Clipboard => "ui.app.capture_selection_clipboard",
+            CaptureSelection::Primary => "ui.app.capture_selection_primary",
+            CaptureSelection::Both => "ui.app.capture_selection_both",
+        }
+    }
+}
+
+/// 剪贴板中只有非文本内容时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NonTextClipboard {
+    /// 忽略，继续显示上次捕获的文本
+    #[default]
+    KeepLastText,
+    /// 提示剪贴板中是非文本内容
+    ShowNotice,
+}
+
+impl NonTextClipboard {
+    /// 获取所有选项
+    pub fn all() -> Vec<NonTextClipboard> {
+        vec![NonTextClipboard::KeepLastText, NonTextClipboard::ShowNotice]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            NonTextClipboard::KeepLastText => "ui.app.non_text_keep_last_text",
+            NonTextClipboard::ShowNotice => "ui.app.non_text_show_notice",
+        }
+    }
+}
+
+/// 无法作为文本读取的剪贴板内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonTextContent {
+    Image,
+    Files,
+}
+
+impl NonTextContent {
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            NonTextContent::Image => "ui.non_text_image",
+            NonTextContent::Files => "ui.non_text_files",
+        }
+    }
+}
+
+/// 检测剪贴板中的图片或文件，读取不到文本时调用
+pub fn detect_non_text(clipboard: &mut Clipboard) -> Option<NonTextContent> {
+    if clipboard.get().file_list().is_ok_and(|files| !files.is_empty()) {
+        return Some(NonTextContent::Files);
+    }
+    if clipboard.get_image().is_ok() {
+        return Some(NonTextContent::Image);
+    }
+    None
+}
+
+/// 按监控的选区读取文本
+///
+/// 同时监控剪贴板和主选区时，记录两者上次读到的内容，只返回发生了变化的一方，
+/// 避免两者内容不同时来回切换。
+#[derive(Default)]
+pub struct SelectionReader {
+    last_clipboard: Option<String>,
+    last_primary: Option<String>,
+}
+
+impl SelectionReader {
+    pub fn read(
+        &mut self,
+        clipboard: &mut Clipboard,
+        selection: CaptureSelection,
+        priority: &[ClipboardFormat],
+        markdown: bool,
+    ) -> Option<String> {
+        // 其他平台没有主选区，始终读取剪贴板
+        let selection = if cfg!(target_os = "linux") {
+            selection
+        } else {
+            CaptureSelection::Clipboard
+        };
+        match selection {
+            CaptureSelection::Clipboard => read_text_by_priority(clipboard, priority, markdown),
+            CaptureSelection::Primary => read_primary_selection(clipboard),
+            CaptureSelection::Both => {
+                let primary = read_primary_selection(clipboard).filter(|t| !t.is_empty());
+                if primary.is_some() && primary != self.last_primary {
+                    self.last_primary = primary.clone();
+                    return primary;
+                }
+                let text = read_text_by_priority(clipboard, priority, markdown);
+                if text != self.last_clipboard {
+                    self.last_clipboard = text.clone();
+                    return text;
+                }
+                None
+            }
+        }
+    }
+}
+
+/// 读取 X11/Wayland 的主选区文本
+#[cfg(target_os = "linux")]
+fn read_primary_selection(clipboard: &mut Clipboard) -> Option<String> {
+    use arboard::{GetExtLinux, LinuxClipboardKind};
+
+    clipboard.get().clipboard(LinuxClipboardKind::Primary).text().ok()
+}
+
+/// 读取主选区文本（当前平台不支持）
+#[cfg(not(target_os = "linux"))]
+fn read_primary_selection(_clipboard: &mut Clipboard) -> Option<String> {
+    None
+}
+
+/// 按优先级读取剪贴板文本
+///
+/// 依次尝试 `priority` 中的格式，返回第一个非空结果；都失败时回退到纯文本。
+/// `markdown` 为 true 时优先读取 HTML 并转换为 Markdown，保留列表、标题等结构。
+pub fn read_text_by_priority(
+    clipboard: &mut Clipboard,
+    priority: &[ClipboardFormat],
+    markdown: bool,
+) -> Option<String> {
+    if markdown {
+        if let Some(text) = clipboard
+            .get()
+            .html()
+            .ok()
+            .map(|html| html_to_markdown(&html))
+            .filter(|t| !t.is_empty())
+        {
+            return Some(text);
+        }
+    }
+
+    for format in priority {
+        let text = match format {
+            ClipboardFormat::Rtf => read_rtf(clipboard).map(|rtf| rtf_to_text(&rtf)),
+            ClipboardFormat::Html => clipboard.get().html().ok().map(|html| html_to_text(&html)),
+            ClipboardFormat::PlainText => clipboard.get_text().ok(),
+        };
+        if let Some(text) = text.filter(|t| !t.is_empty()) {
+            return Some(text);
+        }
+    }
+
+    clipboard.get_text().ok()
+}
+
+/// 剪贴板中是否带有 HTML 或 RTF 富文本格式
+pub fn has_rich_text(clipboard: &mut Clipboard) -> bool {
+    clipboard.get().html().is_ok_and(|html| !html.is_empty()) || read_rtf(clipboard).is_some()
+}
+
+/// 读取剪贴板中的原始 RTF 数据
+#[cfg(target_os = "windows")]
+fn read_rtf(_clipboard: &mut Clipboard) -> Option<String> {
+    use windows::core::w;
+    use windows::Win32::Foundation::{HGLOBAL, HWND};
+    use windows::Win32::System::DataExchange::{
+        CloseClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard,
+        RegisterClipboardFormatW,
+    };
+    use windows::Win32::System::Memory::{GlobalLock, GlobalSize, GlobalUnlock};
+
+    unsafe {
+        let format = RegisterClipboardFormatW(w!("Rich Text Format"));
+        if format == 0 || IsClipboardFormatAvailable(format).is_err() {
+            return None;
+        }
+        if OpenClipboard(HWND::default()).is_err() {
+            return None;
+        }
+
+        let mut result = None;
+        if let Ok(handle) = GetClipboardData(format) {
+            let hglobal = HGLOBAL(handle.0);
+            let ptr = GlobalLock(hglobal) as *const u8;
+            if !ptr.is_null() {
+                let size = GlobalSize(hglobal);
+                let bytes = std::slice::from_raw_parts(ptr, size);
+                // RTF 数据以 NUL 结尾
+                let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+                result = Some(String::from_utf8_lossy(&bytes[..end]).into_owned());
+                let _ = GlobalUnlock(hglobal);
+            }
+        }
+
+        let _ = CloseClipboard();
+        result
+    }
+}
+
+/// 读取剪贴板中的原始 RTF 数据（当前平台不支持）
+#[cfg(not(target_os = "windows"))]
+fn read_rtf(_clipboard: &mut Clipboard) -> Option<String> {
+    None
+}
+
+/// 在剪贴板现有内容之外再写入 RTF 数据，不清空其他格式
+#[cfg(target_os = "windows")]
+fn add_rtf(rtf: &str) -> bool {
+    use windows::core::w;
+    use windows::Win32::Foundation::{GlobalFree, HANDLE, HWND};
+    use windows::Win32::System::DataExchange::{
+        CloseClipboard, OpenClipboard, RegisterClipboardFormatW, SetClipboardData,
+    };
+    use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+
+    unsafe {
+        let format = RegisterClipboardFormatW(w!("Rich Text Format"));
+        if format == 0 || OpenClipboard(HWND::default()).is_err() {
+            return false;
+        }
+
+        let mut ok = false;
+        // RTF 数据以 NUL 结尾
+        if let Ok(hglobal) = GlobalAlloc(GMEM_MOVEABLE, rtf.len() + 1) {
+            let ptr = GlobalLock(hglobal) as *mut u8;
+            if !ptr.is_null() {
+                std::ptr::copy_nonoverlapping(rtf.as_ptr(), ptr, rtf.len());
+                *ptr.add(rtf.len()) = 0;
+                let _ = GlobalUnlock(hglobal);
+                // 写入成功后内存归系统所有，失败时需自行释放
+                ok = SetClipboardData(format, HANDLE(hglobal.0)).is_ok();
+            }
+            if !ok {
+                let _ = GlobalFree(hglobal);
+            }
+        }
+
+        let _ = CloseClipboard();
+        ok
+    }
+}
+
+/// 在剪贴板现有内容之外再写入 RTF 数据（当前平台不支持）
+#[cfg(not(target_os = "windows"))]
+fn add_rtf(_rtf: &str) -> bool {
+    false
+}
+
+/// 剪贴板中文本相关格式的快照，临时写入其他内容后用于恢复
+///
+/// 除纯文本外还保存 HTML 和 RTF（仅 Windows），恢复后富文本格式不会丢失。
+pub struct ClipboardSnapshot {
+    text: Option<String>,
+    html: Option<String>,
+    rtf: Option<String>,
+}
+
+impl ClipboardSnapshot {
+    /// 保存剪贴板当前的内容
+    pub fn take(clipboard: &mut Clipboard) -> Self {
+        Self {
+            text: clipboard.get_text().ok(),
+            html: clipboard.get().html().ok().filter(|html| !html.is_empty()),
+            rtf: read_rtf(clipboard),
+        }
+    }
+
+    /// 把保存的内容写回剪贴板，没有保存到任何内容时不做处理
+    pub fn restore(self, clipboard: &mut Clipboard) {
+        let restored = match (self.html, self.text) {
+            (Some(html), text) => clipboard.set_html(html, text).is_ok(),
+            (None, Some(text)) => clipboard.set_text(text).is_ok(),
+            (None, None) => false,
+        };
+        if restored {
+            if let Some(rtf) = self.rtf {
+                add_rtf(&rtf);
+            }
+        }
+    }
+}
+
+/// Windows 的 CF_HTML 可能带有片段标记，只取片段部分
+fn html_fragment(html: &str) -> &str {
+    match (html.find("<!--StartFragment-->"), html.find("<!--EndFragment-->")) {
+        (Some(start), Some(end)) if start < end => &html[start + "<!--StartFragment-->".len()..end],
+        _ => html,
+    }
+}
+
+/// 跳过 `<script>`、`<style>` 元素的内容，返回其结束标签之后的部分
+fn skip_raw_element<'a>(rest: &'a str, name: &str) -> &'a str {
+    let close = format!("</{}", name);
+    match rest.to_ascii_lowercase().find(&close) {
+        Some(pos) => {
+            let skipped = &rest[pos..];
+            skipped.find('>').map(|i| &skipped[i + 1..]).unwrap_or("")
+        }
+        None => "",
+    }
+}
+
+/// 将 HTML 转换为纯文本
+pub fn html_to_text(html: &str) -> String {
+    let html = html_fragment(html);
+
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(lt) = rest.find('<') {
+        out.push_str(&rest[..lt]);
+        let after = &rest[lt..];
+        let Some(gt) = after.find('>') else {
+            // 未闭合的标签，按原文保留
+            out.push_str(after);
+            rest = "";
+            break;
+        };
+
+        let tag = after[1..gt].trim().to_ascii_lowercase();
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        rest = &after[gt + 1..];
+
+        // 跳过脚本和样式内容
+        if !tag.starts_with('/') && (name == "script" || name == "style") {
+            rest = skip_raw_element(rest, name);
+            continue;
+        }
+
+        match name {
+            "br" => out.push('\n'),
+            "p" | "div" | "li" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" if tag.starts_with('/') => {
+                out.push('\n')
+            }
+            "td" | "th" if tag.starts_with('/') => out.push('\t'),
+            _ => {}
+        }
+    }
+    out.push_str(rest);
+
+    decode_html_entities(&out).trim_end_matches('\n').to_string()
+}
+
+/// 将 HTML 转换为 Markdown
+///
+/// 保留标题、列表、粗体/斜体、链接、代码和引用等常见结构，其余标签按纯文本处理。
+pub fn html_to_markdown(html: &str) -> String {
+    let html = html_fragment(html);
+
+    let mut out = String::with_capacity(html.len());
+    // 列表栈：None 为无序列表，Some(n) 为有序列表的下一个序号
+    let mut lists: Vec<Option<u32>> = Vec::new();
+    // 链接栈：保存每个 <a> 的 href，没有 href 的链接按纯文本输出
+    let mut links: Vec<Option<String>> = Vec::new();
+    let mut in_pre = false;
+    let mut rest = html;
+
+    while let Some(lt) = rest.find('<') {
+        push_markdown_text(&mut out, &rest[..lt], in_pre);
+        let after = &rest[lt..];
+        let Some(gt) = after.find('>') else {
+            push_markdown_text(&mut out, after, in_pre);
+            rest = "";
+            break;
+        };
+
+        let raw_tag = after[1..gt].trim();
+        let tag = raw_tag.to_ascii_lowercase();
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_string();
+        rest = &after[gt + 1..];
+
+        if !closing && (name == "script" || name == "style") {
+            rest = skip_raw_element(rest, &name);
+            continue;
+        }
+
+        match (name.as_str(), closing) {
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                start_block(&mut out);
+                let level = name[1..].parse().unwrap_or(1);
+                out.push_str(&"#".repeat(level));
+                out.push(' ');
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "div" | "table", true) => start_block(&mut out),
+            ("p" | "div" | "table", false) => start_block(&mut out),
+            ("br", _) => out.push('\n'),
+            ("tr", true) => start_line(&mut out),
+            ("td" | "th", true) => out.push('\t'),
+            ("ul", false) => {
+                start_line(&mut out);
+                lists.push(None);
+            }
+            ("ol", false) => {
+                start_line(&mut out);
+                lists.push(Some(1));
+            }
+            ("ul" | "ol", true) => {
+                lists.pop();
+                if lists.is_empty() {
+                    start_block(&mut out);
+                }
+            }
+            ("li", false) => {
+                start_line(&mut out);
+                out.push_str(&"  ".repeat(lists.len().saturating_sub(1)));
+                match lists.last_mut() {
+                    Some(Some(n)) => {
+                        out.push_str(&format!("{}. ", n));
+                        *n += 1;
+                    }
+                    _ => out.push_str("- "),
+                }
+            }
+            ("li", true) => start_line(&mut out),
+            ("strong" | "b", _) => out.push_str("**"),
+            ("em" | "i", _) => out.push('*'),
+            ("code", _) if !in_pre => out.push('`'),
+            ("pre", false) => {
+                start_block(&mut out);
+                out.push_str("```\n");
+                in_pre = true;
+            }
+            ("pre", true) => {
+                start_line(&mut out);
+                out.push_str("```");
+                start_block(&mut out);
+                in_pre = false;
+            }
+            ("blockquote", false) => {
+                start_block(&mut out);
+                out.push_str("> ");
+            }
+            ("blockquote", true) => start_block(&mut out),
+            ("a", false) => {
+                let href = attribute_value(raw_tag, "href");
+                if href.is_some() {
+                    out.push('[');
+                }
+                links.push(href);
+            }
+            ("a", true) => {
+                if let Some(Some(href)) = links.pop() {
+                    out.push_str(&format!("]({})", href));
+                }
+            }
+            _ => {}
+        }
+    }
+    push_markdown_text(&mut out, rest, in_pre);
+
+    // 合并多余的空行
+    let mut result = String::with_capacity(out.len());
+    let mut newlines = 0;
+    for c in out.chars() {
+        if c == '\n' {
+            newlines += 1;
+            if newlines > 2 {
+                continue;
+            }
+        } else {
+            newlines = 0;
+        }
+        result.push(c);
+    }
+    result.trim().to_string()
+}
+
+/// 向 Markdown 输出追加一段文本；`<pre>` 之外的连续空白折叠为一个空格
+fn push_markdown_text(out: &mut String, text: &str, in_pre: bool) {
+    let text = decode_html_entities(text);
+    if in_pre {
+        out.push_str(&text);
+        return;
+    }
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !out.is_empty() && !out.ends_with([' ', '\n']) {
+                out.push(' ');
+            }
+        } else {
+            out.push(c);
+        }
+    }
+}
+
+/// 确保从新的一行开始
+fn start_line(out: &mut String) {
+    while out.ends_with(' ') {
+        out.pop();
+    }
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// 确保与前面的内容之间有一个空行
+fn start_block(out: &mut String) {
+    start_line(out);
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+/// 读取标签中指定属性的值
+fn attribute_value(tag: &str, attr: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let pattern = format!("{}=", attr);
+    let mut search_from = 0;
+    while let Some(pos) = lower[search_from..].find(&pattern) {
+        let start = search_from + pos;
+        search_from = start + pattern.len();
+        // 确保匹配的是完整的属性名
+        if start > 0 && !lower[..start].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let value = &tag[search_from..];
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or(""),
+            _ => value.split(char::is_whitespace).next().unwrap_or(""),
+        };
+        return Some(decode_html_entities(value)).filter(|v| !v.is_empty());
+    }
+    None
+}
+
+/// 解码常见的 HTML 实体
+fn decode_html_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        let after = &rest[amp..];
+        let decoded = after.find(';').filter(|&semi| semi <= 10).and_then(|semi| {
+            let entity = &after[1..semi];
+            let ch = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                    u32::from_str_radix(&entity[2..], 16).ok().and_then(char::from_u32)
+                }
+                _ if entity.starts_with('#') => {
+                    entity[1..].parse::<u32>().ok().and_then(char::from_u32)
+                }
+                _ => None,
+            };
+            ch.map(|c| (c, semi))
+        });
+
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &after[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &after[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// RTF 组的状态，进入子组时继承，离开时恢复
+#[derive(Clone, Copy)]
+struct RtfGroup {
+    /// 是否处于不含正文的目标组中
+    skip: bool,
+    /// `\ucN`：每个 `\uN` 之后的替代字符个数
+    uc: usize,
+}
+
+/// 根据 `\ansicpg` 指定的代码页选择编码，无法识别时按 Windows-1252 处理
+fn rtf_codepage_encoding(codepage: i32) -> &'static Encoding {
+    match codepage {
+        932 => encoding_rs::SHIFT_JIS,
+        936 => encoding_rs::GBK,
+        949 => encoding_rs::EUC_KR,
+        950 => encoding_rs::BIG5,
+        874 => encoding_rs::WINDOWS_874,
+        65001 => encoding_rs::UTF_8,
+        1250..=1258 => Encoding::for_label(format!("windows-{}", codepage).as_bytes())
+            .unwrap_or(encoding_rs::WINDOWS_1252),
+        _ => encoding_rs::WINDOWS_1252,
+    }
+}
+
+/// 从 RTF 中提取纯文本
+///
+/// 只处理常见的控制字和转义，足以应对一般富文本编辑器复制的内容。
+/// 连续的 `\'xx` 按 `\ansicpg` 的代码页解码，`\uN` 之后按 `\ucN` 跳过替代字符。
+pub fn rtf_to_text(rtf: &str) -> String {
+    // 这些目标组中的内容不是正文
+    const SKIP_DESTINATIONS: &[&str] = &[
+        "fonttbl", "colortbl", "stylesheet", "info", "pict", "header", "footer",
+        "generator", "listtable", "listoverridetable", "themedata", "datastore",
+    ];
+
+    let chars: Vec<char> = rtf.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    let mut groups = vec![RtfGroup { skip: false, uc: 1 }];
+    let mut encoding = encoding_rs::WINDOWS_1252;
+    // 尚未解码的 \'xx 字节，多字节编码的一个字符由多个转义组成
+    let mut bytes: Vec<u8> = Vec::new();
+    // \uN 之后还需跳过的替代字符个数
+    let mut fallback = 0;
+
+    while i < chars.len() {
+        let group = *groups.last().unwrap_or(&RtfGroup { skip: false, uc: 1 });
+        let is_hex_escape = chars[i] == '\\' && chars.get(i + 1) == Some(&'\'');
+        if !is_hex_escape && !bytes.is_empty() {
+            out.push_str(&encoding.decode_without_bom_handling(&bytes).0);
+            bytes.clear();
+        }
+        match chars[i] {
+            '{' => {
+                groups.push(group);
+                fallback = 0;
+                i += 1;
+            }
+            '}' => {
+                if groups.len() > 1 {
+                    groups.pop();
+                }
+                fallback = 0;
+                i += 1;
+            }
+            '\\' => {
+                i += 1;
+                let Some(&next) = chars.get(i) else { break };
+                if next.is_ascii_alphabetic() {
+                    let start = i;
+                    while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                        i += 1;
+                    }
+                    let word: String = chars[start..i].iter().collect();
+                    let num_start = i;
+                    if i < chars.len() && (chars[i] == '-' || chars[i].is_ascii_digit()) {
+                        i += 1;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                    let param: Option<i32> = chars[num_start..i].iter().collect::<String>().parse().ok();
+                    // 控制字后的一个空格是分隔符
+                    if i < chars.len() && chars[i] == ' ' {
+                        i += 1;
+                    }
+                    fallback = 0;
+
+                    if SKIP_DESTINATIONS.contains(&word.as_str()) {
+                        if let Some(top) = groups.last_mut() {
+                            top.skip = true;
+                        }
+                        continue;
+                    }
+                    match word.as_str() {
+                        "ansicpg" => {
+                            if let Some(codepage) = param {
+                                encoding = rtf_codepage_encoding(codepage);
+                            }
+                        }
+                        "uc" => {
+                            if let Some(top) = groups.last_mut() {
+                                top.uc = param.unwrap_or(1).max(0) as usize;
+                            }
+                        }
+                        _ if group.skip => {}
+                        "par" | "line" => out.push('\n'),
+                        "tab" => out.push('\t'),
+                        "u" => {
+                            if let Some(c) = param
+                                .map(|p| (if p < 0 { p + 65536 } else { p }) as u32)
+                                .and_then(char::from_u32)
+                            {
+                                out.push(c);
+                            }
+                            // 跳过紧随其后的替代字符（单个字符或 \'xx 转义）
+                            fallback = group.uc;
+                        }
+                        _ => {}
+                    }
+                } else if next == '*' {
+                    // \* 开头的目标组均为可忽略的扩展
+                    if let Some(top) = groups.last_mut() {
+                        top.skip = true;
+                    }
+                    i += 1;
+                } else if next == '\'' {
+                    let hex: String = chars.iter().skip(i + 1).take(2).collect();
+                    if fallback > 0 {
+                        fallback -= 1;
+                    } else if !group.skip {
+                        if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+                            bytes.push(byte);
+                        }
+                    }
+                    i += 3;
+                } else {
+                    if !group.skip {
+                        match next {
+                            '\\' | '{' | '}' => out.push(next),
+                            '~' => out.push(' '),
+                            '\n' | '\r' => out.push('\n'),
+                            _ => {}
+                        }
+                    }
+                    fallback = 0;
+                    i += 1;
+                }
+            }
+            '\r' | '\n' => i += 1,
+            c => {
+                if fallback > 0 {
+                    fallback -= 1;
+                } else if !group.skip {
+                    out.push(c);
+                }
+                i += 1;
+            }
+        }
+    }
+    if !bytes.is_empty() {
+        out.push_str(&encoding.decode_without_bom_handling(&bytes).0);
+    }
+
+    out.trim_end_matches('\n').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rtf_plain_paragraphs() {
+        let rtf = r"{\rtf1\ansi{\fonttbl{\f0 Arial;}}\f0 Hello\par World\tab!}";
+        assert_eq!(rtf_to_text(rtf), "Hello\nWorld\t!");
+    }
+
+    #[test]
+    fn rtf_unicode_skips_default_fallback() {
+        let rtf = r"{\rtf1\ansi \u8220?quoted\u8221?}";
+        assert_eq!(rtf_to_text(rtf), "\u{201C}quoted\u{201D}");
+    }
+
+    #[test]
+    fn rtf_word_cjk_skips_uc2_fallback_bytes() {
+        // Word 复制中文时的写法：\uc2 后每个 \uN 跟两个 GBK 字节作为替代
+        let rtf = r"{\rtf1\ansi\ansicpg936\deff0{\fonttbl{\f0\fnil\fcharset134 \'cb\'ce\'cc\'e5;}}
+{\*\generator Microsoft Word;}\uc2\pard\f0\lang2052 \u20013\'d6\'d0\u25991\'ce\'c4 text\par}";
+        assert_eq!(rtf_to_text(rtf), "中文 text");
+    }
+
+    #[test]
+    fn rtf_uc_is_scoped_to_group() {
+        let rtf = r"{\rtf1\ansi\ansicpg936{\uc2\u20013\'d6\'d0}\u25991?}";
+        assert_eq!(rtf_to_text(rtf), "中文");
+    }
+
+    #[test]
+    fn rtf_hex_escapes_use_ansicpg() {
+        let rtf = r"{\rtf1\ansi\ansicpg936 \'d6\'d0\'ce\'c4}";
+        assert_eq!(rtf_to_text(rtf), "中文");
+    }
+
+    #[test]
+    fn rtf_hex_escapes_default_to_windows_1252() {
+        let rtf = r"{\rtf1\ansi caf\'e9 \'93hi\'94}";
+        assert_eq!(rtf_to_text(rtf), "café \u{201C}hi\u{201D}");
+    }
+}
@@ -0,0 +1,160 @@
+//! 文件监视模块
+//!
+//! 定期检查指定文件的修改时间和大小，内容变化且在防抖时间内不再变化后读取文件，
+//! 供自动化工具通过写入文件驱动输入。
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// 用于判断文件是否变化的标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl Stamp {
+    /// 读取文件当前的标识，文件不存在或无法访问时返回 None
+    fn of(path: &Path) -> Option<Stamp> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Stamp {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
+
+/// 对单个文件的监视
+pub struct FileWatch {
+    path: PathBuf,
+    /// 上次读取时的标识
+    last: Option<Stamp>,
+    /// 检测到但尚未稳定的变化及其开始时间
+    pending: Option<(Stamp, Instant)>,
+}
+
+impl FileWatch {
+    /// 开始监视 `path`，开始时已有的内容不会触发
+    pub fn new(path: PathBuf) -> Self {
+        let last = Stamp::of(&path);
+        Self {
+            path,
+            last,
+            pending: None,
+        }
+    }
+
+    /// 被监视的文件
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 检查文件是否变化，变化后在 `debounce` 时间内不再变化时返回新的内容
+    ///
+    /// 写入方分多次写入时只在写完后触发一次。
+    pub fn poll(&mut self, debounce: Duration) -> Option<io::Result<String>> {
+        let current = Stamp::of(&self.path);
+        if current == self.last {
+            self.pending = None;
+            return None;
+        }
+        let Some(current) = current else {
+            // 文件被删除，重新创建后再触发
+            self.last = None;
+            self.pending = None;
+            return None;
+        };
+        match self.pending {
+            Some((pending, since)) if pending == current => {
+                if since.elapsed() < debounce {
+                    return None;
+                }
+            }
+            _ => {
+                self.pending = Some((current, Instant::now()));
+                return None;
+            }
+        }
+        self.pending = None;
+        self.last = Some(current);
+        Some(fs::read_to_string(&self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    const DEBOUNCE: Duration = Duration::from_millis(50);
+
+    /// 写入内容，并等待足够久以确保修改时间变化
+    fn write(path: &Path, text: &str) {
+        thread::sleep(Duration::from_millis(20));
+        fs::write(path, text).unwrap();
+    }
+
+    /// 反复检查直到触发或超时
+    fn poll_until_settled(watch: &mut FileWatch) -> Option<String> {
+        let deadline = Instant::now() + DEBOUNCE * 10;
+        while Instant::now() < deadline {
+            if let Some(result) = watch.poll(DEBOUNCE) {
+                return Some(result.unwrap());
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        None
+    }
+
+    #[test]
+    fn content_present_at_start_does_not_trigger() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("watch.txt");
+        fs::write(&path, "already here").unwrap();
+
+        let mut watch = FileWatch::new(path);
+        assert_eq!(poll_until_settled(&mut watch), None);
+    }
+
+    #[test]
+    fn burst_of_writes_triggers_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("watch.txt");
+        fs::write(&path, "start").unwrap();
+        let mut watch = FileWatch::new(path.clone());
+
+        for text in ["a", "ab", "abc", "abcd"] {
+            write(&path, text);
+            assert!(watch.poll(DEBOUNCE).is_none());
+        }
+        assert_eq!(poll_until_settled(&mut watch).as_deref(), Some("abcd"));
+        assert_eq!(poll_until_settled(&mut watch), None);
+    }
+
+    #[test]
+    fn delete_then_recreate_triggers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("watch.txt");
+        fs::write(&path, "first").unwrap();
+        let mut watch = FileWatch::new(path.clone());
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(poll_until_settled(&mut watch), None);
+
+        write(&path, "second");
+        assert_eq!(poll_until_settled(&mut watch).as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn missing_file_triggers_when_created() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("watch.txt");
+        let mut watch = FileWatch::new(path.clone());
+        assert_eq!(poll_until_settled(&mut watch), None);
+
+        write(&path, "created");
+        assert_eq!(poll_until_settled(&mut watch).as_deref(), Some("created"));
+    }
+}
@@ -0,0 +1,200 @@
+//! 剪贴板历史模块
+//!
+//! 定义历史记录条目及排序方式，并负责把历史保存到配置目录下的 history.json，
+//! 使使用次数等信息在重启后仍然保留。开启加密时改为保存到 history.enc，见 [`crate::history_crypto`]。
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use crate::app_config::AppConfig;
+use crate::history_crypto::{self, CryptoError, HistoryKey};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 一条剪贴板历史记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// 记录的文本
+    pub text: String,
+    /// 从历史中输入的次数
+    #[serde(default)]
+    pub count: u32,
+    /// 记录时间 (Unix 时间戳，秒)
+    pub recorded_at: i64,
+    /// 复制来源程序的可执行文件名（目前仅 Windows 可获取），导入的片段为 None
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn new(text: String, source: Option<String>) -> Self {
+        Self {
+            text,
+            count: 0,
+            recorded_at: Local::now().timestamp(),
+            source,
+        }
+    }
+
+    /// 文本的字符数
+    pub fn char_count(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// 距离记录时间已经过去的时长
+    pub fn age(&self) -> Duration {
+        let seconds = Local::now().timestamp() - self.recorded_at;
+        Duration::from_secs(seconds.max(0) as u64)
+    }
+
+    /// 用于显示的记录时间，当天的记录只显示时分秒
+    pub fn copied_at(&self) -> String {
+        let Some(time) = Local.timestamp_opt(self.recorded_at, 0).single() else {
+            return String::new();
+        };
+        if time.date_naive() == Local::now().date_naive() {
+            time.format("%H:%M:%S").to_string()
+        } else {
+            time.format("%Y-%m-%d %H:%M").to_string()
+        }
+    }
+}
+
+/// 历史列表的排序方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HistorySort {
+    /// 最近记录的在前
+    #[default]
+    Recent,
+    /// 使用次数多的在前，次数相同时最近记录的在前
+    Frequency,
+}
+
+impl HistorySort {
+    /// 获取所有排序方式
+    pub fn all() -> Vec<HistorySort> {
+        vec![HistorySort::Recent, HistorySort::Frequency]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            HistorySort::Recent => "ui.history_sort_recent",
+            HistorySort::Frequency => "ui.history_sort_frequency",
+        }
+    }
+
+    /// 按排序方式返回条目的下标（历史列表按记录时间从旧到新存放）
+    pub fn order(&self, entries: &[HistoryEntry]) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..entries.len()).rev().collect();
+        if *self == HistorySort::Frequency {
+            // 稳定排序，次数相同的条目保持最近在前
+            indices.sort_by(|a, b| entries[*b].count.cmp(&entries[*a].count));
+        }
+        indices
+    }
+}
+
+/// 历史中出现过的来源程序，按名称排序并去重
+pub fn sources(entries: &[HistoryEntry]) -> Vec<String> {
+    let mut sources: Vec<String> = entries.iter().filter_map(|entry| entry.source.clone()).collect();
+    sources.sort();
+    sources.dedup();
+    sources
+}
+
+/// 解析要导入的片段
+///
+/// 以 `[` 开头的内容按 JSON 字符串数组解析，否则每个非空行作为一条片段。
+pub fn parse_snippets(content: &str) -> Result<Vec<String>, serde_json::Error> {
+    if content.trim_start().starts_with('[') {
+        let snippets: Vec<String> = serde_json::from_str(content)?;
+        Ok(snippets.into_iter().filter(|s| !s.is_empty()).collect())
+    } else {
+        Ok(content
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.to_string())
+            .collect())
+    }
+}
+
+/// 获取历史文件路径
+fn history_path() -> Option<PathBuf> {
+    AppConfig::config_dir().map(|p| p.join("history.json"))
+}
+
+/// 获取加密历史文件路径
+fn encrypted_history_path() -> Option<PathBuf> {
+    AppConfig::config_dir().map(|p| p.join("history.enc"))
+}
+
+/// 从文件加载历史，文件不存在或无法解析时返回空列表
+pub fn load() -> Vec<HistoryEntry> {
+    history_path()
+        .and_then(|path| fs::read_to_string(&path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 保存历史到文件
+pub fn save(entries: &[HistoryEntry]) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(path) = history_path() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string(entries)?;
+        fs::write(&path, content)?;
+    }
+    Ok(())
+}
+
+/// 用口令解密并加载加密的历史，返回历史和之后保存时使用的密钥
+///
+/// 文件不存在时返回空列表，并用该口令派生新的密钥。
+pub fn load_encrypted(passphrase: &str) -> Result<(Vec<HistoryEntry>, HistoryKey), CryptoError> {
+    let data = encrypted_history_path().and_then(|path| fs::read(path).ok());
+    let Some(data) = data else {
+        return Ok((Vec::new(), HistoryKey::derive_new(passphrase)?));
+    };
+    let (plaintext, key) = history_crypto::decrypt(passphrase, &data)?;
+    let entries = serde_json::from_slice(&plaintext).map_err(|_| CryptoError::InvalidFormat)?;
+    Ok((entries, key))
+}
+
+/// 加密保存历史到文件
+pub fn save_encrypted(entries: &[HistoryEntry], key: &HistoryKey) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(path) = encrypted_history_path() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_vec(entries)?;
+        fs::write(&path, history_crypto::encrypt(key, &content)?)?;
+    }
+    // 开启加密后删除明文的历史文件
+    if let Some(path) = history_path().filter(|p| p.exists()) {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// 删除加密的历史文件（关闭加密并已改为明文保存后调用）
+///
+/// 保存明文历史时不会自动删除，避免尚未解密的历史被丢弃。
+pub fn remove_encrypted() -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(path) = encrypted_history_path().filter(|p| p.exists()) {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// 删除历史文件（包括加密的历史）
+pub fn remove() -> Result<(), Box<dyn std::error::Error>> {
+    for path in [history_path(), encrypted_history_path()].into_iter().flatten() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
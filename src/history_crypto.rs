@@ -0,0 +1,142 @@
+//! 历史加密模块
+//!
+//! 开启加密后历史保存为 history.enc：用 Argon2id 从口令派生密钥，再用 XChaCha20-Poly1305 加密。
+//! 文件格式为 `魔数 | 盐值 | 随机数 | 密文`。加密依赖由 `encrypted-history` 特性引入，
+//! 未启用时无法加密或解密。
+
+use rand::RngCore;
+use std::fmt;
+
+/// 当前构建是否支持加密历史
+pub const SUPPORTED: bool = cfg!(feature = "encrypted-history");
+
+/// 文件开头的魔数，用于识别格式和版本
+const MAGIC: &[u8; 4] = b"CTH1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+
+/// 加密或解密失败的原因
+///
+/// 部分变体只在启用或未启用 `encrypted-history` 特性时出现。
+#[derive(Debug)]
+#[allow(dead_code)]
+pub enum CryptoError {
+    /// 当前构建未启用 `encrypted-history` 特性
+    Unsupported,
+    /// 口令错误（或文件已被篡改）
+    WrongPassphrase,
+    /// 文件格式无效
+    InvalidFormat,
+    /// 派生密钥失败
+    KeyDerivation,
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::Unsupported => write!(f, "history encryption is not supported by this build"),
+            CryptoError::WrongPassphrase => write!(f, "wrong passphrase"),
+            CryptoError::InvalidFormat => write!(f, "invalid encrypted history file"),
+            CryptoError::KeyDerivation => write!(f, "failed to derive key"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+/// 从口令派生的密钥及其盐值
+///
+/// 解锁后保留在内存中，之后每次保存只生成新的随机数，不必重新派生密钥。
+#[derive(Clone)]
+pub struct HistoryKey {
+    salt: [u8; SALT_LEN],
+    key: [u8; KEY_LEN],
+}
+
+impl HistoryKey {
+    /// 用新的随机盐值从口令派生密钥（设置新口令时使用）
+    pub fn derive_new(passphrase: &str) -> Result<Self, CryptoError> {
+        let mut salt = [0u8; SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self::derive(passphrase, salt)
+    }
+
+    fn derive(passphrase: &str, salt: [u8; SALT_LEN]) -> Result<Self, CryptoError> {
+        let key = imp::derive_key(passphrase, &salt)?;
+        Ok(Self { salt, key })
+    }
+}
+
+/// 加密数据，返回完整的文件内容
+pub fn encrypt(key: &HistoryKey, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = imp::seal(&key.key, &nonce, plaintext)?;
+
+    let mut data = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&key.salt);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+/// 用口令解密文件内容，返回数据和派生的密钥
+pub fn decrypt(passphrase: &str, data: &[u8]) -> Result<(Vec<u8>, HistoryKey), CryptoError> {
+    let rest = data.strip_prefix(MAGIC.as_slice()).ok_or(CryptoError::InvalidFormat)?;
+    if rest.len() < SALT_LEN + NONCE_LEN {
+        return Err(CryptoError::InvalidFormat);
+    }
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let key = HistoryKey::derive(passphrase, salt.try_into().expect("salt length checked"))?;
+    let plaintext = imp::open(&key.key, nonce.try_into().expect("nonce length checked"), ciphertext)?;
+    Ok((plaintext, key))
+}
+
+#[cfg(feature = "encrypted-history")]
+mod imp {
+    use super::{CryptoError, KEY_LEN, NONCE_LEN};
+    use argon2::Argon2;
+    use chacha20poly1305::aead::{Aead, KeyInit};
+    use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+    pub fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], CryptoError> {
+        let mut key = [0u8; KEY_LEN];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|_| CryptoError::KeyDerivation)?;
+        Ok(key)
+    }
+
+    pub fn seal(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        XChaCha20Poly1305::new(Key::from_slice(key))
+            .encrypt(XNonce::from_slice(nonce), plaintext)
+            .map_err(|_| CryptoError::InvalidFormat)
+    }
+
+    pub fn open(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], ciphertext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        XChaCha20Poly1305::new(Key::from_slice(key))
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| CryptoError::WrongPassphrase)
+    }
+}
+
+#[cfg(not(feature = "encrypted-history"))]
+mod imp {
+    use super::{CryptoError, KEY_LEN, NONCE_LEN};
+
+    pub fn derive_key(_passphrase: &str, _salt: &[u8]) -> Result<[u8; KEY_LEN], CryptoError> {
+        Err(CryptoError::Unsupported)
+    }
+
+    pub fn seal(_key: &[u8; KEY_LEN], _nonce: &[u8; NONCE_LEN], _plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        Err(CryptoError::Unsupported)
+    }
+
+    pub fn open(_key: &[u8; KEY_LEN], _nonce: &[u8; NONCE_LEN], _ciphertext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        Err(CryptoError::Unsupported)
+    }
+}
@@ -2,6 +2,7 @@
 
 use global_hotkey::hotkey::{Code, HotKey, Modifiers};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// 支持的按键列表
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -242,8 +243,203 @@ impl Default for KeyCode {
     }
 }
 
+/// 可以绑定独立快捷键的附加操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum HotkeyAction {
+    /// 重复上一次输入
+    RepeatLast,
+    /// 输入便签内容
+    TypeScratchpad,
+    /// 弹出历史选择列表
+    ShowPicker,
+    /// 开启或关闭累积模式
+    ToggleAccumulate,
+    /// 取消正在进行（包括已暂停）的输入
+    CancelTyping,
+}
+
+impl HotkeyAction {
+    /// 获取所有附加操作
+    pub fn all() -> Vec<HotkeyAction> {
+        vec![
+            HotkeyAction::RepeatLast,
+            HotkeyAction::TypeScratchpad,
+            HotkeyAction::ShowPicker,
+            HotkeyAction::ToggleAccumulate,
+            HotkeyAction::CancelTyping,
+        ]
+    }
+
+    /// 显示名称对应的翻译键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            HotkeyAction::RepeatLast => "ui.hotkey_action.repeat_last",
+            HotkeyAction::TypeScratchpad => "ui.hotkey_action.type_scratchpad",
+            HotkeyAction::ShowPicker => "ui.hotkey_action.show_picker",
+            HotkeyAction::ToggleAccumulate => "ui.hotkey_action.toggle_accumulate",
+            HotkeyAction::CancelTyping => "ui.hotkey_action.cancel_typing",
+        }
+    }
+
+    /// 新绑定时使用的默认快捷键
+    pub fn default_hotkey(&self) -> HotkeyConfig {
+        match self {
+            HotkeyAction::RepeatLast => HotkeyConfig {
+                ctrl: true,
+                shift: true,
+                alt: false,
+                meta: false,
+                key: KeyCode::R,
+            },
+            HotkeyAction::TypeScratchpad => HotkeyConfig {
+                ctrl: true,
+                shift: true,
+                alt: false,
+                meta: false,
+                key: KeyCode::Y,
+            },
+            HotkeyAction::ShowPicker => HotkeyConfig {
+                ctrl: true,
+                shift: true,
+                alt: false,
+                meta: false,
+                key: KeyCode::H,
+            },
+            HotkeyAction::ToggleAccumulate => HotkeyConfig {
+                ctrl: true,
+                shift: true,
+                alt: false,
+                meta: false,
+                key: KeyCode::A,
+            },
+            HotkeyAction::CancelTyping => HotkeyConfig {
+                ctrl: true,
+                shift: true,
+                alt: false,
+                meta: false,
+                key: KeyCode::X,
+            },
+        }
+    }
+}
+
+/// 两个使用了相同组合的快捷键，`None` 表示主快捷键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HotkeyConflict {
+    pub first: Option<HotkeyAction>,
+    pub second: Option<HotkeyAction>,
+}
+
+/// 检查主快捷键与所有附加操作快捷键两两之间是否重复，返回找到的第一组冲突
+pub fn find_conflict(
+    main: &HotkeyConfig,
+    actions: &BTreeMap<HotkeyAction, HotkeyConfig>,
+) -> Option<HotkeyConflict> {
+    let entries: Vec<(Option<HotkeyAction>, &HotkeyConfig)> = std::iter::once((None, main))
+        .chain(actions.iter().map(|(action, config)| (Some(*action), config)))
+        .collect();
+    for (i, (first, first_config)) in entries.iter().enumerate() {
+        for (second, second_config) in &entries[i + 1..] {
+            if first_config.conflicts_with(second_config) {
+                return Some(HotkeyConflict {
+                    first: *first,
+                    second: *second,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// 主快捷键（或绑定的鼠标按键）在未输入时执行的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MainHotkeyAction {
+    /// 输入剪贴板内容
+    #[default]
+    Type,
+    /// 复制前台程序中选中的内容到剪贴板，不进行输入
+    CopyToClipboard,
+    /// 弹出历史选择列表，选择后输入或复制
+    ShowPicker,
+}
+
+impl MainHotkeyAction {
+    /// 获取所有操作
+    pub fn all() -> Vec<MainHotkeyAction> {
+        vec![
+            MainHotkeyAction::Type,
+            MainHotkeyAction::CopyToClipboard,
+            MainHotkeyAction::ShowPicker,
+        ]
+    }
+
+    /// 显示名称对应的翻译键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            MainHotkeyAction::Type => "ui.main_hotkey_action.type",
+            MainHotkeyAction::CopyToClipboard => "ui.main_hotkey_action.copy_to_clipboard",
+            MainHotkeyAction::ShowPicker => "ui.main_hotkey_action.show_picker",
+        }
+    }
+}
+
+/// 输入暂停时再次按下主快捷键（或绑定的鼠标按键）执行的操作
+///
+/// 输入进行中按下主快捷键总是暂停输入；此设置只决定暂停后的再次按下是继续还是取消。
+/// 取消快捷键不受此设置影响，任何时候都会取消输入。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PausedHotkeyAction {
+    /// 继续输入
+    #[default]
+    Resume,
+    /// 取消输入
+    Cancel,
+}
+
+impl PausedHotkeyAction {
+    /// 获取所有操作
+    pub fn all() -> Vec<PausedHotkeyAction> {
+        vec![PausedHotkeyAction::Resume, PausedHotkeyAction::Cancel]
+    }
+
+    /// 显示名称对应的翻译键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            PausedHotkeyAction::Resume => "ui.paused_hotkey_action.resume",
+            PausedHotkeyAction::Cancel => "ui.paused_hotkey_action.cancel",
+        }
+    }
+}
+
+/// 可以代替快捷键触发输入的鼠标按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MouseTrigger {
+    /// 中键
+    Middle,
+    /// 侧键 1（通常为“后退”）
+    X1,
+    /// 侧键 2（通常为“前进”）
+    X2,
+}
+
+impl MouseTrigger {
+    /// 获取所有鼠标按键
+    pub fn all() -> Vec<MouseTrigger> {
+        vec![MouseTrigger::Middle, MouseTrigger::X1, MouseTrigger::X2]
+    }
+
+    /// 显示名称对应的翻译键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            MouseTrigger::Middle => "ui.mouse_trigger.middle",
+            MouseTrigger::X1 => "ui.mouse_trigger.x1",
+            MouseTrigger::X2 => "ui.mouse_trigger.x2",
+        }
+    }
+}
+
 /// 快捷键配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct HotkeyConfig {
     pub ctrl: bool,
     pub shift: bool,
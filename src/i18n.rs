@@ -55,6 +55,38 @@ impl I18n {
         })
     }
 
+    /// Translate a key whose wording depends on `count`, replacing `%{count}`.
+    ///
+    /// The key points to a table of plural forms (`one`, `other`); languages without
+    /// plural inflection such as `zh-CN` only define `other`.
+    pub fn tr_plural(&self, key: &str, count: u64) -> String {
+        let lang = self.current_language();
+        let form = format!("{}.{}", key, plural_form(&lang, count));
+        let key = if self.lookup(&lang, &form).is_some() {
+            form
+        } else {
+            format!("{}.other", key)
+        };
+        let count = count.to_string();
+        self.tr(&key, &[("count", count.as_str())])
+    }
+
+    /// Format an elapsed time in seconds as "just now" / "5 minutes ago" in the current language.
+    pub fn format_relative_time(&self, secs: u64) -> String {
+        const MINUTE: u64 = 60;
+        const HOUR: u64 = 60 * MINUTE;
+        const DAY: u64 = 24 * HOUR;
+        if secs < MINUTE {
+            self.t("time.just_now")
+        } else if secs < HOUR {
+            self.tr_plural("time.minutes_ago", secs / MINUTE)
+        } else if secs < DAY {
+            self.tr_plural("time.hours_ago", secs / HOUR)
+        } else {
+            self.tr_plural("time.days_ago", secs / DAY)
+        }
+    }
+
     /// List available languages `(code, display_name)`.
     pub fn available_languages(&self) -> Vec<(&'static str, &'static str)> {
         vec![("zh-CN", "简体中文"), ("en", "English")]
@@ -65,6 +97,14 @@ impl I18n {
     }
 }
 
+/// Plural form used by `lang` for `count`.
+fn plural_form(lang: &str, count: u64) -> &'static str {
+    match lang {
+        "en" if count == 1 => "one",
+        _ => "other",
+    }
+}
+
 fn load_store() -> HashMap<String, HashMap<String, String>> {
     let mut store = HashMap::new();
     store.insert(
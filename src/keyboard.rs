@@ -0,0 +1,149 @@
+//! 键盘后端模块
+//!
+//! 把模拟输入所需的操作抽象为 `KeyboardBackend`，输入流程只依赖该接口，
+//! 实际发送按键由 enigo 完成。
+
+use crate::clipboard_format::ClipboardSnapshot;
+use arboard::Clipboard;
+use enigo::{Direction, Enigo, InputError, InputResult, Key, Keyboard, NewConError, Settings};
+use std::thread;
+use std::time::Duration;
+
+/// 复制、粘贴使用的修饰键
+#[cfg(target_os = "macos")]
+const PASTE_MODIFIER: Key = Key::Meta;
+#[cfg(not(target_os = "macos"))]
+const PASTE_MODIFIER: Key = Key::Control;
+/// 粘贴后等待目标程序读取剪贴板的时间，之后再恢复原有内容
+const PASTE_RESTORE_DELAY: Duration = Duration::from_millis(50);
+
+/// 模拟键盘输入的后端
+pub trait KeyboardBackend {
+    /// 输入一段文本
+    fn text(&mut self, text: &str) -> InputResult<()>;
+    /// 按下并松开一个按键
+    fn key(&mut self, key: Key) -> InputResult<()>;
+    /// 通过剪贴板粘贴一段文本，完成后恢复原有剪贴板内容
+    fn paste(&mut self, text: &str) -> InputResult<()>;
+    /// 发送系统粘贴快捷键，直接粘贴剪贴板现有内容（保留富文本格式）
+    fn paste_clipboard(&mut self) -> InputResult<()>;
+    /// 发送系统复制快捷键，复制前台程序中选中的内容
+    fn copy_selection(&mut self) -> InputResult<()>;
+}
+
+/// 基于 enigo 的键盘后端
+pub struct EnigoBackend {
+    enigo: Enigo,
+}
+
+impl EnigoBackend {
+    pub fn new() -> Result<Self, NewConError> {
+        Ok(Self {
+            enigo: Enigo::new(&Settings::default())?,
+        })
+    }
+
+    /// 按下 Ctrl（macOS 为 ⌘）加指定字母
+    fn shortcut(&mut self, letter: char) -> InputResult<()> {
+        self.enigo.key(PASTE_MODIFIER, Direction::Press)?;
+        let result = self.enigo.key(Key::Unicode(letter), Direction::Click);
+        self.enigo.key(PASTE_MODIFIER, Direction::Release)?;
+        result
+    }
+}
+
+impl KeyboardBackend for EnigoBackend {
+    fn text(&mut self, text: &str) -> InputResult<()> {
+        self.enigo.text(text)
+    }
+
+    fn key(&mut self, key: Key) -> InputResult<()> {
+        self.enigo.key(key, Direction::Click)
+    }
+
+    fn paste(&mut self, text: &str) -> InputResult<()> {
+        let mut clipboard =
+            Clipboard::new().map_err(|_| InputError::Simulate("failed to open the clipboard"))?;
+        // 保存包括富文本格式在内的原有内容，粘贴完成或失败后都恢复
+        let previous = ClipboardSnapshot::take(&mut clipboard);
+        let result = clipboard
+            .set_text(text)
+            .map_err(|_| InputError::Simulate("failed to write to the clipboard"))
+            .and_then(|()| self.paste_clipboard());
+
+        if result.is_ok() {
+            thread::sleep(PASTE_RESTORE_DELAY);
+        }
+        previous.restore(&mut clipboard);
+        result
+    }
+
+    fn paste_clipboard(&mut self) -> InputResult<()> {
+        self.shortcut('v')
+    }
+
+    fn copy_selection(&mut self) -> InputResult<()> {
+        self.shortcut('c')
+    }
+}
+
+/// 后端收到的一次操作
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordedCall {
+    Text(String),
+    Key(Key),
+    Paste(String),
+    PasteClipboard,
+    CopySelection,
+}
+
+/// 只记录操作、不发送按键的后端，用于测试输入流程
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingBackend {
+    pub calls: Vec<RecordedCall>,
+}
+
+#[cfg(test)]
+impl RecordingBackend {
+    /// 把连续的逐字输入合并为一段文本，便于比较
+    pub fn merged_calls(&self) -> Vec<RecordedCall> {
+        let mut merged: Vec<RecordedCall> = Vec::new();
+        for call in &self.calls {
+            match (merged.last_mut(), call) {
+                (Some(RecordedCall::Text(last)), RecordedCall::Text(text)) => last.push_str(text),
+                _ => merged.push(call.clone()),
+            }
+        }
+        merged
+    }
+}
+
+#[cfg(test)]
+impl KeyboardBackend for RecordingBackend {
+    fn text(&mut self, text: &str) -> InputResult<()> {
+        self.calls.push(RecordedCall::Text(text.to_string()));
+        Ok(())
+    }
+
+    fn key(&mut self, key: Key) -> InputResult<()> {
+        self.calls.push(RecordedCall::Key(key));
+        Ok(())
+    }
+
+    fn paste(&mut self, text: &str) -> InputResult<()> {
+        self.calls.push(RecordedCall::Paste(text.to_string()));
+        Ok(())
+    }
+
+    fn paste_clipboard(&mut self) -> InputResult<()> {
+        self.calls.push(RecordedCall::PasteClipboard);
+        Ok(())
+    }
+
+    fn copy_selection(&mut self) -> InputResult<()> {
+        self.calls.push(RecordedCall::CopySelection);
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+//! 日志缓冲模块
+//!
+//! 在 env_logger 输出到控制台的同时，把最近的日志行保存在内存环形缓冲区中，
+//! 供界面内的日志查看窗口显示。
+
+use chrono::Local;
+use log::{Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// 内存中最多保留的日志行数
+const MAX_LOG_LINES: usize = 1000;
+
+/// 共享的日志行缓冲区
+pub type LogBuffer = Arc<Mutex<VecDeque<String>>>;
+
+/// 同时写入控制台和内存缓冲区的日志记录器
+struct BufferedLogger {
+    inner: env_logger::Logger,
+    buffer: LogBuffer,
+}
+
+impl Log for BufferedLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        self.inner.log(record);
+
+        let line = format!(
+            "[{} {:<5} {}] {}",
+            Local::now().format("%H:%M:%S"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if let Ok(mut buffer) = self.buffer.lock() {
+            if buffer.len() >= MAX_LOG_LINES {
+                buffer.pop_front();
+            }
+            buffer.push_back(line);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// 初始化日志系统，返回可供界面读取的日志缓冲区
+pub fn init() -> LogBuffer {
+    let inner = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format_timestamp_secs()
+        .build();
+    let buffer: LogBuffer = Arc::new(Mutex::new(VecDeque::with_capacity(MAX_LOG_LINES)));
+
+    let max_level = inner.filter();
+    let logger = BufferedLogger {
+        inner,
+        buffer: buffer.clone(),
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max_level);
+    }
+
+    buffer
+}
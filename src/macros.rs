@@ -0,0 +1,75 @@
+//! 宏模块
+//!
+//! 宏由按顺序执行的步骤（输入文本、按键、停顿）组成，可以绑定独立的快捷键。
+//! 执行时转换为输入指令，与普通输入共用同一套输入流程。
+
+use crate::hotkey_config::HotkeyConfig;
+use crate::typing::{SpecialKey, TypingToken};
+use serde::{Deserialize, Serialize};
+
+/// 宏的一个步骤
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MacroStep {
+    /// 逐字输入一段文本
+    TypeText(String),
+    /// 按下并松开一个特殊按键
+    Key(SpecialKey),
+    /// 暂停指定毫秒数
+    Delay(u64),
+}
+
+impl MacroStep {
+    /// 编辑器中可添加的步骤，带有默认值
+    pub fn templates() -> Vec<MacroStep> {
+        vec![
+            MacroStep::TypeText(String::new()),
+            MacroStep::Key(SpecialKey::Tab),
+            MacroStep::Delay(500),
+        ]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            MacroStep::TypeText(_) => "ui.macro_step_text",
+            MacroStep::Key(_) => "ui.macro_step_key",
+            MacroStep::Delay(_) => "ui.macro_step_delay",
+        }
+    }
+}
+
+/// 命名宏
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Macro {
+    /// 名称
+    pub name: String,
+    /// 按顺序执行的步骤
+    #[serde(default)]
+    pub steps: Vec<MacroStep>,
+    /// 触发宏的快捷键，为 None 时只能从宏窗口运行
+    #[serde(default)]
+    pub hotkey: Option<HotkeyConfig>,
+}
+
+impl Macro {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            steps: Vec::new(),
+            hotkey: None,
+        }
+    }
+
+    /// 转换为输入指令，跳过空文本
+    pub fn tokens(&self) -> Vec<TypingToken> {
+        self.steps
+            .iter()
+            .filter_map(|step| match step {
+                MacroStep::TypeText(text) if text.is_empty() => None,
+                MacroStep::TypeText(text) => Some(TypingToken::Text(text.clone())),
+                MacroStep::Key(key) => Some(TypingToken::Key(*key)),
+                MacroStep::Delay(ms) => Some(TypingToken::Delay(*ms)),
+            })
+            .collect()
+    }
+}
@@ -3,34 +3,112 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app_config;
+mod autostart;
+mod clipboard_format;
+mod file_watch;
+mod history;
+mod history_crypto;
 mod hotkey_config;
+mod log_buffer;
+mod macros;
+mod modifier_keys;
+mod mouse_hook;
+mod password_field;
 mod permissions;
+mod power;
+mod preprocess;
+mod process_info;
+mod rpc;
+mod scratchpad;
+mod session_lock;
+mod sound;
 mod i18n;
+mod keyboard;
+mod text_transform;
+mod typing;
 
 /// 单条剪贴板记录的最大大小（10MB）
 const MAX_SINGLE_ITEM_SIZE: usize = 10 * 1024 * 1024;
 /// 剪贴板历史记录的最大总内存（50MB）
 const MAX_TOTAL_MEMORY: usize = 50 * 1024 * 1024;
-
-use app_config::{AppConfig, CloseAction};
+/// 历史条数滑块的最大值，更大的条数通过数值框输入
+const HISTORY_SLIDER_MAX: u32 = 100;
+/// 定时设置中星期的 i18n 键，从周一开始
+const WEEKDAY_KEYS: [&str; 7] = [
+    "ui.app.weekday_mon",
+    "ui.app.weekday_tue",
+    "ui.app.weekday_wed",
+    "ui.app.weekday_thu",
+    "ui.app.weekday_fri",
+    "ui.app.weekday_sat",
+    "ui.app.weekday_sun",
+];
+/// 输入超过预计时长且没有进展多久后视为卡住
+const STUCK_TYPING_MARGIN: Duration = Duration::from_secs(30);
+/// 主窗口按键延迟滑块的最大值，更大的延迟在设置中调整
+const SESSION_DELAY_SLIDER_MAX: u64 = 500;
+/// 接通电源（或无法检测电源状态）时检查剪贴板的间隔
+const CLIPBOARD_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// 检查监视文件是否变化的间隔
+const FILE_WATCH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// 检查小睡、定时启用和卡住的输入的间隔
+const TIMER_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+/// 输入结束后继续暂停剪贴板捕获的时间
+const MONITOR_COOLDOWN_AFTER_TYPING: Duration = Duration::from_millis(1000);
+/// 正在输入或有对话框打开时的重绘间隔
+const REPAINT_INTERVAL_ACTIVE: Duration = Duration::from_millis(50);
+/// 窗口可见但空闲时的重绘间隔
+const REPAINT_INTERVAL_IDLE: Duration = Duration::from_millis(250);
+/// 窗口隐藏到托盘且空闲时的重绘间隔
+const REPAINT_INTERVAL_HIDDEN: Duration = Duration::from_millis(500);
+/// 主窗口的默认大小
+const WINDOW_SIZE: egui::Vec2 = egui::vec2(400.0, 500.0);
+/// 主窗口的最小大小
+const WINDOW_MIN_SIZE: egui::Vec2 = egui::vec2(350.0, 400.0);
+/// 迷你模式的窗口大小
+const MINI_WINDOW_SIZE: egui::Vec2 = egui::vec2(96.0, 44.0);
+/// 停靠窗口时与屏幕边缘的距离
+const DOCK_MARGIN: f32 = 8.0;
+/// 停靠到底部时与屏幕下边缘的距离，为任务栏留出空间
+const DOCK_BOTTOM_MARGIN: f32 = 56.0;
+
+use app_config::{
+    AppConfig, CharClass, CharClassDelays, CloseAction, DockCorner, EmojiMode, Schedule, ScheduleWindow,
+    SessionLockAction, SourceFilter, SourceFilterMode, TrayMenuEntry, HISTORY_MAX_ITEMS_LIMIT,
+    MINUTES_PER_DAY, MIN_WINDOW_OPACITY,
+};
 use arboard::Clipboard;
-use chrono::Local;
+use clipboard_format::{CaptureSelection, ClipboardFormat, NonTextClipboard, NonTextContent, SelectionReader};
 use eframe::egui;
-use enigo::{Enigo, Keyboard, Settings};
-use global_hotkey::{hotkey::HotKey, GlobalHotKeyEvent, GlobalHotKeyManager};
-use hotkey_config::{HotkeyConfig, KeyCode};
+use enigo::{InputResult, Key};
+use file_watch::FileWatch;
+use history::{HistoryEntry, HistorySort};
+use history_crypto::{CryptoError, HistoryKey};
+use global_hotkey::{hotkey::HotKey, GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use hotkey_config::{
+    HotkeyAction, HotkeyConfig, HotkeyConflict, KeyCode, MainHotkeyAction, MouseTrigger, PausedHotkeyAction,
+};
 use i18n::I18n;
-use log::{debug, error, info, warn};
+use keyboard::{EnigoBackend, KeyboardBackend};
+use log::{debug, error, info, log_enabled, warn, Level};
+use log_buffer::LogBuffer;
+use macros::{Macro, MacroStep};
+use mouse_hook::ClickWait;
 use permissions::{check_permissions, get_permission_fix_instructions, PermissionStatus};
-use rand::Rng;
+use preprocess::PreprocessError;
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use sound::{SoundCue, SoundCueEvents};
+use text_transform::{CharsetRestriction, TransformConfig, UnicodeNormalization};
+use typing::{is_emoji, tokenize, SpecialKey, TypingToken};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc, Mutex,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc, Condvar, Mutex,
 };
 use std::thread;
 use std::time::{Duration, Instant};
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    menu::{IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
     TrayIcon, TrayIconBuilder,
 };
 #[cfg(target_os = "windows")]
@@ -40,47 +118,421 @@ use raw_window_handle::{HasWindowHandle, RawWindowHandle};
 const MENU_SHOW: &str = "show";
 const MENU_TOGGLE: &str = "toggle";
 const MENU_EXIT: &str = "exit";
+const MENU_TYPE_NOW: &str = "type_now";
+const MENU_HISTORY: &str = "history";
+/// 托盘“按速度输入”子菜单项 ID 前缀，后接预设名称
+const MENU_SPEED_PREFIX: &str = "speed_";
+/// 托盘“小睡”子菜单项 ID 前缀，后接分钟数
+const MENU_SNOOZE_PREFIX: &str = "snooze_";
+
+/// 可选的小睡时长 (分钟)
+const SNOOZE_MINUTES: [u64; 3] = [15, 30, 60];
+
+/// 输入速度预设
+#[derive(Clone, Copy)]
+struct SpeedPreset {
+    /// 预设名称，同时用于 i18n 键 `ui.app.preset_<name>`
+    name: &'static str,
+    /// 延迟 (毫秒)
+    delay: u64,
+    /// 随机偏差 (毫秒)
+    variance: u64,
+}
+
+impl SpeedPreset {
+    /// 按名称查找预设
+    fn find(name: &str) -> Option<SpeedPreset> {
+        SPEED_PRESETS.iter().copied().find(|p| p.name == name)
+    }
+
+    /// 对应的 i18n 键
+    fn i18n_key(&self) -> String {
+        format!("ui.app.preset_{}", self.name)
+    }
+}
+
+/// 设置窗口与托盘菜单共用的速度预设
+const SPEED_PRESETS: [SpeedPreset; 4] = [
+    SpeedPreset { name: "ultra", delay: 0, variance: 0 },
+    SpeedPreset { name: "fast", delay: 10, variance: 5 },
+    SpeedPreset { name: "normal", delay: 50, variance: 30 },
+    SpeedPreset { name: "slow", delay: 150, variance: 50 },
+];
+
+/// 各平台常见的中文字体路径，按优先级排列
+#[cfg(target_os = "windows")]
+const CJK_FONT_PATHS: &[&str] = &[
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\msyh.ttf",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\simsun.ttc",
+];
+#[cfg(target_os = "macos")]
+const CJK_FONT_PATHS: &[&str] = &[
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/System/Library/Fonts/STHeiti Light.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+];
+#[cfg(target_os = "linux")]
+const CJK_FONT_PATHS: &[&str] = &[
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/adobe-source-han-sans/SourceHanSansCN-Regular.otf",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-zenhei.ttc",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+];
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+const CJK_FONT_PATHS: &[&str] = &[];
+
+/// 说明 Meta 修饰键在当前系统上对应哪个按键
+#[cfg(target_os = "macos")]
+const META_KEY_TIP: &str = "ui.tip_meta_key_macos";
+#[cfg(target_os = "windows")]
+const META_KEY_TIP: &str = "ui.tip_meta_key_windows";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const META_KEY_TIP: &str = "ui.tip_meta_key_linux";
+
+/// 快捷键测试等待按下的时间
+const HOTKEY_TEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 疑似命令确认窗口中最多预览的行数
+const SHELL_CONFIRM_PREVIEW_LINES: usize = 8;
+/// 历史选择列表最多显示的条目数
+const HISTORY_PICKER_MAX_ITEMS: usize = 20;
+/// 历史选择列表中直接选择前 9 项的数字键
+const HISTORY_PICKER_NUMBER_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
+/// “清除早于…”可选的时长：(i18n 键, 时长)
+const HISTORY_CLEAR_AGES: [(&str, Duration); 3] = [
+    ("ui.history_age_hour", Duration::from_secs(60 * 60)),
+    ("ui.history_age_day", Duration::from_secs(24 * 60 * 60)),
+    ("ui.history_age_week", Duration::from_secs(7 * 24 * 60 * 60)),
+];
+
+/// 输入线程的控制标志，配合条件变量使用，暂停/继续/取消时立即唤醒输入线程
+#[derive(Default)]
+struct TypingControl {
+    /// 输入是否暂停
+    paused: bool,
+    /// 是否请求取消当前输入
+    cancelled: bool,
+    /// 本次暂停开始的时间
+    paused_since: Option<Instant>,
+    /// 已结束的暂停累计时长
+    paused_total: Duration,
+}
+
+impl TypingControl {
+    fn set_paused(&mut self, paused: bool) {
+        if paused {
+            self.paused_since.get_or_insert_with(Instant::now);
+        } else if let Some(since) = self.paused_since.take() {
+            self.paused_total += since.elapsed();
+        }
+        self.paused = paused;
+    }
+
+    /// 累计暂停时长，包括正在进行的暂停
+    fn paused_duration(&self) -> Duration {
+        self.paused_total + self.paused_since.map_or(Duration::ZERO, |since| since.elapsed())
+    }
+}
+
+/// 单次输入请求
+#[derive(Clone, Default)]
+struct TypingRequest {
+    /// 要输入的文本，为 None 时输入当前剪贴板文本
+    text: Option<String>,
+    /// 本次使用的速度预设，为 None 时使用设置中的延迟
+    speed: Option<SpeedPreset>,
+    /// 输入前是否把焦点切回外部窗口（从本程序窗口触发时使用）
+    refocus: bool,
+    /// 是否从历史列表触发，是则增加对应条目的使用次数
+    from_history: bool,
+    /// 是否继续输入上次中断时剩余的部分（忽略 text）
+    resume: bool,
+    /// 直接执行的输入指令（宏），不为 None 时忽略 text
+    tokens: Option<Vec<TypingToken>>,
+    /// 用户是否已确认本次输入（疑似命令、跨程序输入）
+    confirmed: bool,
+}
+
+/// 需要用户确认的原因
+#[derive(Clone)]
+enum ConfirmKind {
+    /// 文本像多行命令
+    ShellCommands,
+    /// 复制时的来源程序与当前前台程序不同
+    CrossApp { source: String, target: String },
+    /// 监视的文件内容发生变化
+    WatchedFile { file: String },
+}
 
+/// 等待用户确认的输入
 #[derive(Clone)]
-struct HistoryItem {
-    text: String,
-    copied_at: String,
+struct CommandConfirm {
+    /// 需要确认的原因
+    kind: ConfirmKind,
+    /// 确认后执行的输入请求
+    request: TypingRequest,
+    /// 关闭确认窗口后是否重新隐藏主窗口（请求前窗口处于隐藏状态）
+    hide_after: bool,
+}
+
+/// 输入流程中与按键发送相关的选项
+struct TypingOptions {
+    /// 列模式：换行时发送 ↓ + Home
+    column_paste: bool,
+    /// 换行后的额外延迟 (毫秒)
+    line_delay: u64,
+    /// 长行中每输入多少个字符额外等待一次换行延迟
+    split_long_lines_at: Option<usize>,
+    /// emoji 的输入方式
+    emoji_mode: EmojiMode,
+    /// 进度日志最多每隔多少个字符输出一次
+    progress_log_every: usize,
+    /// 进度日志最多每隔多长时间输出一次
+    progress_log_interval: Duration,
+    /// 输入的最长时间，暂停的时间不计入
+    max_duration: Option<Duration>,
+}
+
+/// 输入时长限制，暂停的时间不计入
+struct TypingTimeLimit {
+    max: Option<Duration>,
+    started: Instant,
+    paused_before: Duration,
+}
+
+impl TypingTimeLimit {
+    /// `paused` 为开始计时时的累计暂停时长
+    fn new(max: Option<Duration>, paused: Duration) -> Self {
+        Self {
+            max,
+            started: Instant::now(),
+            paused_before: paused,
+        }
+    }
+
+    /// 剩余可用的时长，未设置上限时返回 None
+    fn remaining(&self, paused: Duration) -> Option<Duration> {
+        let max = self.max?;
+        let elapsed = self
+            .started
+            .elapsed()
+            .saturating_sub(paused.saturating_sub(self.paused_before));
+        Some(max.saturating_sub(elapsed))
+    }
+
+    /// 是否已经超过上限
+    fn exceeded(&self, paused: Duration) -> bool {
+        self.remaining(paused) == Some(Duration::ZERO)
+    }
+
+    /// 把等待时间截短到剩余时长以内，避免超长的延迟拖过上限
+    fn clamp(&self, ms: u64, paused: Duration) -> u64 {
+        self.remaining(paused)
+            .map_or(ms, |remaining| ms.min(remaining.as_millis() as u64))
+    }
+}
+
+/// 输入进度日志的节流，避免长文本逐字输出日志
+struct ProgressLog {
+    every_chars: usize,
+    interval: Duration,
+    logged_chars: usize,
+    logged_at: Instant,
 }
 
-impl HistoryItem {
-    fn new(text: String) -> Self {
+impl ProgressLog {
+    fn new(every_chars: usize, interval: Duration) -> Self {
         Self {
-            text,
-            copied_at: format_history_timestamp(),
+            every_chars: every_chars.max(1),
+            interval,
+            logged_chars: 0,
+            logged_at: Instant::now(),
+        }
+    }
+
+    /// 已输入 `typed` 个字符，距上次输出已达到字符数或时间间隔时返回 true
+    fn should_log(&mut self, typed: usize) -> bool {
+        if typed - self.logged_chars >= self.every_chars || self.logged_at.elapsed() >= self.interval {
+            self.logged_chars = typed;
+            self.logged_at = Instant::now();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// 用于检测卡住的输入
+#[derive(Default)]
+struct TypingWatch {
+    /// 每次开始输入时加一，旧的输入线程结束时据此判断是否还需要结束输入状态
+    session: u64,
+    /// 开始输入的时间
+    started: Option<Instant>,
+    /// 按速度估算的输入时长
+    estimated: Duration,
+    /// 预计下一次有进展的时间
+    next_progress_due: Option<Instant>,
+}
+
+/// 输入线程持有的守卫，线程结束（包括 panic）时结束输入状态
+struct TypingGuard {
+    state: SharedState,
+    session: u64,
+}
+
+impl Drop for TypingGuard {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            error!("{}", self.state.t("log.typing_thread_panicked"));
+            self.state.set_status(&self.state.t("status.typing_thread_panicked"));
+        }
+        self.state.finish_typing_session(self.session);
+    }
+}
+
+/// 输入进度：正在输入的标记下标，以及该文本标记中已输入的字符数
+#[derive(Default)]
+struct TypingProgress {
+    token: usize,
+    chars: usize,
+}
+
+impl TypingProgress {
+    /// 根据进度计算尚未输入的标记
+    fn remaining(&self, tokens: &[TypingToken]) -> Vec<TypingToken> {
+        let mut rest = Vec::new();
+        let Some(current) = tokens.get(self.token) else {
+            return rest;
+        };
+        match current {
+            TypingToken::Text(chunk) => {
+                let text: String = chunk.chars().skip(self.chars).collect();
+                if !text.is_empty() {
+                    rest.push(TypingToken::Text(text));
+                }
+            }
+            other => rest.push(other.clone()),
         }
+        rest.extend(tokens[self.token + 1..].iter().cloned());
+        rest
     }
 }
 
+/// 快捷键测试的状态
+#[derive(Clone, Copy)]
+enum HotkeyTest {
+    /// 正在等待按下快捷键，记录开始时间
+    Waiting(Instant),
+    /// 已检测到按下
+    Detected,
+    /// 超时未检测到
+    TimedOut,
+}
+
+/// 一次输入的结束方式
+enum TypingOutcome {
+    /// 全部输入完成
+    Completed,
+    /// 被用户取消
+    Cancelled,
+    /// 超过最长输入时间后自动停止
+    TimedOut,
+}
+
+/// 输入流程依赖的外部状态：暂停与取消、剩余字数统计和日志文本
+trait TypingHost {
+    /// 暂停时阻塞直到继续，返回 false 表示输入已被取消
+    fn wait_if_paused(&self) -> bool;
+    /// 休眠指定毫秒数，暂停的时间不计入，返回 false 表示输入已被取消
+    fn sleep_with_pause(&self, ms: u64) -> bool;
+    /// 本次输入累计暂停的时长
+    fn paused_duration(&self) -> Duration;
+    /// 一个字符已输入或被跳过
+    fn char_done(&self);
+    /// 即将通过剪贴板粘贴 `text`
+    fn before_paste(&self, text: &str);
+    /// 遇到暂停标记，进入暂停状态
+    fn pause_at_marker(&self);
+    /// 翻译带参数的日志文本
+    fn tr(&self, key: &str, args: &[(&str, &str)]) -> String;
+}
+
 /// 共享应用状态
 #[derive(Clone)]
 struct SharedState {
     /// 当前保存的剪贴板文本
     clipboard_text: Arc<Mutex<String>>,
+    /// 当前剪贴板文本的字数和行数，文本变化时更新，避免每帧重新计算
+    clipboard_counts: Arc<Mutex<(usize, usize)>>,
     /// 上一次的剪贴板文本（用于检测变化）
     last_clipboard_text: Arc<Mutex<String>>,
+    /// 本程序最近写入剪贴板的文本，剪贴板变成它时不作为新的复制捕获
+    own_clipboard_text: Arc<Mutex<Option<String>>>,
     /// 剪贴板历史记录
-    clipboard_history: Arc<Mutex<Vec<HistoryItem>>>,
+    clipboard_history: Arc<Mutex<Vec<HistoryEntry>>>,
     /// 剪贴板历史记录占用的总内存（字节）
     history_memory_used: Arc<Mutex<usize>>,
     /// 是否保存剪贴板历史
     history_enabled: Arc<Mutex<bool>>,
     /// 剪贴板历史最多保存条数
     history_max_items: Arc<Mutex<u32>>,
+    /// 是否把剪贴板历史保存到磁盘
+    persist_history: Arc<Mutex<bool>>,
+    /// 是否加密保存历史
+    encrypt_history: Arc<Mutex<bool>>,
+    /// 加密历史的密钥，输入口令解锁前为 None，此时不保存历史
+    history_key: Arc<Mutex<Option<HistoryKey>>>,
+    /// 下一次捕获不记录到历史（一次性）
+    skip_next_record: Arc<AtomicBool>,
+    /// 剪贴板格式读取优先级
+    clipboard_format_priority: Arc<Mutex<Vec<ClipboardFormat>>>,
+    /// 是否将 HTML 剪贴板内容转换为 Markdown
+    markdown_mode: Arc<Mutex<bool>>,
+    /// 来源程序过滤
+    source_filter: Arc<Mutex<SourceFilter>>,
+    /// 在前台时不捕获剪贴板变化的程序
+    capture_blocklist: Arc<Mutex<Vec<String>>>,
+    /// 最近一次剪贴板变化的来源程序
+    last_clipboard_source: Arc<Mutex<Option<String>>>,
     /// 是否正在输入中（防止重复触发）
     is_typing: Arc<Mutex<bool>>,
     /// 程序是否启用
     enabled: Arc<Mutex<bool>>,
+    /// 用户最后一次手动切换的启用状态（小睡、定时切换不改变），记住状态时写入配置
+    toggled_enabled: Arc<Mutex<bool>>,
+    /// 切换启用状态时是否写入配置文件
+    persist_enabled_state: Arc<Mutex<bool>>,
+    /// 小睡结束时间，到期后自动重新启用（仅本次运行有效）
+    snooze_until: Arc<Mutex<Option<Instant>>>,
+    /// 定时启用的时间段
+    schedule: Arc<Mutex<Schedule>>,
+    /// 上次检查时是否在定时时间段内，未启用定时为 None
+    schedule_active: Arc<Mutex<Option<bool>>>,
     /// 状态消息
     status_message: Arc<Mutex<String>>,
     /// 请求退出程序
     request_exit: Arc<AtomicBool>,
     /// 窗口是否可见
-    #[allow(dead_code)]
     window_visible: Arc<AtomicBool>,
     /// 模拟输入时的延迟 (毫秒)
     typing_delay: Arc<Mutex<u64>>,
@@ -88,12 +540,152 @@ struct SharedState {
     typing_variance: Arc<Mutex<u64>>,
     /// 是否启用随机偏差
     typing_variance_enabled: Arc<Mutex<bool>>,
-    /// 输入是否暂停
-    typing_paused: Arc<Mutex<bool>>,
+    /// 随机偏差的固定种子，为 None 时每次随机
+    variance_seed: Arc<Mutex<Option<u64>>>,
+    /// 换行后的额外延迟 (毫秒)
+    line_delay_ms: Arc<Mutex<u64>>,
+    /// 长行中每隔多少个字符额外等待一次，None 表示不拆分
+    split_long_lines_at: Arc<Mutex<Option<usize>>>,
+    /// 按字符类别额外增加的延迟
+    char_class_delays: Arc<Mutex<CharClassDelays>>,
+    /// 强制的最小按键延迟 (毫秒)
+    min_enforced_delay: Arc<Mutex<u64>>,
+    /// 单次输入的最长时间 (毫秒)，0 表示不限制
+    max_type_duration_ms: Arc<Mutex<u64>>,
+    /// 输入完成后的冷却时间 (毫秒)
+    post_type_cooldown_ms: Arc<Mutex<u64>>,
+    /// 上次输入成功完成的时间，用于冷却判断
+    last_typing_completed: Arc<Mutex<Option<Instant>>>,
+    /// 是否解析 `<<TAB>>` 等按键标记
+    key_tokens_enabled: Arc<Mutex<bool>>,
+    /// 焦点位于密码框时是否拒绝输入
+    block_password_fields: Arc<Mutex<bool>>,
+    /// 开始输入前等待鼠标单击的最长时间，为 None 时不等待
+    click_wait_timeout: Arc<Mutex<Option<Duration>>>,
+    /// 从本程序窗口触发输入时是否切回外部窗口
+    refocus_external_window: Arc<Mutex<bool>>,
+    /// 从本程序窗口触发的输入完成后是否隐藏窗口
+    auto_hide_after_type: Arc<Mutex<bool>>,
+    /// 从本程序窗口触发的输入完成后是否把本程序窗口切回前台
+    refocus_self_after_type: Arc<Mutex<bool>>,
+    /// 输入完成后是否把输入的文本写回剪贴板
+    restore_clipboard_to_typed: Arc<Mutex<bool>>,
+    /// 开始输入前等待修饰键松开的最长时间 (毫秒)
+    modifier_release_timeout_ms: Arc<Mutex<u64>>,
+    /// 是否播放提示音
+    sound_cues: Arc<Mutex<bool>>,
+    /// 各事件是否播放提示音
+    sound_cue_events: Arc<Mutex<SoundCueEvents>>,
+    /// 会话锁定时的处理方式
+    on_session_lock: Arc<Mutex<SessionLockAction>>,
+    /// 进度日志节流：(字符数, 毫秒)
+    progress_log_throttle: Arc<Mutex<(u32, u64)>>,
+    /// 当前暂停是否由会话锁定引起
+    paused_by_session_lock: Arc<AtomicBool>,
+    /// 请求界面线程把窗口隐藏到托盘（输入完成后设置）
+    hide_window_requested: Arc<AtomicBool>,
+    /// 请求界面线程把本程序窗口切回前台（输入完成后设置）
+    refocus_self_requested: Arc<AtomicBool>,
+    /// 主快捷键在未输入时执行的操作
+    main_hotkey_action: Arc<Mutex<MainHotkeyAction>>,
+    /// 输入暂停时主快捷键执行的操作
+    paused_hotkey_action: Arc<Mutex<PausedHotkeyAction>>,
+    /// 连按两次取消快捷键即退出的时间窗口 (毫秒)，0 表示不启用
+    double_cancel_quit_ms: Arc<Mutex<u64>>,
+    /// 上次按下取消快捷键的时间
+    last_cancel_press: Arc<Mutex<Option<Instant>>>,
+    /// 剪贴板监控线程是否在运行
+    clipboard_monitor_running: Arc<AtomicBool>,
+    /// 是否请求显示历史选择列表
+    history_picker_requested: Arc<AtomicBool>,
+    /// 历史选择列表关闭后是否重新隐藏主窗口（打开前窗口处于隐藏状态）
+    history_picker_hide_after: Arc<AtomicBool>,
+    /// GUI 上下文，用于从后台线程显示窗口
+    egui_ctx: Arc<Mutex<Option<egui::Context>>>,
+    /// 终端安全模式下，文本像多行命令时是否先确认
+    confirm_shell_commands: Arc<Mutex<bool>>,
+    /// 来源程序与前台程序不同时是否先确认
+    confirm_cross_app: Arc<Mutex<bool>>,
+    /// 输入前预处理文本的外部命令，为空时不使用
+    preprocess_command: Arc<Mutex<String>>,
+    /// 预处理命令的超时时间 (毫秒)
+    preprocess_timeout_ms: Arc<Mutex<u64>>,
+    /// 监视的文件
+    watch_file: Arc<Mutex<Option<std::path::PathBuf>>>,
+    /// 输入监视文件的内容前是否先确认
+    watch_file_confirm: Arc<Mutex<bool>>,
+    /// 监视文件的防抖时间 (毫秒)
+    watch_file_debounce_ms: Arc<Mutex<u64>>,
+    /// 等待确认的疑似命令输入
+    pending_command_confirm: Arc<Mutex<Option<CommandConfirm>>>,
+    /// 捕获到新内容时是否提示
+    notify_on_capture: Arc<Mutex<bool>>,
+    /// 监控的选区（仅 Linux 有效）
+    capture_selection: Arc<Mutex<CaptureSelection>>,
+    /// 使用电池供电时检查剪贴板的间隔 (毫秒)
+    battery_poll_interval_ms: Arc<Mutex<u64>>,
+    /// 最近一次检测到的电源状态：是否使用电池，无法检测时为 None
+    on_battery: Arc<Mutex<Option<bool>>>,
+    /// 剪贴板中只有非文本内容时的处理方式
+    non_text_clipboard: Arc<Mutex<NonTextClipboard>>,
+    /// 剪贴板中当前的非文本内容，读取到文本后清除
+    non_text_content: Arc<Mutex<Option<NonTextContent>>>,
+    /// 等待显示到托盘提示中的最新捕获预览
+    capture_notice: Arc<Mutex<Option<String>>>,
+    /// 累积模式：新捕获的内容追加到当前文本之后，而不是替换
+    accumulate: Arc<AtomicBool>,
+    /// 累积模式下已合并的条数
+    accumulated_count: Arc<AtomicUsize>,
+    /// 累积内容之间的分隔符（已处理转义）
+    accumulate_separator: Arc<Mutex<String>>,
+    /// 本程序主窗口句柄
+    own_window: Arc<Mutex<Option<isize>>>,
+    /// 最近一次处于前台的外部窗口句柄
+    last_external_window: Arc<Mutex<Option<isize>>>,
+    /// 列模式：换行时发送 ↓ + Home
+    column_paste: Arc<Mutex<bool>>,
+    /// 逐行输入：每次触发只输入剪贴板的下一行
+    line_by_line: Arc<Mutex<bool>>,
+    /// 逐行输入时下一次要输入的行（不计空行），剪贴板变化时归零
+    line_cursor: Arc<Mutex<usize>>,
+    /// emoji 的输入方式
+    emoji_mode: Arc<Mutex<EmojiMode>>,
+    /// 剪贴板带富文本格式时是否直接粘贴以保留格式
+    paste_rich: Arc<Mutex<bool>>,
+    /// 输入前的文本转换配置
+    transform: Arc<Mutex<TransformConfig>>,
+    /// 输入的暂停与取消控制
+    typing_control: Arc<(Mutex<TypingControl>, Condvar)>,
+    /// 当前输入尚未输入的字符数
+    typing_remaining_chars: Arc<AtomicUsize>,
+    /// 当前输入的估算速度 (字符/分钟)，为 None 时表示极速
+    typing_cpm: Arc<Mutex<Option<u32>>>,
+    /// 最近一次输入结束的时间
+    typing_finished_at: Arc<Mutex<Option<Instant>>>,
+    /// 卡住检测使用的输入时间记录
+    typing_watch: Arc<Mutex<TypingWatch>>,
+    /// 寄存器 (a-z) 中保存的文本，仅本次运行有效
+    registers: Arc<Mutex<HashMap<char, String>>>,
+    /// 输入期间是否暂停剪贴板捕获
+    pause_monitor_while_typing: Arc<Mutex<bool>>,
     /// 最近一次快捷键触发时间
     last_hotkey_trigger: Arc<Mutex<Option<Instant>>>,
     /// 当前快捷键 ID
     hotkey_id: Arc<Mutex<Option<u32>>>,
+    /// 附加操作快捷键 ID 与操作的对应关系
+    action_hotkey_ids: Arc<Mutex<HashMap<u32, HotkeyAction>>>,
+    /// 主快捷键测试状态，等待期间按下快捷键只用于测试，不触发输入
+    hotkey_test: Arc<Mutex<Option<HotkeyTest>>>,
+    /// 宏列表
+    macros: Arc<Mutex<Vec<Macro>>>,
+    /// 宏快捷键 ID 与宏在列表中下标的对应关系
+    macro_hotkey_ids: Arc<Mutex<HashMap<u32, usize>>>,
+    /// 上一次输入的文本
+    last_typed_text: Arc<Mutex<Option<String>>>,
+    /// 上一次输入中断时尚未输入的部分
+    interrupted_tokens: Arc<Mutex<Option<Vec<TypingToken>>>>,
+    /// 便签内容
+    scratchpad: Arc<Mutex<String>>,
     /// 语言资源
     i18n: I18n,
 }
@@ -103,26 +695,182 @@ impl SharedState {
         let ready = i18n.t("status.ready");
         Self {
             clipboard_text: Arc::new(Mutex::new(String::new())),
+            clipboard_counts: Arc::new(Mutex::new((0, 0))),
             last_clipboard_text: Arc::new(Mutex::new(String::new())),
+            own_clipboard_text: Arc::new(Mutex::new(None)),
             clipboard_history: Arc::new(Mutex::new(Vec::new())),
             history_memory_used: Arc::new(Mutex::new(0)),
             history_enabled: Arc::new(Mutex::new(false)),
             history_max_items: Arc::new(Mutex::new(0)),
+            persist_history: Arc::new(Mutex::new(false)),
+            encrypt_history: Arc::new(Mutex::new(false)),
+            history_key: Arc::new(Mutex::new(None)),
+            skip_next_record: Arc::new(AtomicBool::new(false)),
+            clipboard_format_priority: Arc::new(Mutex::new(ClipboardFormat::default_priority())),
+            markdown_mode: Arc::new(Mutex::new(false)),
+            source_filter: Arc::new(Mutex::new(SourceFilter::default())),
+            capture_blocklist: Arc::new(Mutex::new(Vec::new())),
+            last_clipboard_source: Arc::new(Mutex::new(None)),
             is_typing: Arc::new(Mutex::new(false)),
             enabled: Arc::new(Mutex::new(true)),
+            toggled_enabled: Arc::new(Mutex::new(true)),
+            persist_enabled_state: Arc::new(Mutex::new(false)),
+            snooze_until: Arc::new(Mutex::new(None)),
+            schedule: Arc::new(Mutex::new(Schedule::default())),
+            schedule_active: Arc::new(Mutex::new(None)),
             status_message: Arc::new(Mutex::new(ready)),
             request_exit: Arc::new(AtomicBool::new(false)),
             window_visible: Arc::new(AtomicBool::new(true)),
             typing_delay: Arc::new(Mutex::new(0)),
             typing_variance: Arc::new(Mutex::new(0)),
             typing_variance_enabled: Arc::new(Mutex::new(false)),
-            typing_paused: Arc::new(Mutex::new(false)),
+            variance_seed: Arc::new(Mutex::new(None)),
+            line_delay_ms: Arc::new(Mutex::new(0)),
+            split_long_lines_at: Arc::new(Mutex::new(None)),
+            char_class_delays: Arc::new(Mutex::new(CharClassDelays::default())),
+            min_enforced_delay: Arc::new(Mutex::new(0)),
+            max_type_duration_ms: Arc::new(Mutex::new(0)),
+            post_type_cooldown_ms: Arc::new(Mutex::new(0)),
+            last_typing_completed: Arc::new(Mutex::new(None)),
+            key_tokens_enabled: Arc::new(Mutex::new(false)),
+            block_password_fields: Arc::new(Mutex::new(false)),
+            click_wait_timeout: Arc::new(Mutex::new(None)),
+            refocus_external_window: Arc::new(Mutex::new(true)),
+            auto_hide_after_type: Arc::new(Mutex::new(false)),
+            refocus_self_after_type: Arc::new(Mutex::new(false)),
+            restore_clipboard_to_typed: Arc::new(Mutex::new(false)),
+            modifier_release_timeout_ms: Arc::new(Mutex::new(1000)),
+            sound_cues: Arc::new(Mutex::new(false)),
+            sound_cue_events: Arc::new(Mutex::new(SoundCueEvents::default())),
+            on_session_lock: Arc::new(Mutex::new(SessionLockAction::default())),
+            progress_log_throttle: Arc::new(Mutex::new((100, 1000))),
+            paused_by_session_lock: Arc::new(AtomicBool::new(false)),
+            hide_window_requested: Arc::new(AtomicBool::new(false)),
+            refocus_self_requested: Arc::new(AtomicBool::new(false)),
+            main_hotkey_action: Arc::new(Mutex::new(MainHotkeyAction::default())),
+            paused_hotkey_action: Arc::new(Mutex::new(PausedHotkeyAction::default())),
+            double_cancel_quit_ms: Arc::new(Mutex::new(0)),
+            last_cancel_press: Arc::new(Mutex::new(None)),
+            clipboard_monitor_running: Arc::new(AtomicBool::new(false)),
+            history_picker_requested: Arc::new(AtomicBool::new(false)),
+            history_picker_hide_after: Arc::new(AtomicBool::new(false)),
+            egui_ctx: Arc::new(Mutex::new(None)),
+            confirm_shell_commands: Arc::new(Mutex::new(true)),
+            confirm_cross_app: Arc::new(Mutex::new(false)),
+            preprocess_command: Arc::new(Mutex::new(String::new())),
+            preprocess_timeout_ms: Arc::new(Mutex::new(0)),
+            watch_file: Arc::new(Mutex::new(None)),
+            watch_file_confirm: Arc::new(Mutex::new(true)),
+            watch_file_debounce_ms: Arc::new(Mutex::new(0)),
+            pending_command_confirm: Arc::new(Mutex::new(None)),
+            notify_on_capture: Arc::new(Mutex::new(false)),
+            capture_selection: Arc::new(Mutex::new(CaptureSelection::default())),
+            battery_poll_interval_ms: Arc::new(Mutex::new(0)),
+            on_battery: Arc::new(Mutex::new(None)),
+            non_text_clipboard: Arc::new(Mutex::new(NonTextClipboard::default())),
+            non_text_content: Arc::new(Mutex::new(None)),
+            capture_notice: Arc::new(Mutex::new(None)),
+            accumulate: Arc::new(AtomicBool::new(false)),
+            accumulated_count: Arc::new(AtomicUsize::new(0)),
+            accumulate_separator: Arc::new(Mutex::new("\n".to_string())),
+            own_window: Arc::new(Mutex::new(None)),
+            last_external_window: Arc::new(Mutex::new(None)),
+            column_paste: Arc::new(Mutex::new(false)),
+            line_by_line: Arc::new(Mutex::new(false)),
+            line_cursor: Arc::new(Mutex::new(0)),
+            emoji_mode: Arc::new(Mutex::new(EmojiMode::default())),
+            paste_rich: Arc::new(Mutex::new(false)),
+            transform: Arc::new(Mutex::new(TransformConfig::default())),
+            typing_control: Arc::new((Mutex::new(TypingControl::default()), Condvar::new())),
+            typing_remaining_chars: Arc::new(AtomicUsize::new(0)),
+            typing_cpm: Arc::new(Mutex::new(None)),
+            typing_finished_at: Arc::new(Mutex::new(None)),
+            typing_watch: Arc::new(Mutex::new(TypingWatch::default())),
+            registers: Arc::new(Mutex::new(HashMap::new())),
+            pause_monitor_while_typing: Arc::new(Mutex::new(true)),
             last_hotkey_trigger: Arc::new(Mutex::new(None)),
             hotkey_id: Arc::new(Mutex::new(None)),
+            action_hotkey_ids: Arc::new(Mutex::new(HashMap::new())),
+            hotkey_test: Arc::new(Mutex::new(None)),
+            macros: Arc::new(Mutex::new(Vec::new())),
+            macro_hotkey_ids: Arc::new(Mutex::new(HashMap::new())),
+            last_typed_text: Arc::new(Mutex::new(None)),
+            interrupted_tokens: Arc::new(Mutex::new(None)),
+            scratchpad: Arc::new(Mutex::new(String::new())),
             i18n,
         }
     }
 
+    /// 设置启动时的启用状态，不写入配置文件
+    fn init_enabled(&self, enabled: bool) {
+        *self.enabled.lock().unwrap() = enabled;
+        *self.toggled_enabled.lock().unwrap() = enabled;
+    }
+
+    /// 把配置中与输入、捕获相关的设置同步到共享状态
+    ///
+    /// 启动时和保存应用设置后调用；不改变当前的启用状态，启动时另由 `init_enabled` 设置。
+    fn apply_config(&self, app_config: &AppConfig) {
+        *self.persist_enabled_state.lock().unwrap() = app_config.persist_enabled_state;
+        *self.typing_delay.lock().unwrap() = app_config.typing_delay;
+        *self.typing_variance.lock().unwrap() = app_config.typing_variance;
+        *self.typing_variance_enabled.lock().unwrap() = app_config.typing_variance_enabled;
+        *self.variance_seed.lock().unwrap() = app_config.variance_seed;
+        *self.min_enforced_delay.lock().unwrap() = app_config.min_enforced_delay_ms;
+        *self.line_delay_ms.lock().unwrap() = app_config.line_delay_ms;
+        *self.split_long_lines_at.lock().unwrap() =
+            app_config.split_long_lines.then_some(app_config.split_long_lines_at);
+        *self.char_class_delays.lock().unwrap() = app_config.char_class_delays;
+        *self.max_type_duration_ms.lock().unwrap() = app_config.max_type_duration_ms;
+        *self.post_type_cooldown_ms.lock().unwrap() = app_config.post_type_cooldown_ms;
+        *self.pause_monitor_while_typing.lock().unwrap() = app_config.pause_monitor_while_typing;
+        *self.key_tokens_enabled.lock().unwrap() = app_config.key_tokens_enabled;
+        *self.refocus_external_window.lock().unwrap() = app_config.refocus_external_window;
+        *self.auto_hide_after_type.lock().unwrap() = app_config.auto_hide_after_type;
+        *self.refocus_self_after_type.lock().unwrap() = app_config.refocus_self_after_type;
+        *self.restore_clipboard_to_typed.lock().unwrap() = app_config.restore_clipboard_to_typed;
+        *self.modifier_release_timeout_ms.lock().unwrap() = app_config.modifier_release_timeout_ms;
+        *self.sound_cues.lock().unwrap() = app_config.sound_cues;
+        *self.sound_cue_events.lock().unwrap() = app_config.sound_cue_events;
+        *self.on_session_lock.lock().unwrap() = app_config.on_session_lock;
+        *self.main_hotkey_action.lock().unwrap() = app_config.main_hotkey_action;
+        *self.paused_hotkey_action.lock().unwrap() = app_config.paused_hotkey_action;
+        *self.double_cancel_quit_ms.lock().unwrap() = app_config.double_cancel_quit_ms;
+        *self.notify_on_capture.lock().unwrap() = app_config.notify_on_capture;
+        *self.confirm_shell_commands.lock().unwrap() = app_config.confirm_shell_commands;
+        *self.confirm_cross_app.lock().unwrap() = app_config.confirm_cross_app;
+        *self.preprocess_command.lock().unwrap() = app_config.preprocess_command.trim().to_string();
+        *self.preprocess_timeout_ms.lock().unwrap() = app_config.preprocess_timeout_ms;
+        *self.watch_file.lock().unwrap() = app_config.watch_file.clone();
+        *self.watch_file_confirm.lock().unwrap() = app_config.watch_file_confirm;
+        *self.watch_file_debounce_ms.lock().unwrap() = app_config.watch_file_debounce_ms;
+        *self.capture_selection.lock().unwrap() = app_config.capture_selection;
+        *self.battery_poll_interval_ms.lock().unwrap() = app_config.battery_poll_interval_ms;
+        *self.non_text_clipboard.lock().unwrap() = app_config.non_text_clipboard;
+        *self.accumulate_separator.lock().unwrap() = unescape_separator(&app_config.accumulate_separator);
+        *self.progress_log_throttle.lock().unwrap() =
+            (app_config.progress_log_every_chars, app_config.progress_log_interval_ms);
+        *self.macros.lock().unwrap() = app_config.macros.clone();
+        *self.block_password_fields.lock().unwrap() = app_config.block_password_fields;
+        *self.click_wait_timeout.lock().unwrap() = app_config
+            .wait_for_click_before_type
+            .then(|| Duration::from_millis(app_config.click_wait_timeout_ms));
+        *self.column_paste.lock().unwrap() = app_config.column_paste;
+        *self.line_by_line.lock().unwrap() = app_config.line_by_line;
+        *self.emoji_mode.lock().unwrap() = app_config.emoji_mode;
+        *self.paste_rich.lock().unwrap() = app_config.paste_rich;
+        *self.transform.lock().unwrap() = app_config.transform.clone();
+        *self.history_enabled.lock().unwrap() = app_config.history_enabled;
+        *self.history_max_items.lock().unwrap() = app_config.history_max_items;
+        *self.persist_history.lock().unwrap() = app_config.persist_history;
+        *self.encrypt_history.lock().unwrap() = app_config.encrypt_history;
+        *self.clipboard_format_priority.lock().unwrap() = app_config.clipboard_format_priority.clone();
+        *self.markdown_mode.lock().unwrap() = app_config.markdown_mode;
+        *self.source_filter.lock().unwrap() = app_config.source_filter.clone();
+        *self.capture_blocklist.lock().unwrap() = app_config.capture_blocklist.clone();
+        self.set_schedule(app_config.schedule.clone());
+    }
+
     fn set_status(&self, msg: &str) {
         *self.status_message.lock().unwrap() = msg.to_string();
     }
@@ -137,1363 +885,5800 @@ impl SharedState {
 
     fn set_enabled(&self, enabled: bool) {
         *self.enabled.lock().unwrap() = enabled;
+        *self.toggled_enabled.lock().unwrap() = enabled;
+        // 手动切换状态时取消小睡
+        *self.snooze_until.lock().unwrap() = None;
+        if *self.persist_enabled_state.lock().unwrap() {
+            self.persist_enabled(enabled);
+        }
     }
 
-    fn get_clipboard_text(&self) -> String {
-        self.clipboard_text.lock().unwrap().clone()
-    }
+    /// 暂时禁用一段时间，到期后自动重新启用
+    fn snooze(&self, duration: Duration) {
+        // 小睡只影响本次运行，不写入配置文件
+        *self.enabled.lock().unwrap() = false;
+        *self.snooze_until.lock().unwrap() = Some(Instant::now() + duration);
 
-    fn is_typing(&self) -> bool {
-        *self.is_typing.lock().unwrap()
+        let minutes = (duration.as_secs() / 60).to_string();
+        info!("{}", self.tr("log.snooze_start", &[("minutes", minutes.as_str())]));
+        self.set_status(&self.tr("status.snoozed", &[("minutes", minutes.as_str())]));
     }
 
-    fn toggle_typing_pause(&self) -> bool {
-        let mut paused = self.typing_paused.lock().unwrap();
-        *paused = !*paused;
-        *paused
+    /// 剩余的小睡时间
+    fn snooze_remaining(&self) -> Option<Duration> {
+        self.snooze_until
+            .lock()
+            .unwrap()
+            .map(|until| until.saturating_duration_since(Instant::now()))
     }
 
-    fn wait_if_paused(&self) {
-        loop {
-            if !*self.typing_paused.lock().unwrap() {
-                break;
+    /// 小睡到期时重新启用
+    fn check_snooze(&self) {
+        {
+            let mut until = self.snooze_until.lock().unwrap();
+            if !until.is_some_and(|t| Instant::now() >= t) {
+                return;
             }
-            thread::sleep(Duration::from_millis(50));
+            *until = None;
         }
+        *self.enabled.lock().unwrap() = true;
+        info!("{}", self.t("log.snooze_end"));
+        self.set_status(&self.t("status.snooze_end"));
     }
 
-    fn should_handle_hotkey(&self) -> bool {
-        let mut last = self.last_hotkey_trigger.lock().unwrap();
-        let now = Instant::now();
-        if let Some(prev) = *last {
-            if now.duration_since(prev) < Duration::from_millis(200) {
-                return false;
-            }
+    /// 更新定时设置，时间段有变化时在下次检查时立即按新设置切换启用状态
+    fn set_schedule(&self, schedule: Schedule) {
+        let mut current = self.schedule.lock().unwrap();
+        if *current != schedule {
+            *current = schedule;
+            *self.schedule_active.lock().unwrap() = None;
         }
-        *last = Some(now);
-        true
-    }
-    fn t(&self, key: &str) -> String {
-        self.i18n.t(key)
     }
 
-    fn tr<'a>(&self, key: &str, args: &[(&str, &'a str)]) -> String {
-        self.i18n.tr(key, args)
+    /// 是否在定时时间段内，未启用定时时为 None
+    fn schedule_state(&self) -> Option<bool> {
+        *self.schedule_active.lock().unwrap()
     }
 
-    fn record_history(&self, text: String) {
-        if !*self.history_enabled.lock().unwrap() {
-            return;
+    /// 进入或离开定时时间段时切换启用状态
+    ///
+    /// 只在跨过时间段边界时切换，期间手动切换的状态保持到下一个边界。
+    fn check_schedule(&self) {
+        let schedule = self.schedule.lock().unwrap().clone();
+        let active = schedule
+            .enabled
+            .then(|| schedule.is_active_at(chrono::Local::now().naive_local()));
+        {
+            let mut last = self.schedule_active.lock().unwrap();
+            if *last == active {
+                return;
+            }
+            *last = active;
         }
-        let max_items = *self.history_max_items.lock().unwrap();
-        if max_items == 0 {
+        let Some(active) = active else {
             return;
+        };
+
+        // 定时切换只影响本次运行，不写入配置文件
+        *self.enabled.lock().unwrap() = active;
+        *self.snooze_until.lock().unwrap() = None;
+        if active {
+            info!("{}", self.t("log.schedule_enabled"));
+            self.set_status(&self.t("status.schedule_enabled"));
+        } else {
+            info!("{}", self.t("log.schedule_disabled"));
+            self.set_status(&self.t("status.schedule_disabled"));
         }
-        
-        // 计算文本大小（字节）
-        let text_size = text.len();
-        
-        // 如果单条文本超过10MB，则不存储
-        if text_size > MAX_SINGLE_ITEM_SIZE {
-            warn!(
-                "{}",
-                self.tr(
-                    "log.item_too_large",
-                    &[
-                        ("size", &format!("{:.2}MB", text_size as f64 / 1024.0 / 1024.0)),
-                        ("max", &format!("{:.2}MB", MAX_SINGLE_ITEM_SIZE as f64 / 1024.0 / 1024.0))
-                    ]
-                )
-            );
+    }
+
+    /// 将启用状态写入配置文件，下次启动时恢复
+    fn persist_enabled(&self, enabled: bool) {
+        let mut config = AppConfig::load();
+        if config.start_enabled == enabled {
             return;
         }
-        
-        let mut history = self.clipboard_history.lock().unwrap();
-        let mut memory_used = self.history_memory_used.lock().unwrap();
-        
-        // 如果新增后总内存超过50MB，删除最旧的记录直到能够放下
-        while *memory_used + text_size > MAX_TOTAL_MEMORY && !history.is_empty() {
-            let removed = history.remove(0);
-            let removed_size = removed.text.len();
-            *memory_used = memory_used.saturating_sub(removed_size);
-            debug!(
-                "{}",
-                self.tr(
-                    "log.removed_old_item",
-                    &[
-                        ("size", &format!("{:.2}KB", removed_size as f64 / 1024.0)),
-                        ("remaining", &format!("{:.2}MB", *memory_used as f64 / 1024.0 / 1024.0))
-                    ]
-                )
-            );
-        }
-        
-        // 添加新记录
-        history.push(HistoryItem::new(text));
-        *memory_used += text_size;
-        
-        // 检查是否超出条数限制
-        if history.len() > max_items as usize {
-            let overflow = history.len() - max_items as usize;
-            for item in history.drain(0..overflow) {
-                *memory_used = memory_used.saturating_sub(item.text.len());
-            }
+        config.start_enabled = enabled;
+        if let Err(e) = config.save() {
+            let err = e.to_string();
+            error!("{}", self.tr("log.save_app_config_fail", &[("err", err.as_str())]));
         }
-        
-        debug!(
-            "{}",
-            self.tr(
-                "log.history_stats",
-                &[
-                    ("count", &history.len().to_string()),
-                    ("memory", &format!("{:.2}MB", *memory_used as f64 / 1024.0 / 1024.0))
-                ]
-            )
-        );
+    }
 
-        #[cfg(debug_assertions)]
-        Self::assert_history_memory_sync(&history, *memory_used);
+    fn get_clipboard_text(&self) -> String {
+        self.clipboard_text.lock().unwrap().clone()
     }
 
-    fn clear_history(&self) {
-        let mut history = self.clipboard_history.lock().unwrap();
-        let mut memory_used = self.history_memory_used.lock().unwrap();
-        history.clear();
-        *memory_used = 0;
+    /// 更新当前剪贴板文本，并重新统计字数和行数
+    fn set_clipboard_text(&self, text: String) {
+        *self.clipboard_counts.lock().unwrap() = (text.chars().count(), text.lines().count());
+        *self.clipboard_text.lock().unwrap() = text;
+        *self.line_cursor.lock().unwrap() = 0;
+    }
 
-        #[cfg(debug_assertions)]
-        Self::assert_history_memory_sync(&history, *memory_used);
+    /// 逐行输入：取出 `text` 中下一行（跳过空行）并前进，返回 (该行, 行号, 总行数)
+    ///
+    /// 最后一行输入后回到第一行。
+    fn next_clipboard_line(&self, text: &str) -> Option<(String, usize, usize)> {
+        let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+        if lines.is_empty() {
+            return None;
+        }
+        let mut cursor = self.line_cursor.lock().unwrap();
+        let index = if *cursor < lines.len() { *cursor } else { 0 };
+        *cursor = (index + 1) % lines.len();
+        Some((lines[index].to_string(), index + 1, lines.len()))
     }
 
-    fn trim_history(&self) {
-        let max_items = *self.history_max_items.lock().unwrap();
-        if max_items == 0 {
-            self.clear_history();
+    /// 捕获到新内容：累积模式下追加到已有内容之后，否则替换
+    fn capture_clipboard_text(&self, text: String) {
+        if !self.accumulate.load(Ordering::SeqCst) {
+            self.set_clipboard_text(text);
             return;
         }
-        let mut history = self.clipboard_history.lock().unwrap();
-        let mut memory_used = self.history_memory_used.lock().unwrap();
-        if history.len() > max_items as usize {
-            let overflow = history.len() - max_items as usize;
-            for item in history.drain(0..overflow) {
-                *memory_used = memory_used.saturating_sub(item.text.len());
-            }
+        let count = self.accumulated_count.fetch_add(1, Ordering::SeqCst) + 1;
+        if count == 1 {
+            self.set_clipboard_text(text);
+        } else {
+            let separator = self.accumulate_separator.lock().unwrap().clone();
+            let combined = format!("{}{}{}", self.get_clipboard_text(), separator, text);
+            self.set_clipboard_text(combined);
         }
+        let count = count.to_string();
+        self.set_status(&self.tr("status.accumulated", &[("count", count.as_str())]));
+    }
 
-        #[cfg(debug_assertions)]
-        Self::assert_history_memory_sync(&history, *memory_used);
+    /// 开启或关闭累积模式，开启时从下一次复制开始重新累积
+    fn set_accumulate(&self, enabled: bool) {
+        self.accumulate.store(enabled, Ordering::SeqCst);
+        self.accumulated_count.store(0, Ordering::SeqCst);
+        let status = if enabled {
+            self.t("status.accumulate_on")
+        } else {
+            self.t("status.accumulate_off")
+        };
+        self.set_status(&status);
     }
 
-    #[cfg(debug_assertions)]
-    fn assert_history_memory_sync(history: &[HistoryItem], memory_used: usize) {
-        let computed: usize = history.iter().map(|item| item.text.len()).sum();
-        debug_assert_eq!(
-            memory_used,
-            computed,
-            "history_memory_used out of sync: tracked={}, actual={}",
-            memory_used,
-            computed
-        );
+    /// 清空已累积的内容，继续保持累积模式
+    fn reset_accumulated(&self) {
+        self.accumulated_count.store(0, Ordering::SeqCst);
+        self.set_clipboard_text(String::new());
+        self.set_status(&self.t("status.accumulate_reset"));
     }
-    
-    /// 执行模拟输入逻辑
-    fn execute_typing(&self) {
-        if !self.is_enabled() {
-            warn!("{}", self.t("log.request_ignored_disabled"));
+
+    /// 当前剪贴板文本的 (字数, 行数)
+    fn clipboard_counts(&self) -> (usize, usize) {
+        *self.clipboard_counts.lock().unwrap()
+    }
+
+    fn is_typing(&self) -> bool {
+        *self.is_typing.lock().unwrap()
+    }
+
+    /// 把输入的文本（不含按键标记）写回剪贴板，并标记为已读，不作为新内容捕获
+    fn copy_typed_to_clipboard(&self, tokens: &[TypingToken]) {
+        let text: String = tokens
+            .iter()
+            .filter_map(|token| match token {
+                TypingToken::Text(chunk) => Some(chunk.as_str()),
+                _ => None,
+            })
+            .collect();
+        if text.is_empty() {
             return;
         }
 
-        // 检查是否正在输入
-        {
-            let mut typing = self.is_typing.lock().unwrap();
-            if *typing {
-                warn!("{}", self.t("log.request_ignored_typing"));
-                return;
-            }
-            *typing = true;
+        self.mark_own_clipboard_write(&text);
+        let result = Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text));
+        if let Err(e) = result {
+            let err = e.to_string();
+            warn!("{}", self.tr("log.restore_clipboard_fail", &[("err", err.as_str())]));
         }
+    }
 
-        *self.typing_paused.lock().unwrap() = false;
-        self.set_status(&self.t("status.typing"));
-        let state = self.clone();
-        let delay = *self.typing_delay.lock().unwrap();
-        let variance = *self.typing_variance.lock().unwrap();
-        let variance_enabled = *self.typing_variance_enabled.lock().unwrap();
+    /// 记录即将由本程序写入剪贴板的文本，必须在写入前调用
+    fn mark_own_clipboard_write(&self, text: &str) {
+        *self.own_clipboard_text.lock().unwrap() = Some(text.to_string());
+    }
 
-        thread::spawn(move || {
-            // 延迟输入，防止还未松开快捷键
-            thread::sleep(Duration::from_millis(250));
+    /// 剪贴板变化后的新内容是否是本程序写入的；不是则说明已被其他内容替换，清除记录
+    fn is_own_clipboard_write(&self, text: &str) -> bool {
+        let mut own = self.own_clipboard_text.lock().unwrap();
+        if own.as_deref() == Some(text) {
+            return true;
+        }
+        *own = None;
+        false
+    }
+
+    /// 结束输入状态，记录结束时间
+    fn finish_typing(&self) {
+        self.reset_typing_control();
+        *self.typing_finished_at.lock().unwrap() = Some(Instant::now());
+        *self.is_typing.lock().unwrap() = false;
+    }
 
-            let text = state.clipboard_text.lock().unwrap().clone();
+    /// 开始一次新的输入，返回其编号
+    fn start_typing_session(&self) -> u64 {
+        let mut watch = self.typing_watch.lock().unwrap();
+        let now = Instant::now();
+        watch.session += 1;
+        watch.started = Some(now);
+        watch.estimated = Duration::ZERO;
+        watch.next_progress_due = Some(now);
+        watch.session
+    }
 
-            if text.is_empty() {
-                warn!("{}", state.t("log.clipboard_empty"));
-                state.set_status(&state.t("status.clipboard_empty"));
-                *state.typing_paused.lock().unwrap() = false;
-                *state.is_typing.lock().unwrap() = false;
+    /// 结束编号为 `session` 的输入；该输入已被强制结束时不做任何事
+    fn finish_typing_session(&self, session: u64) {
+        {
+            let mut watch = self.typing_watch.lock().unwrap();
+            if watch.session != session {
                 return;
             }
+            watch.started = None;
+            watch.next_progress_due = None;
+        }
+        self.finish_typing();
+    }
 
-            let len_str = text.len().to_string();
-            let delay_str = delay.to_string();
-            let variance_str = variance.to_string();
-            let variance_enabled_str = variance_enabled.to_string();
+    /// 记录输入有了进展，`wait` 为之后预计等待的时间
+    fn note_typing_progress(&self, wait: Duration) {
+        self.typing_watch.lock().unwrap().next_progress_due = Some(Instant::now() + wait);
+    }
 
-            info!(
-                "{}",
-                state.tr(
-                    "log.input_start",
-                    &[
-                        ("len", len_str.as_str()),
-                        ("delay", delay_str.as_str()),
-                        ("variance", variance_str.as_str()),
-                        ("variance_enabled", variance_enabled_str.as_str())
-                    ]
-                )
-            );
+    /// 输入远超预计时长且长时间没有进展时强制结束输入状态
+    ///
+    /// 输入线程卡在按键发送中时，输入状态不会结束，之后的所有触发都会被忽略。
+    fn check_stuck_typing(&self) {
+        if !self.is_typing() {
+            return;
+        }
+        let paused = {
+            let (lock, _) = &*self.typing_control;
+            let control = lock.lock().unwrap();
+            if control.paused {
+                return;
+            }
+            control.paused_duration()
+        };
+        {
+            let mut watch = self.typing_watch.lock().unwrap();
+            let (Some(started), Some(due)) = (watch.started, watch.next_progress_due) else {
+                return;
+            };
+            let overdue = due.elapsed() > STUCK_TYPING_MARGIN
+                && started.elapsed() > watch.estimated + paused + STUCK_TYPING_MARGIN;
+            if !overdue {
+                return;
+            }
+            // 卡住的线程之后恢复时不再结束新的输入
+            watch.session += 1;
+            watch.started = None;
+            watch.next_progress_due = None;
+        }
 
-            let settings = Settings::default();
-            let mut enigo = match Enigo::new(&settings) {
-                Ok(e) => e,
-                Err(e) => {
-                    let err = e.to_string();
-                    error!("{}", state.tr("log.input_init_error", &[("err", err.as_str())]));
-                    state.set_status(&state.tr("status.input_init_error", &[("err", err.as_str())]));
-                    *state.typing_paused.lock().unwrap() = false;
-                    *state.is_typing.lock().unwrap() = false;
-                    return;
-                }
-            };
+        let seconds = STUCK_TYPING_MARGIN.as_secs().to_string();
+        warn!("{}", self.tr("log.typing_stuck_reset", &[("seconds", seconds.as_str())]));
+        self.set_status(&self.t("status.typing_stuck_reset"));
+        self.finish_typing();
+    }
 
-            let mut result = Ok(());
-            let mut rng = rand::thread_rng();
+    /// 剪贴板监控是否应暂停捕获（正在输入或刚结束输入）
+    fn should_pause_capture(&self) -> bool {
+        if !*self.pause_monitor_while_typing.lock().unwrap() {
+            return false;
+        }
+        if self.is_typing() {
+            return true;
+        }
+        self.typing_finished_at
+            .lock()
+            .unwrap()
+            .is_some_and(|finished| finished.elapsed() < MONITOR_COOLDOWN_AFTER_TYPING)
+    }
 
-            for c in text.chars() {
-                state.wait_if_paused();
-                if let Err(e) = enigo.text(&c.to_string()) {
-                    result = Err(e);
-                    break;
-                }
+    /// 清除暂停和取消标志
+    fn reset_typing_control(&self) {
+        let (lock, _) = &*self.typing_control;
+        *lock.lock().unwrap() = TypingControl::default();
+    }
 
-                 // 计算实际延迟
-                let mut actual_delay = delay;
-                if variance_enabled && variance > 0 {
-                    // 在 [delay, delay + variance] 之间随机
-                    let v = rng.gen_range(0..=variance);
-                    actual_delay += v;
-                }
+    fn toggle_typing_pause(&self) -> bool {
+        let (lock, cvar) = &*self.typing_control;
+        let mut control = lock.lock().unwrap();
+        let paused = !control.paused;
+        control.set_paused(paused);
+        self.paused_by_session_lock.store(false, Ordering::SeqCst);
+        cvar.notify_all();
+        control.paused
+    }
 
-                if actual_delay > 0 {
-                    let mut remaining = actual_delay;
-                    while remaining > 0 {
-                        state.wait_if_paused();
-                        let step = remaining.min(50);
-                        thread::sleep(Duration::from_millis(step));
-                        remaining -= step;
-                    }
-                }
-            }
+    /// 设置暂停状态，返回状态是否发生了变化
+    fn set_typing_paused(&self, paused: bool) -> bool {
+        let (lock, cvar) = &*self.typing_control;
+        let mut control = lock.lock().unwrap();
+        if control.paused == paused {
+            return false;
+        }
+        control.set_paused(paused);
+        cvar.notify_all();
+        true
+    }
 
-            if let Err(e) = result {
-                let err = e.to_string();
-                error!("{}", state.tr("log.input_error", &[("err", err.as_str())]));
-                state.set_status(&state.tr("status.input_error", &[("err", err.as_str())]));
+    /// 会话锁定或解锁时按设置暂停、继续或取消正在进行的输入
+    fn handle_session_lock(&self, locked: bool) {
+        let action = *self.on_session_lock.lock().unwrap();
+        if locked {
+            info!("{}", self.t("log.session_locked"));
+            if !self.is_typing() {
+                return;
+            }
+            if action == SessionLockAction::Cancel {
+                self.cancel_typing();
+                self.set_status(&self.t("status.session_lock_cancelled"));
+            } else if self.set_typing_paused(true) {
+                self.paused_by_session_lock.store(true, Ordering::SeqCst);
+                self.set_status(&self.t("status.session_lock_paused"));
+            }
+        } else {
+            info!("{}", self.t("log.session_unlocked"));
+            if !self.paused_by_session_lock.swap(false, Ordering::SeqCst) || !self.is_typing() {
+                return;
+            }
+            if action == SessionLockAction::PauseAndResume {
+                self.set_typing_paused(false);
+                self.set_status(&self.t("status.typing"));
             } else {
-                info!("{}", state.t("log.input_complete"));
-                state.set_status(&state.t("status.input_complete"));
+                self.set_status(&self.t("status.session_unlocked_paused"));
             }
+        }
+    }
 
-            *state.typing_paused.lock().unwrap() = false;
-            *state.is_typing.lock().unwrap() = false;
-        });
+    /// 请求取消当前输入，正在暂停或等待中的输入线程会立即结束
+    fn cancel_typing(&self) {
+        if !self.is_typing() {
+            return;
+        }
+        let (lock, cvar) = &*self.typing_control;
+        lock.lock().unwrap().cancelled = true;
+        cvar.notify_all();
     }
-}
 
-/// GUI 应用程序
-struct CopyTypeApp {
-    /// 共享状态
-    state: SharedState,
-    /// 国际化
-    i18n: I18n,
-    /// 快捷键管理器
-    hotkey_manager: Option<GlobalHotKeyManager>,
-    /// 当前快捷键 ID
-    current_hotkey_id: Option<u32>,
-    /// 当前已注册的快捷键
-    current_hotkey: Option<HotKey>,
-    /// 快捷键配置
-    hotkey_config: HotkeyConfig,
-    /// 临时快捷键配置（编辑中）
-    temp_hotkey_config: HotkeyConfig,
-    /// 应用程序配置
-    app_config: AppConfig,
-    /// 临时应用配置（编辑中）
-    temp_app_config: AppConfig,
-    /// 显示快捷键设置面板
-    show_hotkey_settings: bool,
-    /// 显示应用设置面板
-    show_app_settings: bool,
-    /// 显示权限警告
-    show_permission_warning: bool,
-    /// 快捷键注册错误信息
-    hotkey_register_error: Option<String>,
-    /// 显示启动时快捷键错误弹窗
-    show_startup_hotkey_error: bool,
-    /// 启动时快捷键错误信息
-    startup_hotkey_error: Option<String>,
-    /// 权限状态
-    permission_status: PermissionStatus,
-    /// 系统托盘上下文，必须保持活跃
-    #[allow(dead_code)]
-    tray_context: Option<TrayContext>,
-}
+    /// 按设置播放提示音
+    fn play_cue(&self, cue: SoundCue) {
+        if *self.sound_cues.lock().unwrap() && self.sound_cue_events.lock().unwrap().is_enabled(cue) {
+            sound::play(cue);
+        }
+    }
 
-/// 保持托盘及其菜单项存活的结构体
-struct TrayContext {
-    #[allow(dead_code)]
-    tray: TrayIcon,
-    #[allow(dead_code)]
-    show_item: MenuItem,
-    #[allow(dead_code)]
-    toggle_item: MenuItem,
-    #[allow(dead_code)]
-    exit_item: MenuItem,
-    #[allow(dead_code)]
-    separator: PredefinedMenuItem,
-}
+    /// 按当前速度估算输入剩余部分所需的时间
+    fn typing_time_remaining(&self) -> Duration {
+        let chars = self.typing_remaining_chars.load(Ordering::SeqCst) as u64;
+        match *self.typing_cpm.lock().unwrap() {
+            Some(cpm) if cpm > 0 => Duration::from_secs(chars * 60 / cpm as u64),
+            _ => Duration::ZERO,
+        }
+    }
 
-impl CopyTypeApp {
-    fn new(cc: &eframe::CreationContext<'_>, icon: Option<tray_icon::Icon>) -> Self {
-        // 设置中文字体
-        setup_fonts(&cc.egui_ctx);
+    /// 本次输入累计暂停的时长
+    fn paused_duration(&self) -> Duration {
+        let (lock, _) = &*self.typing_control;
+        lock.lock().unwrap().paused_duration()
+    }
 
-        // 加载配置（统一从 AppConfig 加载）
-        let app_config = AppConfig::load();
-        let hotkey_config = app_config.hotkey.clone();
-        let i18n = I18n::new(&app_config.language);
+    /// 等待用户单击要输入的位置，返回 false 表示已放弃输入
+    fn wait_for_click(&self, timeout: Duration) -> bool {
+        let seconds = timeout.as_secs().to_string();
+        info!("{}", self.t("log.waiting_for_click"));
+        self.set_status(&self.tr("status.waiting_for_click", &[("seconds", seconds.as_str())]));
+        // 等待期间没有输入进展，避免被当作卡住
+        self.note_typing_progress(timeout);
+        let cancelled = || {
+            let (lock, _) = &*self.typing_control;
+            lock.lock().unwrap().cancelled
+        };
+        match mouse_hook::wait_for_click(timeout, cancelled) {
+            ClickWait::Clicked => {
+                self.set_status(&self.t("status.typing"));
+                true
+            }
+            ClickWait::Unsupported => {
+                warn!("{}", self.t("log.click_wait_unsupported"));
+                true
+            }
+            ClickWait::Cancelled => {
+                info!("{}", self.t("log.input_cancelled"));
+                self.set_status(&self.t("status.input_cancelled"));
+                false
+            }
+            ClickWait::TimedOut => {
+                warn!("{}", self.t("log.click_wait_timed_out"));
+                self.set_status(&self.t("status.click_wait_timed_out"));
+                false
+            }
+        }
+    }
 
-        // 检查权限
-        let permission_status = check_permissions(&i18n);
-        let show_permission_warning = !permission_status.all_granted();
+    /// 暂停时阻塞直到继续，返回 false 表示输入已被取消
+    fn wait_if_paused(&self) -> bool {
+        let (lock, cvar) = &*self.typing_control;
+        let control = cvar
+            .wait_while(lock.lock().unwrap(), |c| c.paused && !c.cancelled)
+            .unwrap();
+        !control.cancelled
+    }
 
-        if show_permission_warning {
-            let issues = permission_status.issues.join(", ");
-            warn!("{}", i18n.tr("log.permission_issue", &[("issues", issues.as_str())]));
+    /// 休眠指定毫秒数，暂停的时间不计入，返回 false 表示输入已被取消
+    fn sleep_with_pause(&self, ms: u64) -> bool {
+        let (lock, cvar) = &*self.typing_control;
+        let mut remaining = Duration::from_millis(ms);
+        self.note_typing_progress(remaining);
+        let mut control = lock.lock().unwrap();
+        loop {
+            control = cvar.wait_while(control, |c| c.paused && !c.cancelled).unwrap();
+            if control.cancelled {
+                return false;
+            }
+            if remaining.is_zero() {
+                return true;
+            }
+            let started = Instant::now();
+            control = cvar
+                .wait_timeout_while(control, remaining, |c| !c.paused && !c.cancelled)
+                .unwrap()
+                .0;
+            remaining = remaining.saturating_sub(started.elapsed());
         }
+    }
 
-        // 创建共享状态
-        let state = SharedState::new(i18n.clone());
-        // 初始化 state 中的配置值
-        *state.typing_delay.lock().unwrap() = app_config.typing_delay;
-        *state.typing_variance.lock().unwrap() = app_config.typing_variance;
-        *state.typing_variance_enabled.lock().unwrap() = app_config.typing_variance_enabled;
-        *state.history_enabled.lock().unwrap() = app_config.history_enabled;
-        *state.history_max_items.lock().unwrap() = app_config.history_max_items;
+    /// 快捷键测试等待中时记录按下并返回 true，事件不再触发其他操作
+    fn consume_hotkey_test(&self, pressed: bool) -> bool {
+        let mut test = self.hotkey_test.lock().unwrap();
+        match *test {
+            Some(HotkeyTest::Waiting(since)) if since.elapsed() < HOTKEY_TEST_TIMEOUT => {
+                if pressed {
+                    *test = Some(HotkeyTest::Detected);
+                    info!("{}", self.t("log.hotkey_test_detected"));
+                    if let Some(ctx) = self.egui_ctx.lock().unwrap().as_ref() {
+                        ctx.request_repaint();
+                    }
+                }
+                true
+            }
+            _ => false,
+        }
+    }
 
-        // 根据配置显示/隐藏控制台
-        #[cfg(target_os = "windows")]
-        {
-            if app_config.show_console {
-                show_console_window();
-            } else {
-                hide_console_window();
+    fn should_handle_hotkey(&self) -> bool {
+        let mut last = self.last_hotkey_trigger.lock().unwrap();
+        let now = Instant::now();
+        if let Some(prev) = *last {
+            if now.duration_since(prev) < Duration::from_millis(200) {
+                return false;
             }
         }
+        *last = Some(now);
+        true
+    }
 
-        // 创建系统托盘，并保存上下文
-        let tray_context = if let Some(icon) = icon {
-            create_tray_context(&i18n, icon)
-        } else {
-            warn!("Tray icon unavailable; skipping tray menu.");
-            None
-        };
-        
-        let window_hwnd = get_window_hwnd(cc);
-        let ctx_clone = cc.egui_ctx.clone();
-        let i18n_tray = i18n.clone();
-        let tray_state = state.clone();
+    /// 是否仍处于上次输入完成后的冷却时间内
+    fn in_post_type_cooldown(&self) -> bool {
+        let cooldown = Duration::from_millis(*self.post_type_cooldown_ms.lock().unwrap());
+        self.last_typing_completed
+            .lock()
+            .unwrap()
+            .is_some_and(|completed| completed.elapsed() < cooldown)
+    }
 
-        // 启动独立的托盘事件监控线程
-        // 这解决了主线程阻塞导致托盘事件无法及时处理的问题
-        std::thread::spawn(move || {
-             let receiver = MenuEvent::receiver();
-             loop {
-                 // 使用阻塞式 recv()，这样一有事件就会立即响应
-                 if let Ok(event) = receiver.recv() {
-                    let id_str = event.id.0.as_str();
-                    info!("{}", i18n_tray.tr("log.tray_event", &[("id", id_str)]));
-                    
-                    match id_str {
-                        MENU_EXIT => {
-                            info!("{}", i18n_tray.t("log.tray_exec_exit"));
-                            // 强制退出，不等待任何UI更新
-                            std::process::exit(0);
-                        }
-                        MENU_SHOW => {
-                            info!("{}", i18n_tray.t("log.tray_exec_show"));
-                            tray_state.window_visible.store(true, Ordering::SeqCst);
-                            show_main_window(&ctx_clone, window_hwnd);
-                        }
-                        MENU_TOGGLE => {
-                            let enabled = !tray_state.is_enabled();
-                            let state_text = if enabled {
-                                i18n_tray.t("common.enabled")
-                            } else {
-                                i18n_tray.t("common.disabled")
-                            };
-                            info!(
-                                "{}",
-                                i18n_tray.tr("log.tray_exec_toggle", &[("state", state_text.as_str())])
-                            );
-                            tray_state.set_enabled(enabled);
-                            let status = if enabled {
-                                i18n_tray.t("status.enabled")
-                            } else {
-                                i18n_tray.t("status.disabled")
-                            };
-                            tray_state.set_status(&status);
-                            ctx_clone.request_repaint();
-                        }
-                        _ => {
-                            ctx_clone.request_repaint();
-                        }
-                    }
-                 }
-             }
-        });
+    /// 主快捷键（或绑定的鼠标按键）触发：输入中则暂停/继续（或按设置在暂停时取消），否则开始输入
+    fn handle_main_trigger(&self) {
+        if self.is_typing() {
+            let cancel_when_paused = *self.paused_hotkey_action.lock().unwrap() == PausedHotkeyAction::Cancel;
+            if cancel_when_paused && self.typing_control.0.lock().unwrap().paused {
+                self.cancel_typing();
+                return;
+            }
+            let paused = self.toggle_typing_pause();
+            if paused {
+                let secs = self.typing_time_remaining().as_secs();
+                let time = format!("{:02}:{:02}", secs / 60, secs % 60);
+                self.set_status(&self.tr("status.typing_paused", &[("time", time.as_str())]));
+                self.play_cue(SoundCue::Pause);
+            } else {
+                self.set_status(&self.t("status.typing"));
+            }
+        } else {
+            match *self.main_hotkey_action.lock().unwrap() {
+                MainHotkeyAction::Type => self.execute_typing(),
+                MainHotkeyAction::CopyToClipboard => self.copy_selection(),
+                MainHotkeyAction::ShowPicker => self.show_history_picker(),
+            }
+        }
+    }
 
-        // 启动独立的快捷键事件监控线程
-        // 这解决了窗口隐藏/最小化时快捷键不响应的问题
-        let hotkey_state = state.clone();
-        let i18n_hotkey = i18n.clone();
-        std::thread::spawn(move || {
-            let receiver = GlobalHotKeyEvent::receiver();
-            loop {
-                if let Ok(event) = receiver.recv() {
-                    let current_id = *hotkey_state.hotkey_id.lock().unwrap();
-                    if let Some(id) = current_id {
-                        if event.id == id {
-                            if !hotkey_state.should_handle_hotkey() {
-                                continue;
-                            }
-                            info!("{}", i18n_hotkey.t("log.hotkey_triggered"));
-                            if hotkey_state.is_typing() {
-                                let paused = hotkey_state.toggle_typing_pause();
-                                if paused {
-                                    hotkey_state
-                                        .set_status(&i18n_hotkey.t("status.typing_paused"));
-                                } else {
-                                    hotkey_state.set_status(&i18n_hotkey.t("status.typing"));
-                                }
-                            } else {
-                                hotkey_state.execute_typing();
-                            }
-                        }
-                    }
+    /// 复制前台程序中选中的内容，由剪贴板监控记录到历史
+    fn copy_selection(&self) {
+        let state = self.clone();
+        thread::spawn(move || {
+            // 等待快捷键松开，避免与仍按住的修饰键组合成其他快捷键
+            thread::sleep(Duration::from_millis(250));
+            let result = EnigoBackend::new()
+                .map_err(|e| e.to_string())
+                .and_then(|mut backend| backend.copy_selection().map_err(|e| e.to_string()));
+            match result {
+                Ok(()) => state.set_status(&state.t("status.selection_copied")),
+                Err(err) => {
+                    error!("{}", state.tr("log.copy_selection_fail", &[("err", err.as_str())]));
+                    state.set_status(&state.tr("status.copy_selection_fail", &[("err", err.as_str())]));
                 }
             }
         });
+    }
 
-        let mut app = Self {
-            state,
-            i18n: i18n.clone(),
-            hotkey_manager: None,
-            current_hotkey_id: None,
-            current_hotkey: None,
-            hotkey_config: hotkey_config.clone(),
-            temp_hotkey_config: hotkey_config,
-            app_config: app_config.clone(),
-            temp_app_config: app_config.clone(),
-            show_hotkey_settings: false,
-            show_app_settings: false,
-            show_permission_warning,
-            hotkey_register_error: None,
-            show_startup_hotkey_error: false,
-            startup_hotkey_error: None,
-            permission_status,
-            tray_context,
+    /// 显示主窗口并弹出历史选择列表
+    fn show_history_picker(&self) {
+        let Some(ctx) = self.egui_ctx.lock().unwrap().clone() else {
+            return;
         };
+        let was_visible = self.window_visible.swap(true, Ordering::SeqCst);
+        self.history_picker_hide_after.store(!was_visible, Ordering::SeqCst);
+        self.history_picker_requested.store(true, Ordering::SeqCst);
+        show_main_window(&ctx, *self.own_window.lock().unwrap());
+    }
 
-        // 初始化快捷键
-        app.init_hotkey();
-
-        // 启动剪贴板监控
-        app.start_clipboard_monitor();
-
-        // 如果设置为启动时最小化，则隐藏窗口
-        if app_config.start_minimized {
-            app.state.window_visible.store(false, Ordering::SeqCst);
-            if let Some(ctx) = cc.egui_ctx.clone().into() {
-                let ctx: egui::Context = ctx;
-                ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+    /// 读取不到文本时检测剪贴板中的图片或文件，读取到文本后清除提示
+    ///
+    /// 已检测到非文本内容后不再重复检测，避免每次轮询都解码图片。
+    fn update_non_text_content(&self, clipboard: &mut Clipboard, selection: CaptureSelection, text: Option<&str>) {
+        let mut content = self.non_text_content.lock().unwrap();
+        if text.is_some_and(|text| !text.is_empty()) {
+            *content = None;
+            return;
+        }
+        // 主选区只有文本；同时监控两者时内容未变化也读不到文本，无法据此判断
+        if text.is_some()
+            || selection != CaptureSelection::Clipboard
+            || content.is_some()
+            || *self.non_text_clipboard.lock().unwrap() != NonTextClipboard::ShowNotice
+        {
+            return;
+        }
+        *content = clipboard_format::detect_non_text(clipboard);
+        if content.is_some() {
+            debug!("{}", self.t("log.non_text_clipboard"));
+            if let Some(ctx) = self.egui_ctx.lock().unwrap().as_ref() {
+                ctx.request_repaint();
             }
         }
+    }
 
-        app
+    /// 显示主窗口，请求用户确认本次输入
+    fn request_command_confirm(&self, kind: ConfirmKind, request: TypingRequest) {
+        let Some(ctx) = self.egui_ctx.lock().unwrap().clone() else {
+            // 没有界面（如 --rpc 模式）时无法确认，直接放弃输入
+            self.set_status(&self.t(match kind {
+                ConfirmKind::ShellCommands => "status.shell_commands_blocked",
+                ConfirmKind::CrossApp { .. } => "status.cross_app_blocked",
+                ConfirmKind::WatchedFile { .. } => "status.watch_file_blocked",
+            }));
+            return;
+        };
+        let was_visible = self.window_visible.swap(true, Ordering::SeqCst);
+        *self.pending_command_confirm.lock().unwrap() = Some(CommandConfirm {
+            kind,
+            request,
+            hide_after: !was_visible,
+        });
+        show_main_window(&ctx, *self.own_window.lock().unwrap());
     }
 
-    /// 初始化快捷键管理器
-    fn init_hotkey(&mut self) {
-        match GlobalHotKeyManager::new() {
-            Ok(manager) => {
-                if let Some(hotkey) = self.hotkey_config.to_global_hotkey() {
-                    match manager.register(hotkey) {
-                        Ok(()) => {
-                            self.current_hotkey_id = Some(hotkey.id());
-                            self.current_hotkey = Some(hotkey);
-                            *self.state.hotkey_id.lock().unwrap() = Some(hotkey.id());
-                            let display = self.hotkey_config.display();
-                            info!(
-                                "{}",
-                                self.i18n
-                                    .tr("log.hotkey_registered", &[("hotkey", display.as_str())])
-                            );
-                            self.state.set_status(
-                                &self
-                                    .i18n
-                                    .tr("status.hotkey_registered", &[("hotkey", display.as_str())]),
-                            );
-                        }
-                        Err(e) => {
-                            let err = e.to_string();
-                            error!(
-                                "{}",
-                                self.i18n
-                                    .tr("log.hotkey_register_fail", &[("err", err.as_str())])
-                            );
-                            self.state.set_status(
-                                &self
-                                    .i18n
-                                    .tr("status.hotkey_register_fail", &[("err", err.as_str())]),
-                            );
-                            // 保存用户友好的错误信息
-                            let friendly_error = if err.contains("already register") {
-                                self.i18n.t("ui.error_hotkey_already_registered")
-                            } else {
-                                self.i18n.tr("ui.error_hotkey_register_failed", &[("error", err.as_str())])
-                            };
-                            self.startup_hotkey_error =
-                                Some(format!("{} - {}", self.hotkey_config.display(), friendly_error));
-                            self.show_startup_hotkey_error = true;
-                        }
-                    }
-                }
-                self.hotkey_manager = Some(manager);
+    /// 通过外部命令预处理文本，失败时记录原因并返回原文本
+    fn preprocess_text(&self, command: &str, text: String, timeout: Duration) -> String {
+        let err = match preprocess::run(command, &text, timeout) {
+            Ok(output) => {
+                info!("{}", self.t("log.preprocess_done"));
+                return output;
             }
-            Err(e) => {
+            Err(PreprocessError::Spawn(e)) => {
                 let err = e.to_string();
-                error!(
-                    "{}",
-                    self.i18n
-                        .tr("log.hotkey_manager_fail", &[("err", err.as_str())])
-                );
-                self.state
-                    .set_status(
-                        &self
-                            .i18n
-                            .tr("status.hotkey_manager_fail", &[("err", err.as_str())]),
-                    );
-                // 保存用户友好的错误信息
-                self.startup_hotkey_error = Some(self.i18n.t("ui.error_hotkey_manager_init_failed"));
-                self.show_startup_hotkey_error = true;
+                self.tr("log.preprocess_spawn_failed", &[("err", err.as_str())])
             }
-        }
+            Err(PreprocessError::Timeout) => {
+                let seconds = timeout.as_secs_f64().to_string();
+                self.tr("log.preprocess_timed_out", &[("seconds", seconds.as_str())])
+            }
+            Err(PreprocessError::Failed { code, stderr }) => {
+                let code = code.map_or_else(|| "-".to_string(), |code| code.to_string());
+                self.tr("log.preprocess_exit_failed", &[("code", code.as_str()), ("stderr", stderr.as_str())])
+            }
+            Err(PreprocessError::InvalidOutput) => self.t("log.preprocess_invalid_output"),
+        };
+        warn!("{}", err);
+        self.set_status(&self.t("status.preprocess_failed"));
+        text
     }
 
-    /// 更新快捷键
-    fn update_hotkey(&mut self) {
-        // 先尝试注册新的快捷键（不注销旧的）
-        if let Some(manager) = &self.hotkey_manager {
-            if let Some(new_hotkey) = self.temp_hotkey_config.to_global_hotkey() {
-                if let Some(current_hotkey) = self.current_hotkey {
-                    if current_hotkey == new_hotkey {
-                        self.hotkey_register_error = None;
-                        return;
-                    }
-                }
-                match manager.register(new_hotkey) {
-                    Ok(()) => {
-                        // 注册成功，现在注销旧的快捷键
-                        if let Some(old_hotkey) = self.current_hotkey {
-                            if let Err(e) = manager.unregister(old_hotkey) {
-                                let err = e.to_string();
-                                warn!(
-                                    "{}",
-                                    self.i18n
-                                        .tr("log.hotkey_unregister_fail", &[("err", err.as_str())])
-                                );
-                            } else {
-                                info!("{}", self.i18n.t("log.hotkey_unregistered"));
-                            }
-                        }
+    /// 剪贴板文本的来源程序与当前前台程序不同时返回 (来源, 前台)
+    ///
+    /// 任一方无法确定，或前台是本程序时返回 None。
+    fn cross_app_target(&self) -> Option<(String, String)> {
+        let source = self.last_clipboard_source.lock().unwrap().clone()?;
+        let target = process_info::foreground_exe()?;
+        let own_exe = std::env::current_exe()
+            .ok()
+            .and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()));
+        if source.eq_ignore_ascii_case(&target) || own_exe.is_some_and(|own| own.eq_ignore_ascii_case(&target)) {
+            return None;
+        }
+        Some((source, target))
+    }
 
-                        // 更新配置
-                        self.hotkey_config = self.temp_hotkey_config.clone();
-                        self.current_hotkey_id = Some(new_hotkey.id());
-                        self.current_hotkey = Some(new_hotkey);
-                        *self.state.hotkey_id.lock().unwrap() = Some(new_hotkey.id());
-                        
-                        let display = self.hotkey_config.display();
-                        info!(
-                            "{}",
-                            self.i18n
-                                .tr("log.hotkey_updated", &[("hotkey", display.as_str())])
-                        );
-                        self.state.set_status(
-                            &self
-                                .i18n
-                                .tr("status.hotkey_updated", &[("hotkey", display.as_str())]),
-                        );
+    /// 监视的文件内容变化后输入新的内容，按设置先确认
+    fn type_watched_file(&self, path: &std::path::Path, text: String) {
+        if !self.is_enabled() {
+            warn!("{}", self.t("log.request_ignored_disabled"));
+            return;
+        }
+        let file = path.display().to_string();
+        if text.trim().is_empty() {
+            debug!("{}", self.tr("log.watch_file_empty", &[("file", file.as_str())]));
+            return;
+        }
+        if self.last_typed_text.lock().unwrap().as_deref() == Some(text.as_str()) {
+            // 写入方重写了相同的内容（或把刚输入的内容写回文件），不重复输入
+            debug!("{}", self.tr("log.watch_file_same_as_last", &[("file", file.as_str())]));
+            return;
+        }
+        info!("{}", self.tr("log.watch_file_changed", &[("file", file.as_str())]));
+        let request = TypingRequest {
+            text: Some(text),
+            ..Default::default()
+        };
+        if *self.watch_file_confirm.lock().unwrap() {
+            self.set_status(&self.t("status.watch_file_confirm"));
+            self.request_command_confirm(ConfirmKind::WatchedFile { file }, request);
+        } else {
+            self.execute_typing_request(request);
+        }
+    }
 
-                        // 保存配置（更新 app_config.hotkey 并保存）
-                        self.app_config.hotkey = self.hotkey_config.clone();
-                        if let Err(e) = self.app_config.save() {
-                            let err = e.to_string();
-                            error!(
-                                "{}",
-                                self.i18n
-                                    .tr("log.save_config_fail", &[("err", err.as_str())])
-                            );
-                        }
+    /// 取消快捷键：取消输入；在设置的时间内连按两次则直接退出程序
+    fn handle_cancel_hotkey(&self) {
+        let window = Duration::from_millis(*self.double_cancel_quit_ms.lock().unwrap());
+        let now = Instant::now();
+        let previous = self.last_cancel_press.lock().unwrap().replace(now);
+        if !window.is_zero() && previous.is_some_and(|prev| now.duration_since(prev) <= window) {
+            warn!("{}", self.t("log.double_cancel_quit"));
+            // 界面可能已无响应，不经过界面直接退出
+            std::process::exit(0);
+        }
+        self.cancel_typing();
+    }
 
-                        // 清除错误信息
-                        self.hotkey_register_error = None;
-                    }
-                    Err(e) => {
-                        // 注册失败，保存错误信息
-                        let err = e.to_string();
-                        error!(
-                            "{}",
-                            self.i18n
-                                .tr("log.hotkey_register_fail", &[("err", err.as_str())])
-                        );
-                        // 保存用户友好的错误信息
-                        let friendly_error = if err.contains("already register") {
-                            self.i18n.t("ui.error_hotkey_already_registered")
-                        } else {
-                            err
-                        };
-                        self.hotkey_register_error = Some(friendly_error);
-                    }
-                }
-            }
+    /// 执行附加快捷键绑定的操作
+    fn run_hotkey_action(&self, action: HotkeyAction) {
+        match action {
+            HotkeyAction::RepeatLast => self.repeat_last_typing(false),
+            HotkeyAction::TypeScratchpad => self.type_scratchpad(false),
+            HotkeyAction::ShowPicker => self.show_history_picker(),
+            HotkeyAction::ToggleAccumulate => self.set_accumulate(!self.accumulate.load(Ordering::SeqCst)),
+            HotkeyAction::CancelTyping => self.handle_cancel_hotkey(),
         }
     }
 
-    /// 启动剪贴板监控线程
-    fn start_clipboard_monitor(&self) {
-        let state = self.state.clone();
+    /// 记录当前剪贴板内容的来源程序，并检查是否允许捕获
+    fn check_clipboard_source(&self) -> bool {
+        let source = process_info::clipboard_owner_exe();
+        let allowed = self.source_filter.lock().unwrap().allows(source.as_deref());
+        if !allowed {
+            let app = source.as_deref().unwrap_or("");
+            info!("{}", self.tr("log.clipboard_source_filtered", &[("app", app)]));
+        }
+        *self.last_clipboard_source.lock().unwrap() = source;
+        allowed
+    }
 
-        thread::spawn(move || {
-            let mut clipboard = match Clipboard::new() {
-                Ok(cb) => cb,
-                Err(e) => {
-                    let err = e.to_string();
-                    error!("{}", state.tr("log.clipboard_init_fail", &[("err", err.as_str())]));
-                    state.set_status(&state.tr("status.clipboard_init_fail", &[("err", err.as_str())]));
-                    return;
-                }
+    /// 下一次检查剪贴板前等待的时间：使用电池时按设置放慢
+    fn clipboard_poll_interval(&self) -> Duration {
+        let on_battery = power::on_battery();
+        let changed = {
+            let mut last = self.on_battery.lock().unwrap();
+            std::mem::replace(&mut *last, on_battery) != on_battery
+        };
+        if changed && on_battery.is_some() {
+            let key = if on_battery == Some(true) {
+                "log.power_on_battery"
+            } else {
+                "log.power_on_ac"
             };
+            info!("{}", self.t(key));
+        }
+        if on_battery == Some(true) {
+            Duration::from_millis(*self.battery_poll_interval_ms.lock().unwrap()).max(CLIPBOARD_POLL_INTERVAL)
+        } else {
+            CLIPBOARD_POLL_INTERVAL
+        }
+    }
 
-            info!("{}", state.t("log.clipboard_monitor_started"));
-
-            loop {
-                // 只在启用时监控
-                if state.is_enabled() {
-                    if let Ok(text) = clipboard.get_text() {
-                        let last = state.last_clipboard_text.lock().unwrap().clone();
-
-                        if text != last && !text.is_empty() {
-                            let len_str = text.len().to_string();
-                            info!(
-                                "{}",
-                                state.tr("log.clipboard_changed", &[("len", len_str.as_str())])
-                            );
-                            
-                            // 安全地生成预览，如果 truncate_text panic 就用简单方式
-                            let preview = std::panic::catch_unwind(|| truncate_text(&text, 50))
-                                .unwrap_or_else(|_| {
-                                    error!("truncate_text 发生错误，使用简单截断");
-                                    text.chars().take(50).collect::<String>() + "..."
-                                });
-                            debug!("{}", state.tr("log.clipboard_preview", &[("preview", preview.as_str())]));
+    /// 前台程序是否在屏蔽列表中，此时不捕获剪贴板变化
+    fn foreground_blocks_capture(&self) -> bool {
+        let blocklist = self.capture_blocklist.lock().unwrap();
+        if blocklist.is_empty() {
+            return false;
+        }
+        let Some(exe) = process_info::foreground_exe() else {
+            return false;
+        };
+        let blocked = blocklist.iter().any(|app| app.eq_ignore_ascii_case(&exe));
+        if blocked {
+            info!("{}", self.tr("log.clipboard_foreground_blocked", &[("app", exe.as_str())]));
+        }
+        blocked
+    }
 
-                            *state.clipboard_text.lock().unwrap() = text.clone();
-                            *state.last_clipboard_text.lock().unwrap() = text.clone();
-                            state.record_history(text);
-                        }
-                    }
-                }
+    /// 记录当前前台窗口，忽略本程序自身的窗口
+    fn track_foreground_window(&self) {
+        let own = *self.own_window.lock().unwrap();
+        if let Some(hwnd) = foreground_window().filter(|&hwnd| Some(hwnd) != own) {
+            *self.last_external_window.lock().unwrap() = Some(hwnd);
+        }
+    }
 
-                thread::sleep(Duration::from_millis(500));
+    /// 输入前将焦点切回外部窗口
+    ///
+    /// 前台窗口是本程序自身时（例如点击了手动输入按钮），切换到最近一次记录的外部窗口，
+    /// 避免把文本输入到本程序中。
+    fn refocus_external_window(&self) {
+        // 无法获取本程序窗口句柄（非 Windows 平台）时不做处理
+        let Some(own) = *self.own_window.lock().unwrap() else {
+            return;
+        };
+        if foreground_window().is_some_and(|hwnd| hwnd != own) {
+            return;
+        }
+        match *self.last_external_window.lock().unwrap() {
+            Some(hwnd) if focus_window(hwnd) => {
+                debug!("{}", self.t("log.refocus_external_window"));
             }
-        });
+            _ => warn!("{}", self.t("log.refocus_no_target")),
+        }
     }
 
-    /// 模拟键盘输入文本
-    fn type_text(&self) {
-        self.state.execute_typing();
+    fn t(&self, key: &str) -> String {
+        self.i18n.t(key)
     }
 
-    /// 处理快捷键事件
-    fn handle_hotkey_events(&self) {
-        // 快捷键事件现在由后台线程处理
+    fn tr<'a>(&self, key: &str, args: &[(&str, &'a str)]) -> String {
+        self.i18n.tr(key, args)
     }
 
-}
-
-impl eframe::App for CopyTypeApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let i18n = self.i18n.clone();
-        // 处理快捷键事件
-        self.handle_hotkey_events();
-
-        // 请求持续重绘以处理事件
-        ctx.request_repaint_after(Duration::from_millis(50));
+    fn record_history(&self, text: String) {
+        if !*self.history_enabled.lock().unwrap() {
+            return;
+        }
+        let max_items = *self.history_max_items.lock().unwrap();
+        if max_items == 0 {
+            return;
+        }
+        if self.skip_next_record.swap(false, Ordering::SeqCst) {
+            info!("{}", self.t("log.history_record_skipped"));
+            return;
+        }
 
-        // 权限警告窗口
-        if self.show_permission_warning {
-            egui::Window::new(i18n.t("ui.title_permission_warning"))
-                .collapsible(false)
-                .resizable(false)
-                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
-                .show(ctx, |ui| {
-                    ui.label(i18n.t("ui.label_permission_issues"));
-                    ui.add_space(10.0);
+        let source = self.last_clipboard_source.lock().unwrap().clone();
+        self.add_history_entries(vec![text], source, max_items);
+    }
 
-                    if let Some(msg) = self.permission_status.get_warning_message(&i18n) {
-                        ui.label(msg);
-                    }
+    /// 把文本依次加入历史，超出条数或内存限制时删除最旧的记录，返回加入的条数
+    ///
+    /// `source` 为文本的来源程序，从文件导入时为 None。
+    fn add_history_entries(&self, texts: Vec<String>, source: Option<String>, max_items: u32) -> usize {
+        let mut history = self.clipboard_history.lock().unwrap();
+        let mut memory_used = self.history_memory_used.lock().unwrap();
+        let mut added = 0;
 
-                    ui.add_space(10.0);
-                    ui.separator();
-                    ui.add_space(10.0);
+        for text in texts {
+            // 计算文本大小（字节）
+            let text_size = text.len();
 
-                    ui.collapsing(i18n.t("ui.label_fix_suggestions"), |ui| {
-                        ui.label(get_permission_fix_instructions(&i18n));
-                    });
+            // 如果单条文本超过10MB，则不存储
+            if text_size > MAX_SINGLE_ITEM_SIZE {
+                warn!(
+                    "{}",
+                    self.tr(
+                        "log.item_too_large",
+                        &[
+                            ("size", &format!("{:.2}MB", text_size as f64 / 1024.0 / 1024.0)),
+                            ("max", &format!("{:.2}MB", MAX_SINGLE_ITEM_SIZE as f64 / 1024.0 / 1024.0))
+                        ]
+                    )
+                );
+                continue;
+            }
 
-                    ui.add_space(10.0);
+            // 重复的文本合并为一条，保留使用次数并移到最新位置
+            let mut entry = HistoryEntry::new(text, source.clone());
+            if let Some(pos) = history.iter().position(|item| item.text == entry.text) {
+                let existing = history.remove(pos);
+                *memory_used = memory_used.saturating_sub(existing.text.len());
+                entry.count = existing.count;
+                entry.source = entry.source.or(existing.source);
+            }
 
+            // 如果新增后总内存超过50MB，删除最旧的记录直到能够放下
+            while *memory_used + text_size > MAX_TOTAL_MEMORY && !history.is_empty() {
+                let removed = history.remove(0);
+                let removed_size = removed.text.len();
+                *memory_used = memory_used.saturating_sub(removed_size);
+                debug!(
+                    "{}",
+                    self.tr(
+                        "log.removed_old_item",
+                        &[
+                            ("size", &format!("{:.2}KB", removed_size as f64 / 1024.0)),
+                            ("remaining", &format!("{:.2}MB", *memory_used as f64 / 1024.0 / 1024.0))
+                        ]
+                    )
+                );
+            }
+
+            // 添加新记录
+            history.push(entry);
+            *memory_used += text_size;
+            added += 1;
+        }
+
+        // 检查是否超出条数限制
+        if history.len() > max_items as usize {
+            let overflow = history.len() - max_items as usize;
+            for item in history.drain(0..overflow) {
+                *memory_used = memory_used.saturating_sub(item.text.len());
+            }
+        }
+        
+        debug!(
+            "{}",
+            self.tr(
+                "log.history_stats",
+                &[
+                    ("count", &history.len().to_string()),
+                    ("memory", &format!("{:.2}MB", *memory_used as f64 / 1024.0 / 1024.0))
+                ]
+            )
+        );
+
+        #[cfg(debug_assertions)]
+        Self::assert_history_memory_sync(&history, *memory_used);
+
+        self.save_history(&history);
+        added
+    }
+
+    /// 从文件导入片段到历史，返回导入的条数
+    fn import_history(&self, path: &std::path::Path) -> Result<usize, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        let snippets = history::parse_snippets(&content)?;
+        let max_items = *self.history_max_items.lock().unwrap();
+        Ok(self.add_history_entries(snippets, None, max_items))
+    }
+
+    fn clear_history(&self) {
+        let mut history = self.clipboard_history.lock().unwrap();
+        let mut memory_used = self.history_memory_used.lock().unwrap();
+        history.clear();
+        *memory_used = 0;
+
+        #[cfg(debug_assertions)]
+        Self::assert_history_memory_sync(&history, *memory_used);
+
+        self.save_history(&history);
+    }
+
+    /// 用从磁盘加载的条目替换当前历史
+    fn load_history(&self, entries: Vec<HistoryEntry>) {
+        let mut history = self.clipboard_history.lock().unwrap();
+        let mut memory_used = self.history_memory_used.lock().unwrap();
+        *memory_used = entries.iter().map(|item| item.text.len()).sum();
+        *history = entries;
+    }
+
+    /// 解锁加密的历史：已保存的历史排在前面，解锁前捕获的条目接在其后，并立即保存
+    fn unlock_history(&self, entries: Vec<HistoryEntry>, key: HistoryKey) {
+        let captured = std::mem::take(&mut *self.clipboard_history.lock().unwrap());
+        let mut merged = entries;
+        merged.extend(captured);
+        self.load_history(merged);
+        *self.history_key.lock().unwrap() = Some(key);
+        self.trim_history();
+    }
+
+    /// 记录一次从历史中输入，增加对应条目的使用次数
+    fn record_history_use(&self, text: &str) {
+        let mut history = self.clipboard_history.lock().unwrap();
+        if let Some(item) = history.iter_mut().rev().find(|item| item.text == text) {
+            item.count = item.count.saturating_add(1);
+        }
+        self.save_history(&history);
+    }
+
+    /// 开启持久化时把历史写入磁盘
+    fn save_history(&self, history: &[HistoryEntry]) {
+        if !*self.persist_history.lock().unwrap() {
+            return;
+        }
+        let result = if *self.encrypt_history.lock().unwrap() {
+            // 未解锁时不保存，避免覆盖尚未解密的历史
+            let Some(key) = self.history_key.lock().unwrap().clone() else {
+                return;
+            };
+            history::save_encrypted(history, &key)
+        } else {
+            history::save(history)
+        };
+        if let Err(e) = result {
+            let err = e.to_string();
+            error!("{}", self.tr("log.save_history_fail", &[("err", err.as_str())]));
+        }
+    }
+
+    /// 清除记录时间早于 `age` 之前的历史，返回清除的条数
+    fn clear_history_older_than(&self, age: Duration) -> usize {
+        let mut history = self.clipboard_history.lock().unwrap();
+        let mut memory_used = self.history_memory_used.lock().unwrap();
+        let before = history.len();
+        history.retain(|item| {
+            if item.age() > age {
+                *memory_used = memory_used.saturating_sub(item.text.len());
+                false
+            } else {
+                true
+            }
+        });
+
+        #[cfg(debug_assertions)]
+        Self::assert_history_memory_sync(&history, *memory_used);
+
+        self.save_history(&history);
+        before - history.len()
+    }
+
+    fn trim_history(&self) {
+        let max_items = *self.history_max_items.lock().unwrap();
+        if max_items == 0 {
+            self.clear_history();
+            return;
+        }
+        let mut history = self.clipboard_history.lock().unwrap();
+        let mut memory_used = self.history_memory_used.lock().unwrap();
+        if history.len() > max_items as usize {
+            let overflow = history.len() - max_items as usize;
+            for item in history.drain(0..overflow) {
+                *memory_used = memory_used.saturating_sub(item.text.len());
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        Self::assert_history_memory_sync(&history, *memory_used);
+
+        self.save_history(&history);
+    }
+
+    #[cfg(debug_assertions)]
+    fn assert_history_memory_sync(history: &[HistoryEntry], memory_used: usize) {
+        let computed: usize = history.iter().map(|item| item.text.len()).sum();
+        debug_assert_eq!(
+            memory_used,
+            computed,
+            "history_memory_used out of sync: tracked={}, actual={}",
+            memory_used,
+            computed
+        );
+    }
+    
+    /// 执行模拟输入逻辑（输入当前剪贴板文本）
+    fn execute_typing(&self) {
+        self.execute_typing_request(TypingRequest::default());
+    }
+
+    /// 重复上一次输入的文本，即使剪贴板已经变化
+    fn repeat_last_typing(&self, refocus: bool) {
+        let last = self.last_typed_text.lock().unwrap().clone();
+        match last {
+            Some(text) => self.execute_typing_request(TypingRequest {
+                text: Some(text),
+                refocus,
+                ..Default::default()
+            }),
+            None => {
+                warn!("{}", self.t("log.nothing_to_repeat"));
+                self.set_status(&self.t("status.nothing_to_repeat"));
+            }
+        }
+    }
+
+    /// 输入便签内容
+    fn type_scratchpad(&self, refocus: bool) {
+        let text = self.scratchpad.lock().unwrap().clone();
+        if text.is_empty() {
+            warn!("{}", self.t("log.scratchpad_empty"));
+            self.set_status(&self.t("status.scratchpad_empty"));
+            return;
+        }
+        self.execute_typing_request(TypingRequest {
+            text: Some(text),
+            refocus,
+            ..Default::default()
+        });
+    }
+
+    /// 依次执行宏的各个步骤
+    fn run_macro(&self, macro_def: &Macro, refocus: bool) {
+        let tokens = macro_def.tokens();
+        if tokens.is_empty() {
+            warn!("{}", self.tr("log.macro_empty", &[("name", macro_def.name.as_str())]));
+            self.set_status(&self.tr("status.macro_empty", &[("name", macro_def.name.as_str())]));
+            return;
+        }
+        info!("{}", self.tr("log.macro_started", &[("name", macro_def.name.as_str())]));
+        self.execute_typing_request(TypingRequest {
+            tokens: Some(tokens),
+            refocus,
+            ..Default::default()
+        });
+    }
+
+    /// 把文本存入寄存器，文本为空时清空该寄存器
+    fn set_register(&self, name: char, text: String) {
+        let name_str = name.to_string();
+        let mut registers = self.registers.lock().unwrap();
+        if text.is_empty() {
+            registers.remove(&name);
+            info!("{}", self.tr("log.register_cleared", &[("register", name_str.as_str())]));
+        } else {
+            let len = text.chars().count().to_string();
+            registers.insert(name, text);
+            info!(
+                "{}",
+                self.tr("log.register_stored", &[("register", name_str.as_str()), ("len", len.as_str())])
+            );
+        }
+    }
+
+    /// 输入寄存器中的文本
+    fn type_register(&self, name: char, refocus: bool) {
+        let text = self.registers.lock().unwrap().get(&name).cloned();
+        let Some(text) = text else {
+            let name_str = name.to_string();
+            warn!("{}", self.tr("log.register_empty", &[("register", name_str.as_str())]));
+            self.set_status(&self.tr("status.register_empty", &[("register", name_str.as_str())]));
+            return;
+        };
+        self.execute_typing_request(TypingRequest {
+            text: Some(text),
+            refocus,
+            ..Default::default()
+        });
+    }
+
+    /// 执行一次输入请求
+    fn execute_typing_request(&self, request: TypingRequest) {
+        if !self.is_enabled() {
+            warn!("{}", self.t("log.request_ignored_disabled"));
+            return;
+        }
+        if self.in_post_type_cooldown() {
+            warn!("{}", self.t("log.request_ignored_cooldown"));
+            return;
+        }
+
+        // 检查是否正在输入
+        {
+            let mut typing = self.is_typing.lock().unwrap();
+            if *typing {
+                warn!("{}", self.t("log.request_ignored_typing"));
+                return;
+            }
+            *typing = true;
+        }
+
+        self.reset_typing_control();
+        let session = self.start_typing_session();
+        self.set_status(&self.t("status.typing"));
+        self.play_cue(SoundCue::Start);
+        let state = self.clone();
+        let (delay, variance) = match request.speed {
+            Some(preset) => (preset.delay, preset.variance),
+            None => (
+                *self.typing_delay.lock().unwrap(),
+                *self.typing_variance.lock().unwrap(),
+            ),
+        };
+        let variance_enabled = *self.typing_variance_enabled.lock().unwrap();
+        let variance_seed = *self.variance_seed.lock().unwrap();
+        let min_delay = *self.min_enforced_delay.lock().unwrap();
+        let line_delay = *self.line_delay_ms.lock().unwrap();
+        let split_long_lines_at = *self.split_long_lines_at.lock().unwrap();
+        let char_class_delays = *self.char_class_delays.lock().unwrap();
+        let max_type_duration = *self.max_type_duration_ms.lock().unwrap();
+        let key_tokens_enabled = *self.key_tokens_enabled.lock().unwrap();
+        let hide_after = request.refocus && *self.auto_hide_after_type.lock().unwrap();
+        let refocus_self_after = request.refocus && *self.refocus_self_after_type.lock().unwrap();
+        let restore_clipboard = *self.restore_clipboard_to_typed.lock().unwrap();
+        let modifier_release_timeout = Duration::from_millis(*self.modifier_release_timeout_ms.lock().unwrap());
+        let refocus = request.refocus && *self.refocus_external_window.lock().unwrap();
+        let block_password_fields = *self.block_password_fields.lock().unwrap();
+        let click_wait_timeout = *self.click_wait_timeout.lock().unwrap();
+        let column_paste = *self.column_paste.lock().unwrap();
+        let line_by_line = *self.line_by_line.lock().unwrap();
+        let emoji_mode = *self.emoji_mode.lock().unwrap();
+        let paste_rich = *self.paste_rich.lock().unwrap();
+        let markdown_mode = *self.markdown_mode.lock().unwrap();
+        let (progress_log_every, progress_log_interval) = *self.progress_log_throttle.lock().unwrap();
+        let transform = self.transform.lock().unwrap().clone();
+        let confirm_commands = transform.terminal_safe && *self.confirm_shell_commands.lock().unwrap();
+        let confirm_cross_app = *self.confirm_cross_app.lock().unwrap();
+        let preprocess_command = self.preprocess_command.lock().unwrap().clone();
+        let preprocess_timeout = Duration::from_millis(*self.preprocess_timeout_ms.lock().unwrap());
+
+        thread::spawn(move || {
+            let _guard = TypingGuard {
+                state: state.clone(),
+                session,
+            };
+            if refocus {
+                state.refocus_external_window();
+            }
+
+            // 等待松开快捷键的修饰键，防止与输入的字符组合成快捷键；等待期间没有输入进展，避免被当作卡住
+            state.note_typing_progress(modifier_release_timeout);
+            if !modifier_keys::wait_for_release(modifier_release_timeout) {
+                warn!("{}", state.t("log.modifier_release_timeout"));
+            }
+
+            // 等待用户点击要输入的位置
+            if let Some(timeout) = click_wait_timeout {
+                if !state.wait_for_click(timeout) {
+                    return;
+                }
+            }
+
+            if block_password_fields && password_field::focused_is_password() == Some(true) {
+                warn!("{}", state.t("log.password_field_blocked"));
+                state.set_status(&state.t("status.password_field_blocked"));
+                return;
+            }
+
+            // 输入剪贴板文本时，前台程序与复制时的来源程序不同则先确认；无法确定任一方时直接输入
+            let from_clipboard = !request.resume && request.text.is_none() && request.tokens.is_none();
+            if confirm_cross_app && from_clipboard && !request.confirmed {
+                if let Some((source, target)) = state.cross_app_target() {
+                    info!(
+                        "{}",
+                        state.tr("log.cross_app_detected", &[("source", source.as_str()), ("target", target.as_str())])
+                    );
+                    state.set_status(&state.t("status.cross_app_confirm"));
+                    state.request_command_confirm(
+                        ConfirmKind::CrossApp { source, target },
+                        TypingRequest {
+                            speed: request.speed,
+                            ..Default::default()
+                        },
+                    );
+                    return;
+                }
+            }
+
+            // 剪贴板带有富文本时直接粘贴，保留原有格式。
+            // 只在粘贴的内容就是要输入的文本时这样做：累积的文本、被过滤而未捕获的复制、
+            // Markdown 模式、文本转换、预处理和按键标记都会使两者不同，此时照常逐字输入
+            let paste_rich = paste_rich
+                && !line_by_line
+                && !request.resume
+                && request.text.is_none()
+                && request.tokens.is_none()
+                && !markdown_mode
+                && transform == TransformConfig::default()
+                && preprocess_command.is_empty()
+                && !state.accumulate.load(Ordering::SeqCst)
+                && Clipboard::new().is_ok_and(|mut clipboard| {
+                    let captured = state.clipboard_text.lock().unwrap().clone();
+                    clipboard_format::has_rich_text(&mut clipboard)
+                        && clipboard.get_text().is_ok_and(|text| text == captured)
+                        && (!key_tokens_enabled
+                            || tokenize(&captured).iter().all(|token| matches!(token, TypingToken::Text(_))))
+                });
+            if paste_rich {
+                let result = EnigoBackend::new()
+                    .map_err(|e| e.to_string())
+                    .and_then(|mut backend| backend.paste_clipboard().map_err(|e| e.to_string()));
+                match result {
+                    Ok(()) => {
+                        info!("{}", state.t("log.rich_text_pasted"));
+                        *state.last_typing_completed.lock().unwrap() = Some(Instant::now());
+                        state.set_status(&state.t("status.rich_text_pasted"));
+                        state.play_cue(SoundCue::Complete);
+                        if hide_after {
+                            state.hide_window_requested.store(true, Ordering::SeqCst);
+                        } else if refocus_self_after {
+                            state.refocus_self_requested.store(true, Ordering::SeqCst);
+                        }
+                    }
+                    Err(err) => {
+                        error!("{}", state.tr("log.input_error", &[("err", err.as_str())]));
+                        state.set_status(&state.tr("status.input_error", &[("err", err.as_str())]));
+                        state.play_cue(SoundCue::Error);
+                    }
+                }
+                return;
+            }
+
+            let mut dropped_chars = 0;
+            // 逐行输入时本次输入的行号和总行数
+            let mut line_progress = None;
+            let tokens = if request.resume {
+                match state.interrupted_tokens.lock().unwrap().take() {
+                    Some(tokens) => tokens,
+                    None => {
+                        warn!("{}", state.t("log.nothing_to_resume"));
+                        state.set_status(&state.t("status.nothing_to_resume"));
+                        return;
+                    }
+                }
+            } else if let Some(tokens) = request.tokens {
+                tokens
+            } else {
+                let from_history = request.from_history;
+                let text = match request.text {
+                    Some(text) => text,
+                    None => {
+                        let text = state.clipboard_text.lock().unwrap().clone();
+                        match line_by_line.then(|| state.next_clipboard_line(&text)).flatten() {
+                            Some((line, index, total)) => {
+                                line_progress = Some((index, total));
+                                line
+                            }
+                            None => text,
+                        }
+                    }
+                };
+
+                if text.is_empty() {
+                    warn!("{}", state.t("log.clipboard_empty"));
+                    state.set_status(&state.t("status.clipboard_empty"));
+                    return;
+                }
+
+                if confirm_commands && !request.confirmed && text_transform::looks_like_shell_commands(&text) {
+                    warn!("{}", state.t("log.shell_commands_detected"));
+                    state.set_status(&state.t("status.shell_commands_confirm"));
+                    state.request_command_confirm(
+                        ConfirmKind::ShellCommands,
+                        TypingRequest {
+                            text: Some(text),
+                            speed: request.speed,
+                            from_history,
+                            ..Default::default()
+                        },
+                    );
+                    return;
+                }
+
+                if from_history {
+                    state.record_history_use(&text);
+                }
+                *state.last_typed_text.lock().unwrap() = Some(text.clone());
+                let text = if preprocess_command.is_empty() {
+                    text
+                } else {
+                    // 预处理命令最长运行到超时，期间没有输入进展，避免被当作卡住
+                    state.note_typing_progress(preprocess_timeout);
+                    state.preprocess_text(&preprocess_command, text, preprocess_timeout)
+                };
+                let text = text_transform::apply(&text, &transform);
+
+                let tokens = if key_tokens_enabled {
+                    tokenize(&text)
+                } else {
+                    vec![TypingToken::Text(text)]
+                };
+                tokens
+                    .into_iter()
+                    .map(|token| match token {
+                        TypingToken::Text(chunk) => {
+                            let (chunk, dropped) = text_transform::restrict_charset(&chunk, &transform);
+                            dropped_chars += dropped;
+                            TypingToken::Text(chunk)
+                        }
+                        token => token,
+                    })
+                    .collect()
+            };
+
+            if dropped_chars > 0 {
+                let count = dropped_chars.to_string();
+                info!("{}", state.tr("log.charset_dropped", &[("count", count.as_str())]));
+            }
+
+            let len_str = tokens
+                .iter()
+                .map(|token| match token {
+                    TypingToken::Text(chunk) => chunk.len(),
+                    _ => 0,
+                })
+                .sum::<usize>()
+                .to_string();
+            let char_count = tokens
+                .iter()
+                .map(|token| match token {
+                    TypingToken::Text(chunk) => chunk.chars().count(),
+                    _ => 0,
+                })
+                .sum::<usize>();
+            state.typing_remaining_chars.store(char_count, Ordering::SeqCst);
+            let effective_variance = if variance_enabled { variance } else { 0 };
+            *state.typing_cpm.lock().unwrap() = estimated_cpm(delay.max(min_delay), effective_variance);
+            state.typing_watch.lock().unwrap().estimated = state.typing_time_remaining();
+            let delay_str = delay.to_string();
+            let variance_str = variance.to_string();
+            let variance_enabled_str = variance_enabled.to_string();
+
+            info!(
+                "{}",
+                state.tr(
+                    "log.input_start",
+                    &[
+                        ("len", len_str.as_str()),
+                        ("delay", delay_str.as_str()),
+                        ("variance", variance_str.as_str()),
+                        ("variance_enabled", variance_enabled_str.as_str())
+                    ]
+                )
+            );
+
+            let mut backend = match EnigoBackend::new() {
+                Ok(backend) => backend,
+                Err(e) => {
+                    let err = e.to_string();
+                    error!("{}", state.tr("log.input_init_error", &[("err", err.as_str())]));
+                    state.set_status(&state.tr("status.input_init_error", &[("err", err.as_str())]));
+                    state.play_cue(SoundCue::Error);
+                    return;
+                }
+            };
+
+            // 设置了种子时使用确定的随机数序列，相同文本每次的输入节奏相同
+            let mut rng: Box<dyn RngCore> = match variance_seed {
+                Some(seed) => Box::new(StdRng::seed_from_u64(seed)),
+                None => Box::new(rand::thread_rng()),
+            };
+            let mut next_delay = |c: Option<char>| {
+                // 计算实际延迟
+                let mut actual_delay = delay + c.map_or(0, |c| char_class_delays.char_class_delay(c));
+                if variance_enabled && variance > 0 {
+                    // 在 [delay, delay + variance] 之间随机
+                    let v = rng.gen_range(0..=variance);
+                    actual_delay += v;
+                }
+                // 保护目标程序，防止输入过快导致丢字
+                actual_delay.max(min_delay)
+            };
+
+            let options = TypingOptions {
+                column_paste,
+                line_delay,
+                split_long_lines_at,
+                emoji_mode,
+                progress_log_every: progress_log_every as usize,
+                progress_log_interval: Duration::from_millis(progress_log_interval),
+                max_duration: (max_type_duration > 0).then(|| Duration::from_millis(max_type_duration)),
+            };
+            let mut progress = TypingProgress::default();
+            let result = type_tokens(&mut backend, &state, &tokens, &options, &mut next_delay, &mut progress);
+
+            // 中断时保留尚未输入的部分，供“继续输入剩余部分”使用
+            let remaining = progress.remaining(&tokens);
+            *state.interrupted_tokens.lock().unwrap() = if remaining.is_empty() {
+                None
+            } else {
+                Some(remaining)
+            };
+
+            match result {
+                Ok(TypingOutcome::Completed) => {
+                    info!("{}", state.t("log.input_complete"));
+                    *state.last_typing_completed.lock().unwrap() = Some(Instant::now());
+                    state.play_cue(SoundCue::Complete);
+                    if dropped_chars > 0 {
+                        let count = dropped_chars.to_string();
+                        state.set_status(
+                            &state.tr("status.input_complete_charset_dropped", &[("count", count.as_str())]),
+                        );
+                    } else if let Some((index, total)) = line_progress {
+                        let next = (index % total + 1).to_string();
+                        let total = total.to_string();
+                        state.set_status(
+                            &state.tr("status.line_typed_next", &[("next", next.as_str()), ("total", total.as_str())]),
+                        );
+                    } else {
+                        state.set_status(&state.t("status.input_complete"));
+                    }
+                    if restore_clipboard {
+                        state.copy_typed_to_clipboard(&tokens);
+                    }
+                    if hide_after {
+                        state.hide_window_requested.store(true, Ordering::SeqCst);
+                    } else if refocus_self_after {
+                        state.refocus_self_requested.store(true, Ordering::SeqCst);
+                    }
+                }
+                Ok(TypingOutcome::Cancelled) => {
+                    info!("{}", state.t("log.input_cancelled"));
+                    state.set_status(&state.t("status.input_cancelled"));
+                }
+                Ok(TypingOutcome::TimedOut) => {
+                    let seconds = (max_type_duration as f64 / 1000.0).to_string();
+                    warn!("{}", state.tr("log.input_timed_out", &[("seconds", seconds.as_str())]));
+                    state.set_status(&state.tr("status.input_timed_out", &[("seconds", seconds.as_str())]));
+                    state.play_cue(SoundCue::Error);
+                }
+                Err(e) => {
+                    let err = e.to_string();
+                    error!("{}", state.tr("log.input_error", &[("err", err.as_str())]));
+                    state.set_status(&state.tr("status.input_error", &[("err", err.as_str())]));
+                    state.play_cue(SoundCue::Error);
+                }
+            }
+        });
+    }
+}
+
+impl TypingHost for SharedState {
+    fn wait_if_paused(&self) -> bool {
+        SharedState::wait_if_paused(self)
+    }
+
+    fn sleep_with_pause(&self, ms: u64) -> bool {
+        SharedState::sleep_with_pause(self, ms)
+    }
+
+    fn paused_duration(&self) -> Duration {
+        SharedState::paused_duration(self)
+    }
+
+    fn char_done(&self) {
+        self.typing_remaining_chars.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn before_paste(&self, text: &str) {
+        self.mark_own_clipboard_write(text);
+    }
+
+    fn pause_at_marker(&self) {
+        if self.set_typing_paused(true) {
+            info!("{}", self.t("log.typing_paused_at_marker"));
+            self.set_status(&self.t("status.typing_paused_at_marker"));
+            self.play_cue(SoundCue::Pause);
+        }
+    }
+
+    fn tr(&self, key: &str, args: &[(&str, &str)]) -> String {
+        SharedState::tr(self, key, args)
+    }
+}
+
+/// 通过键盘后端依次发送输入标记，期间响应暂停与取消
+///
+/// `progress` 随输入实时更新，中断时可据此计算尚未输入的部分。
+/// 暂停、取消等状态由 `host` 提供，测试时可以替换为不依赖界面的实现。
+fn type_tokens(
+    backend: &mut dyn KeyboardBackend,
+    host: &dyn TypingHost,
+    tokens: &[TypingToken],
+    options: &TypingOptions,
+    next_delay: &mut dyn FnMut(Option<char>) -> u64,
+    progress: &mut TypingProgress,
+) -> InputResult<TypingOutcome> {
+    let total = tokens
+        .iter()
+        .map(|token| match token {
+            TypingToken::Text(chunk) => chunk.chars().count(),
+            _ => 0,
+        })
+        .sum::<usize>();
+    let total_str = total.to_string();
+    let mut typed = 0;
+    // 当前行已输入的字符数，用于拆分长行
+    let mut line_len = 0;
+    let mut progress_log = ProgressLog::new(options.progress_log_every, options.progress_log_interval);
+    let time_limit = TypingTimeLimit::new(options.max_duration, host.paused_duration());
+
+    for (index, token) in tokens.iter().enumerate() {
+        progress.token = index;
+        progress.chars = 0;
+        match token {
+            TypingToken::Text(chunk) => {
+                for c in chunk.chars() {
+                    if options.column_paste && c == '\r' {
+                        progress.chars += 1;
+                        host.char_done();
+                        continue;
+                    }
+                    let emoji = is_emoji(c);
+                    if emoji && options.emoji_mode == EmojiMode::Skip {
+                        let code = format!("U+{:04X}", c as u32);
+                        debug!("{}", host.tr("log.emoji_skipped", &[("char", code.as_str())]));
+                        progress.chars += 1;
+                        host.char_done();
+                        continue;
+                    }
+                    if !host.wait_if_paused() {
+                        return Ok(TypingOutcome::Cancelled);
+                    }
+                    if time_limit.exceeded(host.paused_duration()) {
+                        return Ok(TypingOutcome::TimedOut);
+                    }
+                    if options.column_paste && c == '\n' {
+                        // 列模式：移动到下一行行首，而不是插入新行
+                        backend.key(Key::DownArrow)?;
+                        backend.key(Key::Home)?;
+                    } else if emoji && options.emoji_mode == EmojiMode::PasteFallback {
+                        let text = c.to_string();
+                        host.before_paste(&text);
+                        backend.paste(&text)?;
+                    } else {
+                        backend.text(&c.to_string())?;
+                    }
+                    progress.chars += 1;
+                    host.char_done();
+                    typed += 1;
+                    if log_enabled!(Level::Debug) && progress_log.should_log(typed) {
+                        let typed_str = typed.to_string();
+                        debug!(
+                            "{}",
+                            host.tr(
+                                "log.typing_progress",
+                                &[("typed", typed_str.as_str()), ("total", total_str.as_str())]
+                            )
+                        );
+                    }
+                    let line_break = if c == '\n' {
+                        line_len = 0;
+                        true
+                    } else {
+                        line_len += 1;
+                        options.split_long_lines_at.is_some_and(|at| line_len % at == 0)
+                    };
+                    let pause = next_delay(Some(c)) + if line_break { options.line_delay } else { 0 };
+                    if !host.sleep_with_pause(time_limit.clamp(pause, host.paused_duration())) {
+                        return Ok(TypingOutcome::Cancelled);
+                    }
+                }
+            }
+            TypingToken::Key(key) => {
+                if !host.wait_if_paused() {
+                    return Ok(TypingOutcome::Cancelled);
+                }
+                if time_limit.exceeded(host.paused_duration()) {
+                    return Ok(TypingOutcome::TimedOut);
+                }
+                backend.key(key.to_enigo_key())?;
+                progress.token = index + 1;
+                if *key == SpecialKey::Enter {
+                    line_len = 0;
+                }
+                let pause = next_delay(None) + if *key == SpecialKey::Enter { options.line_delay } else { 0 };
+                if !host.sleep_with_pause(time_limit.clamp(pause, host.paused_duration())) {
+                    return Ok(TypingOutcome::Cancelled);
+                }
+            }
+            TypingToken::Delay(ms) => {
+                if !host.sleep_with_pause(time_limit.clamp(*ms, host.paused_duration())) {
+                    return Ok(TypingOutcome::Cancelled);
+                }
+                if time_limit.exceeded(host.paused_duration()) {
+                    return Ok(TypingOutcome::TimedOut);
+                }
+            }
+            TypingToken::Pause => {
+                // 中断后继续输入时不再停在同一个标记
+                progress.token = index + 1;
+                host.pause_at_marker();
+                if !host.wait_if_paused() {
+                    return Ok(TypingOutcome::Cancelled);
+                }
+            }
+        }
+    }
+    progress.token = tokens.len();
+    progress.chars = 0;
+    Ok(TypingOutcome::Completed)
+}
+
+/// GUI 应用程序
+struct CopyTypeApp {
+    /// 共享状态
+    state: SharedState,
+    /// 国际化
+    i18n: I18n,
+    /// 快捷键管理器
+    hotkey_manager: Option<GlobalHotKeyManager>,
+    /// 当前快捷键 ID
+    current_hotkey_id: Option<u32>,
+    /// 当前已注册的快捷键
+    current_hotkey: Option<HotKey>,
+    /// 快捷键配置
+    hotkey_config: HotkeyConfig,
+    /// 临时快捷键配置（编辑中）
+    temp_hotkey_config: HotkeyConfig,
+    /// 已注册的附加操作快捷键
+    action_hotkeys: Vec<(HotkeyAction, HotKey)>,
+    /// 临时附加操作快捷键配置（编辑中）
+    temp_action_hotkeys: BTreeMap<HotkeyAction, HotkeyConfig>,
+    /// 已注册的宏快捷键
+    macro_hotkeys: Vec<HotKey>,
+    /// 临时宏列表（编辑中）
+    temp_macros: Vec<Macro>,
+    /// 显示宏编辑窗口
+    show_macro_editor: bool,
+    /// 保存宏时的错误信息
+    macro_error: Option<String>,
+    /// 应用程序配置
+    app_config: AppConfig,
+    /// 临时应用配置（编辑中）
+    temp_app_config: AppConfig,
+    /// 显示快捷键设置面板
+    show_hotkey_settings: bool,
+    /// 显示应用设置面板
+    show_app_settings: bool,
+    /// 显示权限警告
+    show_permission_warning: bool,
+    /// 快捷键注册错误信息
+    hotkey_register_error: Option<String>,
+    /// 显示启动时快捷键错误弹窗
+    show_startup_hotkey_error: bool,
+    /// 启动时快捷键错误信息
+    startup_hotkey_error: Option<String>,
+    /// 显示日志查看窗口
+    show_log_viewer: bool,
+    /// 显示快捷键诊断窗口
+    show_hotkey_diagnostics: bool,
+    /// 是否显示运行状态概览窗口
+    show_health_summary: bool,
+    /// 显示寄存器窗口
+    show_registers: bool,
+    /// 寄存器窗口中选中的存入目标
+    register_target: char,
+    /// 最近的日志行
+    log_buffer: LogBuffer,
+    /// 等待显示器尺寸可用后执行的窗口停靠
+    pending_dock: Option<DockCorner>,
+    /// 来源程序过滤列表中待添加的程序名
+    new_source_app: String,
+    /// 前台屏蔽列表中待添加的程序名
+    new_blocked_app: String,
+    /// 显示首次启动引导
+    show_onboarding: bool,
+    /// 显示加密历史的解锁窗口
+    show_history_unlock: bool,
+    /// 解锁窗口中输入的口令
+    history_unlock_passphrase: String,
+    /// 解锁失败的提示
+    history_unlock_error: Option<String>,
+    /// 设置窗口中输入的新口令，为空时保留当前口令
+    new_history_passphrase: String,
+    /// 以迷你部件显示
+    mini_mode: bool,
+    /// 当前界面使用的窗口不透明度
+    applied_opacity: f32,
+    /// 首尾带有空白、等待确认后再输入的文本
+    pending_whitespace_confirm: Option<String>,
+    /// 历史列表只显示来自该程序的记录，None 表示显示全部
+    history_source_filter: Option<String>,
+    /// 显示历史选择列表
+    show_history_picker: bool,
+    /// 历史选择列表中选中的行
+    history_picker_selected: usize,
+    /// 历史选择列表打开后窗口是否已获得焦点
+    history_picker_focused: bool,
+    /// 打开历史选择列表前的窗口位置，关闭时恢复
+    history_picker_restore_pos: Option<egui::Pos2>,
+    /// 引导中“开机自启”复选框的值
+    onboarding_autostart: bool,
+    /// 权限状态
+    permission_status: PermissionStatus,
+    /// 系统托盘上下文，必须保持活跃
+    tray_context: Option<TrayContext>,
+    /// 托盘提示中显示的定时启用状态
+    tray_schedule_state: Option<bool>,
+}
+
+/// 保持托盘及其菜单项存活的结构体
+struct TrayContext {
+    tray: TrayIcon,
+    /// 菜单中的项目，与托盘图标一起保持存活
+    #[allow(dead_code)]
+    items: Vec<Box<dyn IsMenuItem>>,
+}
+
+impl CopyTypeApp {
+    fn new(
+        cc: &eframe::CreationContext<'_>,
+        icon: Option<tray_icon::Icon>,
+        log_buffer: LogBuffer,
+    ) -> Self {
+        // 加载配置（统一从 AppConfig 加载）
+        let app_config = AppConfig::load();
+        let hotkey_config = app_config.hotkey.clone();
+        let i18n = I18n::new(&app_config.language);
+
+        // 设置中文字体，找不到时中文会显示为方框，本次改用英文界面
+        if !setup_fonts(&cc.egui_ctx, &app_config.cjk_font_path) {
+            if i18n.current_language() == "zh-CN" {
+                i18n.set_language("en");
+            }
+            warn!("{}", i18n.t("log.cjk_font_missing"));
+        }
+
+        let meta_platform_change = app_config.meta_platform_change().map(|os| os.to_string());
+        if let Some(os) = &meta_platform_change {
+            warn!("{}", i18n.tr("log.meta_platform_changed", &[("os", os.as_str())]));
+        }
+
+        // 检查权限
+        let permission_status = check_permissions(&i18n);
+        // 首次启动时权限检查结果在引导窗口中显示
+        let show_permission_warning = !permission_status.all_granted() && app_config.onboarded;
+
+        if show_permission_warning {
+            let issues = permission_status.issues.join(", ");
+            warn!("{}", i18n.tr("log.permission_issue", &[("issues", issues.as_str())]));
+        }
+
+        // 创建共享状态
+        let state = SharedState::new(i18n.clone());
+        // 初始化 state 中的配置值
+        state.apply_config(&app_config);
+        state.init_enabled(app_config.start_enabled);
+        // 加密的历史在输入口令解锁后加载
+        let history_locked = app_config.history_enabled && app_config.persist_history && app_config.encrypt_history;
+        if history_locked && !history_crypto::SUPPORTED {
+            warn!("{}", i18n.t("log.history_encryption_unsupported"));
+        } else if app_config.history_enabled && app_config.persist_history && !history_locked {
+            state.load_history(history::load());
+            state.trim_history();
+        }
+        *state.scratchpad.lock().unwrap() = scratchpad::load();
+        if let Some(os) = &meta_platform_change {
+            state.set_status(&state.tr("status.meta_platform_changed", &[("os", os.as_str())]));
+        }
+
+        // 根据配置显示/隐藏控制台
+        #[cfg(target_os = "windows")]
+        {
+            if app_config.show_console {
+                show_console_window();
+            } else {
+                hide_console_window();
+            }
+        }
+
+        // 创建系统托盘，并保存上下文
+        let tray_context = if let Some(icon) = icon {
+            create_tray_context(&i18n, icon, &app_config.tray_menu)
+        } else {
+            warn!("Tray icon unavailable; skipping tray menu.");
+            None
+        };
+        
+        let window_hwnd = get_window_hwnd(cc);
+        *state.own_window.lock().unwrap() = window_hwnd;
+        *state.egui_ctx.lock().unwrap() = Some(cc.egui_ctx.clone());
+        let ctx_clone = cc.egui_ctx.clone();
+        let i18n_tray = i18n.clone();
+        let tray_state = state.clone();
+
+        // 启动独立的托盘事件监控线程
+        // 这解决了主线程阻塞导致托盘事件无法及时处理的问题
+        std::thread::spawn(move || {
+             let receiver = MenuEvent::receiver();
+             loop {
+                 // 使用阻塞式 recv()，这样一有事件就会立即响应
+                 if let Ok(event) = receiver.recv() {
+                    let id_str = event.id.0.as_str();
+                    info!("{}", i18n_tray.tr("log.tray_event", &[("id", id_str)]));
+                    
+                    match id_str {
+                        MENU_EXIT => {
+                            info!("{}", i18n_tray.t("log.tray_exec_exit"));
+                            // 强制退出，不等待任何UI更新
+                            std::process::exit(0);
+                        }
+                        MENU_SHOW => {
+                            info!("{}", i18n_tray.t("log.tray_exec_show"));
+                            tray_state.window_visible.store(true, Ordering::SeqCst);
+                            show_main_window(&ctx_clone, window_hwnd);
+                        }
+                        MENU_TYPE_NOW => {
+                            info!("{}", i18n_tray.t("log.tray_exec_type_now"));
+                            tray_state.execute_typing();
+                        }
+                        MENU_HISTORY => {
+                            info!("{}", i18n_tray.t("log.tray_exec_history"));
+                            tray_state.show_history_picker();
+                        }
+                        MENU_TOGGLE => {
+                            let enabled = !tray_state.is_enabled();
+                            let state_text = if enabled {
+                                i18n_tray.t("common.enabled")
+                            } else {
+                                i18n_tray.t("common.disabled")
+                            };
+                            info!(
+                                "{}",
+                                i18n_tray.tr("log.tray_exec_toggle", &[("state", state_text.as_str())])
+                            );
+                            tray_state.set_enabled(enabled);
+                            let status = if enabled {
+                                i18n_tray.t("status.enabled")
+                            } else {
+                                i18n_tray.t("status.disabled")
+                            };
+                            tray_state.set_status(&status);
+                            ctx_clone.request_repaint();
+                        }
+                        _ => {
+                            if let Some(minutes) = id_str
+                                .strip_prefix(MENU_SNOOZE_PREFIX)
+                                .and_then(|m| m.parse::<u64>().ok())
+                            {
+                                tray_state.snooze(Duration::from_secs(minutes * 60));
+                            } else if let Some(preset) = id_str
+                                .strip_prefix(MENU_SPEED_PREFIX)
+                                .and_then(SpeedPreset::find)
+                            {
+                                info!(
+                                    "{}",
+                                    i18n_tray.tr("log.tray_exec_type_with_speed", &[("preset", preset.name)])
+                                );
+                                tray_state.execute_typing_request(TypingRequest {
+                                    speed: Some(preset),
+                                    ..Default::default()
+                                });
+                            }
+                            ctx_clone.request_repaint();
+                        }
+                    }
+                 }
+             }
+        });
+
+        // 启动独立的快捷键事件监控线程
+        // 这解决了窗口隐藏/最小化时快捷键不响应的问题
+        let hotkey_state = state.clone();
+        let i18n_hotkey = i18n.clone();
+        std::thread::spawn(move || {
+            let receiver = GlobalHotKeyEvent::receiver();
+            loop {
+                if let Ok(event) = receiver.recv() {
+                    let action = hotkey_state.action_hotkey_ids.lock().unwrap().get(&event.id).copied();
+                    if let Some(action) = action {
+                        if event.state == HotKeyState::Pressed {
+                            hotkey_state.run_hotkey_action(action);
+                        }
+                        continue;
+                    }
+
+                    let macro_index = hotkey_state.macro_hotkey_ids.lock().unwrap().get(&event.id).copied();
+                    if let Some(index) = macro_index {
+                        if event.state == HotKeyState::Pressed {
+                            let macro_def = hotkey_state.macros.lock().unwrap().get(index).cloned();
+                            if let Some(macro_def) = macro_def {
+                                hotkey_state.run_macro(&macro_def, false);
+                            }
+                        }
+                        continue;
+                    }
+
+                    let current_id = *hotkey_state.hotkey_id.lock().unwrap();
+                    if let Some(id) = current_id {
+                        if event.id == id {
+                            if hotkey_state.consume_hotkey_test(event.state == HotKeyState::Pressed) {
+                                continue;
+                            }
+                            if !hotkey_state.should_handle_hotkey() {
+                                continue;
+                            }
+                            info!("{}", i18n_hotkey.t("log.hotkey_triggered"));
+                            hotkey_state.handle_main_trigger();
+                        }
+                    }
+                }
+            }
+        });
+
+        let mut app = Self {
+            state,
+            i18n: i18n.clone(),
+            hotkey_manager: None,
+            current_hotkey_id: None,
+            current_hotkey: None,
+            hotkey_config: hotkey_config.clone(),
+            temp_hotkey_config: hotkey_config,
+            action_hotkeys: Vec::new(),
+            temp_action_hotkeys: app_config.action_hotkeys.clone(),
+            macro_hotkeys: Vec::new(),
+            temp_macros: app_config.macros.clone(),
+            show_macro_editor: false,
+            macro_error: None,
+            app_config: app_config.clone(),
+            temp_app_config: app_config.clone(),
+            show_hotkey_settings: false,
+            show_app_settings: false,
+            show_permission_warning,
+            hotkey_register_error: None,
+            show_startup_hotkey_error: false,
+            startup_hotkey_error: None,
+            show_log_viewer: false,
+            show_registers: false,
+            register_target: 'a',
+            show_hotkey_diagnostics: false,
+            show_health_summary: false,
+            log_buffer,
+            pending_dock: app_config.dock_corner,
+            new_source_app: String::new(),
+            new_blocked_app: String::new(),
+            show_onboarding: !app_config.onboarded,
+            show_history_unlock: history_locked && history_crypto::SUPPORTED,
+            history_unlock_passphrase: String::new(),
+            history_unlock_error: None,
+            new_history_passphrase: String::new(),
+            mini_mode: app_config.mini_mode,
+            applied_opacity: 1.0,
+            pending_whitespace_confirm: None,
+            history_source_filter: None,
+            show_history_picker: false,
+            history_picker_selected: 0,
+            history_picker_focused: false,
+            history_picker_restore_pos: None,
+            onboarding_autostart: false,
+            permission_status,
+            tray_context,
+            tray_schedule_state: None,
+        };
+
+        // 初始化快捷键
+        app.init_hotkey();
+        app.apply_mouse_trigger();
+
+        // 会话锁定时暂停或取消输入，避免把文本输入到锁屏界面
+        let state = app.state.clone();
+        if let Err(e) = session_lock::watch(move |locked| state.handle_session_lock(locked)) {
+            let err = e.to_string();
+            error!("{}", app.i18n.tr("log.session_lock_watch_fail", &[("err", err.as_str())]));
+        }
+
+        // 启动剪贴板监控
+        app.start_clipboard_monitor();
+        app.start_file_watch();
+        app.start_timer_checks();
+
+        // 如果设置为启动时最小化，则隐藏窗口
+        if app_config.start_minimized {
+            app.state.window_visible.store(false, Ordering::SeqCst);
+            if let Some(ctx) = cc.egui_ctx.clone().into() {
+                let ctx: egui::Context = ctx;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+            }
+        }
+
+        app
+    }
+
+    /// 初始化快捷键管理器
+    fn init_hotkey(&mut self) {
+        if !self.app_config.hotkey_enabled {
+            info!("{}", self.i18n.t("log.hotkey_disabled"));
+            return;
+        }
+        match self.create_hotkey_manager() {
+            Ok(manager) => {
+                self.hotkey_manager = Some(manager);
+                self.register_main_hotkey();
+
+                let action_configs = self.app_config.action_hotkeys.clone();
+                let mut errors = self.register_action_hotkeys(&action_configs);
+                errors.extend(self.register_macro_hotkeys());
+                for err in errors {
+                    error!("{}", self.i18n.tr("log.hotkey_register_fail", &[("err", err.as_str())]));
+                }
+            }
+            Err(e) => {
+                let err = e.to_string();
+                error!(
+                    "{}",
+                    self.i18n
+                        .tr("log.hotkey_manager_fail", &[("err", err.as_str())])
+                );
+                self.state
+                    .set_status(
+                        &self
+                            .i18n
+                            .tr("status.hotkey_manager_fail", &[("err", err.as_str())]),
+                    );
+                // 保存用户友好的错误信息
+                self.startup_hotkey_error = Some(self.i18n.t("ui.error_hotkey_manager_init_failed"));
+                self.show_startup_hotkey_error = true;
+            }
+        }
+    }
+
+    /// 创建快捷键管理器，失败时按退避时间重试
+    ///
+    /// 开机自启时显示环境可能尚未就绪，首次创建容易失败。
+    fn create_hotkey_manager(&self) -> Result<GlobalHotKeyManager, global_hotkey::Error> {
+        let attempts = self.app_config.hotkey_init_attempts.max(1);
+        let mut backoff = self.app_config.hotkey_init_backoff_ms;
+        let mut attempt = 1;
+        loop {
+            match GlobalHotKeyManager::new() {
+                Ok(manager) => return Ok(manager),
+                Err(e) if attempt < attempts => {
+                    let err = e.to_string();
+                    let attempt_str = attempt.to_string();
+                    let attempts_str = attempts.to_string();
+                    let backoff_str = backoff.to_string();
+                    warn!(
+                        "{}",
+                        self.i18n.tr(
+                            "log.hotkey_manager_retry",
+                            &[
+                                ("attempt", attempt_str.as_str()),
+                                ("max", attempts_str.as_str()),
+                                ("err", err.as_str()),
+                                ("delay", backoff_str.as_str())
+                            ]
+                        )
+                    );
+                    thread::sleep(Duration::from_millis(backoff));
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// 注册主快捷键
+    fn register_main_hotkey(&mut self) {
+        let Some(manager) = &self.hotkey_manager else {
+            return;
+        };
+        if let Some(hotkey) = self.hotkey_config.to_global_hotkey() {
+            match manager.register(hotkey) {
+                Ok(()) => {
+                    self.current_hotkey_id = Some(hotkey.id());
+                    self.current_hotkey = Some(hotkey);
+                    *self.state.hotkey_id.lock().unwrap() = Some(hotkey.id());
+                    let display = self.hotkey_config.display();
+                    info!(
+                        "{}",
+                        self.i18n
+                            .tr("log.hotkey_registered", &[("hotkey", display.as_str())])
+                    );
+                    self.state.set_status(
+                        &self
+                            .i18n
+                            .tr("status.hotkey_registered", &[("hotkey", display.as_str())]),
+                    );
+                }
+                Err(e) => {
+                    let err = e.to_string();
+                    error!(
+                        "{}",
+                        self.i18n
+                            .tr("log.hotkey_register_fail", &[("err", err.as_str())])
+                    );
+                    self.state.set_status(
+                        &self
+                            .i18n
+                            .tr("status.hotkey_register_fail", &[("err", err.as_str())]),
+                    );
+                    // 保存用户友好的错误信息
+                    let friendly_error = if err.contains("already register") {
+                        self.i18n.t("ui.error_hotkey_already_registered")
+                    } else {
+                        self.i18n.tr("ui.error_hotkey_register_failed", &[("error", err.as_str())])
+                    };
+                    self.startup_hotkey_error =
+                        Some(format!("{} - {}", self.hotkey_config.display(), friendly_error));
+                    self.show_startup_hotkey_error = true;
+                }
+            }
+        }
+    }
+
+    /// 手动重试快捷键注册
+    fn retry_hotkey_registration(&mut self) {
+        if !self.app_config.hotkey_enabled {
+            return;
+        }
+        info!("{}", self.i18n.t("log.hotkey_retry_manual"));
+        self.startup_hotkey_error = None;
+        self.show_startup_hotkey_error = false;
+
+        if self.hotkey_manager.is_none() {
+            self.init_hotkey();
+            return;
+        }
+
+        if self.current_hotkey.is_none() {
+            self.register_main_hotkey();
+        }
+        let action_configs = self.app_config.action_hotkeys.clone();
+        let mut errors = self.register_action_hotkeys(&action_configs);
+        errors.extend(self.register_macro_hotkeys());
+        for err in errors {
+            error!("{}", self.i18n.tr("log.hotkey_register_fail", &[("err", err.as_str())]));
+        }
+    }
+
+    /// 注销所有已跟踪的快捷键后重新注册，用于清理注销失败残留的注册
+    fn reregister_all_hotkeys(&mut self) {
+        if !self.app_config.hotkey_enabled {
+            return;
+        }
+        info!("{}", self.i18n.t("log.hotkey_reregister_all"));
+        self.startup_hotkey_error = None;
+        self.show_startup_hotkey_error = false;
+
+        let Some(manager) = &self.hotkey_manager else {
+            self.init_hotkey();
+            return;
+        };
+        if let Some(hotkey) = self.current_hotkey.take() {
+            if let Err(e) = manager.unregister(hotkey) {
+                let err = e.to_string();
+                warn!("{}", self.i18n.tr("log.hotkey_unregister_fail", &[("err", err.as_str())]));
+            }
+        }
+        self.current_hotkey_id = None;
+        *self.state.hotkey_id.lock().unwrap() = None;
+
+        self.register_main_hotkey();
+        let action_configs = self.app_config.action_hotkeys.clone();
+        let mut errors = self.register_action_hotkeys(&action_configs);
+        errors.extend(self.register_macro_hotkeys());
+        for err in errors {
+            error!("{}", self.i18n.tr("log.hotkey_register_fail", &[("err", err.as_str())]));
+        }
+    }
+
+    /// 按配置绑定触发输入的鼠标按键，全局快捷键停用时一并停用
+    fn apply_mouse_trigger(&mut self) {
+        let trigger = self.app_config.mouse_trigger.filter(|_| self.app_config.hotkey_enabled);
+        let state = self.state.clone();
+        let result = mouse_hook::set_trigger(trigger, move || {
+            if state.should_handle_hotkey() {
+                info!("{}", state.t("log.mouse_trigger_pressed"));
+                state.handle_main_trigger();
+            }
+        });
+        if let Err(e) = result {
+            let err = e.to_string();
+            error!("{}", self.i18n.tr("log.mouse_trigger_fail", &[("err", err.as_str())]));
+            self.state
+                .set_status(&self.i18n.tr("status.mouse_trigger_fail", &[("err", err.as_str())]));
+        }
+    }
+
+    /// 注销主快捷键及所有附加操作快捷键
+    fn unregister_all_hotkeys(&mut self) {
+        let Some(manager) = &self.hotkey_manager else {
+            return;
+        };
+        let hotkeys = self
+            .current_hotkey
+            .take()
+            .into_iter()
+            .chain(self.action_hotkeys.drain(..).map(|(_, hotkey)| hotkey))
+            .chain(self.macro_hotkeys.drain(..));
+        for hotkey in hotkeys {
+            if let Err(e) = manager.unregister(hotkey) {
+                let err = e.to_string();
+                warn!("{}", self.i18n.tr("log.hotkey_unregister_fail", &[("err", err.as_str())]));
+            }
+        }
+        self.current_hotkey_id = None;
+        *self.state.hotkey_id.lock().unwrap() = None;
+        self.state.action_hotkey_ids.lock().unwrap().clear();
+        self.state.macro_hotkey_ids.lock().unwrap().clear();
+        info!("{}", self.i18n.t("log.hotkey_disabled"));
+    }
+
+    /// 更新快捷键（主快捷键及附加操作快捷键）
+    fn update_hotkey(&mut self) {
+        self.hotkey_register_error = None;
+        if !self.temp_hotkey_config.conflicts_with(&self.hotkey_config) {
+            self.update_main_hotkey();
+        }
+        if self.hotkey_register_error.is_none()
+            && self.temp_action_hotkeys != self.app_config.action_hotkeys
+        {
+            self.update_action_hotkeys();
+        }
+    }
+
+    /// 更新附加操作的快捷键并保存配置
+    fn update_action_hotkeys(&mut self) {
+        let configs = self.temp_action_hotkeys.clone();
+        if let Some(conflict) = hotkey_config::find_conflict(&self.hotkey_config, &configs) {
+            self.hotkey_register_error = Some(hotkey_conflict_message(&self.i18n, conflict));
+            return;
+        }
+
+        let errors = self.register_action_hotkeys(&configs);
+        if !errors.is_empty() {
+            for err in &errors {
+                error!("{}", self.i18n.tr("log.hotkey_register_fail", &[("err", err.as_str())]));
+            }
+            self.hotkey_register_error = Some(errors.join("; "));
+            return;
+        }
+
+        self.app_config.action_hotkeys = configs;
+        if let Err(e) = self.app_config.save() {
+            let err = e.to_string();
+            error!("{}", self.i18n.tr("log.save_config_fail", &[("err", err.as_str())]));
+        }
+    }
+
+    /// 注销已注册的附加操作快捷键，并按给定配置重新注册
+    ///
+    /// 返回注册失败的快捷键及原因。
+    fn register_action_hotkeys(&mut self, configs: &BTreeMap<HotkeyAction, HotkeyConfig>) -> Vec<String> {
+        let mut errors = Vec::new();
+        let Some(manager) = &self.hotkey_manager else {
+            return errors;
+        };
+
+        for (_, hotkey) in self.action_hotkeys.drain(..) {
+            if let Err(e) = manager.unregister(hotkey) {
+                let err = e.to_string();
+                warn!("{}", self.i18n.tr("log.hotkey_unregister_fail", &[("err", err.as_str())]));
+            }
+        }
+
+        let mut ids = HashMap::new();
+        for (action, config) in configs {
+            let Some(hotkey) = config.to_global_hotkey() else {
+                continue;
+            };
+            match manager.register(hotkey) {
+                Ok(()) => {
+                    let display = config.display();
+                    info!("{}", self.i18n.tr("log.hotkey_registered", &[("hotkey", display.as_str())]));
+                    ids.insert(hotkey.id(), *action);
+                    self.action_hotkeys.push((*action, hotkey));
+                }
+                Err(e) => {
+                    let err = e.to_string();
+                    let friendly_error = if err.contains("already register") {
+                        self.i18n.t("ui.error_hotkey_already_registered")
+                    } else {
+                        err
+                    };
+                    errors.push(format!("{} - {}", config.display(), friendly_error));
+                }
+            }
+        }
+        *self.state.action_hotkey_ids.lock().unwrap() = ids;
+
+        errors
+    }
+
+    /// 注销已注册的宏快捷键，并按当前的宏列表重新注册
+    ///
+    /// 返回注册失败的快捷键及原因。
+    fn register_macro_hotkeys(&mut self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let Some(manager) = &self.hotkey_manager else {
+            return errors;
+        };
+
+        for hotkey in self.macro_hotkeys.drain(..) {
+            if let Err(e) = manager.unregister(hotkey) {
+                let err = e.to_string();
+                warn!("{}", self.i18n.tr("log.hotkey_unregister_fail", &[("err", err.as_str())]));
+            }
+        }
+
+        let mut ids = HashMap::new();
+        for (index, macro_def) in self.app_config.macros.iter().enumerate() {
+            let Some(config) = &macro_def.hotkey else {
+                continue;
+            };
+            let Some(hotkey) = config.to_global_hotkey() else {
+                continue;
+            };
+            match manager.register(hotkey) {
+                Ok(()) => {
+                    let display = config.display();
+                    info!("{}", self.i18n.tr("log.hotkey_registered", &[("hotkey", display.as_str())]));
+                    ids.insert(hotkey.id(), index);
+                    self.macro_hotkeys.push(hotkey);
+                }
+                Err(e) => {
+                    let err = e.to_string();
+                    let friendly_error = if err.contains("already register") {
+                        self.i18n.t("ui.error_hotkey_already_registered")
+                    } else {
+                        err
+                    };
+                    errors.push(format!("{} ({}) - {}", config.display(), macro_def.name, friendly_error));
+                }
+            }
+        }
+        *self.state.macro_hotkey_ids.lock().unwrap() = ids;
+
+        errors
+    }
+
+    /// 检查编辑中的宏，返回第一个问题的说明
+    fn validate_macros(&self, macros: &[Macro]) -> Option<String> {
+        let i18n = &self.i18n;
+        for (index, macro_def) in macros.iter().enumerate() {
+            if macro_def.name.trim().is_empty() {
+                return Some(i18n.t("ui.error_macro_name_empty"));
+            }
+            let Some(config) = &macro_def.hotkey else {
+                continue;
+            };
+            let name = macro_def.name.as_str();
+            if !config.is_valid() {
+                return Some(i18n.tr("ui.error_macro_hotkey_no_modifier", &[("name", name)]));
+            }
+            let taken = config.conflicts_with(&self.hotkey_config)
+                || self.app_config.action_hotkeys.values().any(|other| config.conflicts_with(other))
+                || macros[..index]
+                    .iter()
+                    .filter_map(|other| other.hotkey.as_ref())
+                    .any(|other| config.conflicts_with(other));
+            if taken {
+                return Some(i18n.tr("ui.error_macro_hotkey_duplicate", &[("name", name)]));
+            }
+        }
+        None
+    }
+
+    /// 保存编辑中的宏并重新注册宏快捷键
+    fn save_macros(&mut self) {
+        let macros = self.temp_macros.clone();
+        if let Some(err) = self.validate_macros(&macros) {
+            self.macro_error = Some(err);
+            return;
+        }
+
+        self.app_config.macros = macros.clone();
+        // 保存应用设置时会用 temp_app_config 覆盖，保持一致
+        self.temp_app_config.macros = macros.clone();
+        *self.state.macros.lock().unwrap() = macros;
+        let errors = if self.app_config.hotkey_enabled {
+            self.register_macro_hotkeys()
+        } else {
+            Vec::new()
+        };
+        for err in &errors {
+            error!("{}", self.i18n.tr("log.hotkey_register_fail", &[("err", err.as_str())]));
+        }
+        self.macro_error = (!errors.is_empty()).then(|| errors.join("; "));
+
+        if let Err(e) = self.app_config.save() {
+            let err = e.to_string();
+            error!("{}", self.i18n.tr("log.save_config_fail", &[("err", err.as_str())]));
+        } else if self.macro_error.is_none() {
+            self.state.set_status(&self.i18n.t("status.macros_saved"));
+        }
+    }
+
+    /// 更新主快捷键
+    fn update_main_hotkey(&mut self) {
+        // 先尝试注册新的快捷键（不注销旧的）
+        if let Some(manager) = &self.hotkey_manager {
+            if let Some(new_hotkey) = self.temp_hotkey_config.to_global_hotkey() {
+                if let Some(current_hotkey) = self.current_hotkey {
+                    if current_hotkey == new_hotkey {
+                        self.hotkey_register_error = None;
+                        return;
+                    }
+                }
+                match manager.register(new_hotkey) {
+                    Ok(()) => {
+                        // 注册成功，现在注销旧的快捷键
+                        if let Some(old_hotkey) = self.current_hotkey {
+                            if let Err(e) = manager.unregister(old_hotkey) {
+                                let err = e.to_string();
+                                warn!(
+                                    "{}",
+                                    self.i18n
+                                        .tr("log.hotkey_unregister_fail", &[("err", err.as_str())])
+                                );
+                            } else {
+                                info!("{}", self.i18n.t("log.hotkey_unregistered"));
+                            }
+                        }
+
+                        // 更新配置
+                        self.hotkey_config = self.temp_hotkey_config.clone();
+                        self.current_hotkey_id = Some(new_hotkey.id());
+                        self.current_hotkey = Some(new_hotkey);
+                        *self.state.hotkey_id.lock().unwrap() = Some(new_hotkey.id());
+                        
+                        let display = self.hotkey_config.display();
+                        info!(
+                            "{}",
+                            self.i18n
+                                .tr("log.hotkey_updated", &[("hotkey", display.as_str())])
+                        );
+                        self.state.set_status(
+                            &self
+                                .i18n
+                                .tr("status.hotkey_updated", &[("hotkey", display.as_str())]),
+                        );
+
+                        // 保存配置（更新 app_config.hotkey 并保存）
+                        self.app_config.hotkey = self.hotkey_config.clone();
+                        if let Err(e) = self.app_config.save() {
+                            let err = e.to_string();
+                            error!(
+                                "{}",
+                                self.i18n
+                                    .tr("log.save_config_fail", &[("err", err.as_str())])
+                            );
+                        }
+
+                        // 清除错误信息
+                        self.hotkey_register_error = None;
+                    }
+                    Err(e) => {
+                        // 注册失败，保存错误信息
+                        let err = e.to_string();
+                        error!(
+                            "{}",
+                            self.i18n
+                                .tr("log.hotkey_register_fail", &[("err", err.as_str())])
+                        );
+                        // 保存用户友好的错误信息
+                        let friendly_error = if err.contains("already register") {
+                            self.i18n.t("ui.error_hotkey_already_registered")
+                        } else {
+                            err
+                        };
+                        self.hotkey_register_error = Some(friendly_error);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 启动剪贴板监控线程
+    ///
+    /// 监控线程已在运行时不做任何事；打开剪贴板最终失败后线程退出，可以再次调用重新启动。
+    fn start_clipboard_monitor(&self) {
+        if self.state.clipboard_monitor_running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let state = self.state.clone();
+        let capture_on_startup = self.app_config.capture_on_startup;
+        let attempts = self.app_config.clipboard_init_attempts.max(1);
+        let backoff = self.app_config.clipboard_init_backoff_ms;
+
+        thread::spawn(move || {
+            let mut clipboard = match open_clipboard(&state, attempts, backoff) {
+                Ok(cb) => cb,
+                Err(e) => {
+                    let err = e.to_string();
+                    error!("{}", state.tr("log.clipboard_init_fail", &[("err", err.as_str())]));
+                    state.set_status(&state.tr("status.clipboard_init_fail", &[("err", err.as_str())]));
+                    state.clipboard_monitor_running.store(false, Ordering::SeqCst);
+                    return;
+                }
+            };
+
+            info!("{}", state.t("log.clipboard_monitor_started"));
+
+            let mut reader = SelectionReader::default();
+            if !capture_on_startup {
+                // 把启动时已有的内容标记为已读，只捕获之后的变化
+                let priority = state.clipboard_format_priority.lock().unwrap().clone();
+                let markdown = *state.markdown_mode.lock().unwrap();
+                let selection = *state.capture_selection.lock().unwrap();
+                if let Some(text) = reader.read(&mut clipboard, selection, &priority, markdown) {
+                    *state.last_clipboard_text.lock().unwrap() = text;
+                }
+                debug!("{}", state.t("log.clipboard_startup_skipped"));
+            }
+
+            // 首次读取不等待轮询间隔，启动后立即显示剪贴板中已有的内容
+            loop {
+                state.track_foreground_window();
+
+                // 只在启用时监控
+                if state.is_enabled() {
+                    let priority = state.clipboard_format_priority.lock().unwrap().clone();
+                    let markdown = *state.markdown_mode.lock().unwrap();
+                    let selection = *state.capture_selection.lock().unwrap();
+                    let text = reader.read(&mut clipboard, selection, &priority, markdown);
+                    state.update_non_text_content(&mut clipboard, selection, text.as_deref());
+                    if let Some(text) = text {
+                        let last = state.last_clipboard_text.lock().unwrap().clone();
+
+                        if text != last && !text.is_empty() && state.is_own_clipboard_write(&text) {
+                            // 本程序写入的内容（粘贴方式输入、输入后放回剪贴板），不作为新的复制；
+                            // 也不标记为已读，之后恢复原内容时不会被当作新内容
+                        } else if text != last && !text.is_empty() && state.should_pause_capture() {
+                            // 输入期间的剪贴板变化只标记为已读，不作为新内容捕获
+                            *state.last_clipboard_text.lock().unwrap() = text;
+                        } else if text != last && !text.is_empty() && state.foreground_blocks_capture() {
+                            // 屏蔽的程序在前台，只标记为已读，不进入预览和历史
+                            *state.last_clipboard_text.lock().unwrap() = text;
+                        } else if text != last && !text.is_empty() && !state.check_clipboard_source() {
+                            // 来源程序被过滤，同样只标记为已读
+                            *state.last_clipboard_text.lock().unwrap() = text;
+                        } else if text != last && !text.is_empty() {
+                            let len_str = text.len().to_string();
+                            info!(
+                                "{}",
+                                state.tr("log.clipboard_changed", &[("len", len_str.as_str())])
+                            );
+                            
+                            // 安全地生成预览，如果 truncate_text panic 就用简单方式
+                            let preview = std::panic::catch_unwind(|| truncate_text(&text, 50))
+                                .unwrap_or_else(|_| {
+                                    error!("truncate_text 发生错误，使用简单截断");
+                                    text.chars().take(50).collect::<String>() + "..."
+                                });
+                            debug!("{}", state.tr("log.clipboard_preview", &[("preview", preview.as_str())]));
+
+                            if *state.notify_on_capture.lock().unwrap() {
+                                state.set_status(&state.tr("status.clipboard_captured", &[("preview", preview.as_str())]));
+                                *state.capture_notice.lock().unwrap() = Some(preview);
+                                if let Some(ctx) = state.egui_ctx.lock().unwrap().as_ref() {
+                                    ctx.request_repaint();
+                                }
+                            }
+
+                            state.capture_clipboard_text(text.clone());
+                            *state.last_clipboard_text.lock().unwrap() = text.clone();
+                            state.record_history(text);
+                        }
+                    }
+                }
+
+                thread::sleep(state.clipboard_poll_interval());
+            }
+        });
+    }
+
+    /// 启动文件监视线程，按设置中的文件随时切换监视目标
+    /// 启动定时检查线程：小睡到期、定时启用/禁用和卡住的输入
+    ///
+    /// 与剪贴板监控分开，监控线程退出或用电池时放慢轮询都不影响这些检查。
+    fn start_timer_checks(&self) {
+        let state = self.state.clone();
+        thread::spawn(move || loop {
+            state.check_snooze();
+            state.check_schedule();
+            state.check_stuck_typing();
+            thread::sleep(TIMER_CHECK_INTERVAL);
+        });
+    }
+
+    fn start_file_watch(&self) {
+        let state = self.state.clone();
+        thread::spawn(move || {
+            let mut watch: Option<FileWatch> = None;
+            loop {
+                let path = state.watch_file.lock().unwrap().clone();
+                if watch.as_ref().map(|w| w.path()) != path.as_deref() {
+                    watch = path.map(|path| {
+                        let file = path.display().to_string();
+                        info!("{}", state.tr("log.watch_file_started", &[("file", file.as_str())]));
+                        FileWatch::new(path)
+                    });
+                }
+                if let Some(watch) = watch.as_mut() {
+                    let debounce = Duration::from_millis(*state.watch_file_debounce_ms.lock().unwrap());
+                    match watch.poll(debounce) {
+                        Some(Ok(text)) => state.type_watched_file(watch.path(), text),
+                        Some(Err(e)) => {
+                            let err = e.to_string();
+                            error!("{}", state.tr("log.watch_file_read_fail", &[("err", err.as_str())]));
+                            state.set_status(&state.tr("status.watch_file_read_fail", &[("err", err.as_str())]));
+                        }
+                        None => {}
+                    }
+                }
+                thread::sleep(FILE_WATCH_POLL_INTERVAL);
+            }
+        });
+    }
+
+    /// 开启或关闭开机自启，失败时记录日志并返回 false
+    fn apply_autostart(&self, enabled: bool) -> bool {
+        match autostart::set_enabled(enabled) {
+            Ok(()) => true,
+            Err(e) => {
+                let err = e.to_string();
+                error!("{}", self.i18n.tr("log.autostart_fail", &[("err", err.as_str())]));
+                self.state
+                    .set_status(&self.i18n.tr("status.autostart_fail", &[("err", err.as_str())]));
+                false
+            }
+        }
+    }
+
+    /// 应用并保存应用设置窗口中的修改
+    fn apply_app_settings(&mut self) {
+        let i18n = self.i18n.clone();
+
+        #[cfg(target_os = "windows")]
+        {
+            let console_changed = self.app_config.show_console != self.temp_app_config.show_console;
+            if console_changed {
+                if self.temp_app_config.show_console {
+                    show_console_window();
+                } else {
+                    hide_console_window();
+                }
+            }
+        }
+
+        if self.app_config.auto_start != self.temp_app_config.auto_start
+            && !self.apply_autostart(self.temp_app_config.auto_start)
+        {
+            self.temp_app_config.auto_start = self.app_config.auto_start;
+        }
+
+        self.temp_app_config.history_max_items =
+            self.temp_app_config.history_max_items.clamp(1, HISTORY_MAX_ITEMS_LIMIT);
+        if self.temp_app_config.persist_enabled_state {
+            self.temp_app_config.start_enabled = *self.state.toggled_enabled.lock().unwrap();
+        }
+
+        let persist_history_changed =
+            self.app_config.persist_history != self.temp_app_config.persist_history;
+        // 加密的历史尚未解锁时内存中的历史不完整，更换口令或关闭加密会用它覆盖磁盘上的历史，
+        // 先要求解锁
+        let history_unlocked = self.state.history_key.lock().unwrap().is_some();
+        if self.app_config.encrypt_history
+            && !history_unlocked
+            && history_crypto::SUPPORTED
+            && (!self.temp_app_config.encrypt_history || !self.new_history_passphrase.is_empty())
+        {
+            self.temp_app_config.encrypt_history = true;
+            self.new_history_passphrase.clear();
+            self.show_history_unlock = true;
+            self.state.set_status(&i18n.t("status.history_unlock_required"));
+        }
+        // 开启加密或更换口令时派生新的密钥；首次开启加密必须输入口令
+        let mut new_history_key = None;
+        if self.temp_app_config.encrypt_history {
+            let passphrase = std::mem::take(&mut self.new_history_passphrase);
+            if !passphrase.is_empty() {
+                match HistoryKey::derive_new(&passphrase) {
+                    Ok(key) => new_history_key = Some(key),
+                    Err(e) => {
+                        let err = e.to_string();
+                        error!("{}", i18n.tr("log.history_key_fail", &[("err", err.as_str())]));
+                        self.state
+                            .set_status(&i18n.tr("status.history_key_fail", &[("err", err.as_str())]));
+                        self.temp_app_config.encrypt_history = self.app_config.encrypt_history;
+                    }
+                }
+            } else if !self.app_config.encrypt_history {
+                self.state.set_status(&i18n.t("status.history_passphrase_required"));
+                self.temp_app_config.encrypt_history = false;
+            }
+        }
+        let encrypt_history_changed =
+            self.app_config.encrypt_history != self.temp_app_config.encrypt_history;
+        let hotkey_enabled_changed =
+            self.app_config.hotkey_enabled != self.temp_app_config.hotkey_enabled;
+        let mouse_trigger_changed =
+            self.app_config.mouse_trigger != self.temp_app_config.mouse_trigger;
+        let tray_menu_changed = self.app_config.tray_menu != self.temp_app_config.tray_menu;
+        self.app_config = self.temp_app_config.clone();
+        if tray_menu_changed {
+            if let Some(tray_context) = &mut self.tray_context {
+                let (menu, items) = build_tray_menu(&i18n, &self.app_config.tray_menu);
+                tray_context.tray.set_menu(Some(Box::new(menu)));
+                tray_context.items = items;
+            }
+        }
+        if hotkey_enabled_changed {
+            if self.app_config.hotkey_enabled {
+                self.retry_hotkey_registration();
+            } else {
+                self.unregister_all_hotkeys();
+            }
+        }
+        if hotkey_enabled_changed || mouse_trigger_changed {
+            self.apply_mouse_trigger();
+        }
+        // 更新 state 中的配置
+        self.state.apply_config(&self.app_config);
+        if self.app_config.non_text_clipboard == NonTextClipboard::KeepLastText {
+            *self.state.non_text_content.lock().unwrap() = None;
+        }
+        if self.app_config.history_enabled {
+            self.state.trim_history();
+        } else {
+            self.state.clear_history();
+        }
+        if persist_history_changed && !self.app_config.persist_history {
+            if let Err(e) = history::remove() {
+                let err = e.to_string();
+                error!("{}", i18n.tr("log.save_history_fail", &[("err", err.as_str())]));
+            }
+        }
+        // 按新的加密设置重新保存历史（保存时会删除另一种格式的文件）
+        if let Some(key) = new_history_key {
+            *self.state.history_key.lock().unwrap() = Some(key);
+            self.state.trim_history();
+        } else if encrypt_history_changed && !self.app_config.encrypt_history {
+            *self.state.history_key.lock().unwrap() = None;
+            self.state.trim_history();
+            // 已解锁的历史改为明文保存后，不再保留加密的副本；从未解锁的加密历史保留在磁盘上
+            if history_unlocked && self.app_config.persist_history {
+                if let Err(e) = history::remove_encrypted() {
+                    let err = e.to_string();
+                    error!("{}", i18n.tr("log.save_history_fail", &[("err", err.as_str())]));
+                }
+            }
+        }
+        self.i18n.set_language(&self.app_config.language);
+
+        // 保存时包含当前的快捷键配置
+        self.app_config.hotkey = self.hotkey_config.clone();
+        if let Err(e) = self.app_config.save() {
+            let err = e.to_string();
+            error!(
+                "{}",
+                i18n.tr("log.save_app_config_fail", &[("err", err.as_str())])
+            );
+        } else {
+            self.state.set_status(&i18n.t("status.app_settings_saved"));
+        }
+    }
+
+    /// 把主窗口中临时调整的按键延迟保存为默认值
+    fn save_session_delay(&mut self, delay: u64) {
+        self.app_config.typing_delay = delay;
+        // 手动设置的延迟不再由目标 WPM 计算
+        self.app_config.delay_from_wpm = false;
+        self.temp_app_config.typing_delay = delay;
+        self.temp_app_config.delay_from_wpm = false;
+        match self.app_config.save() {
+            Ok(()) => {
+                let delay_str = delay.to_string();
+                self.state
+                    .set_status(&self.i18n.tr("status.default_delay_saved", &[("delay", delay_str.as_str())]));
+            }
+            Err(e) => {
+                let err = e.to_string();
+                error!("{}", self.i18n.tr("log.save_app_config_fail", &[("err", err.as_str())]));
+            }
+        }
+    }
+
+    /// 完成首次启动引导并保存选择
+    fn finish_onboarding(&mut self) {
+        self.show_onboarding = false;
+        if self.onboarding_autostart && self.apply_autostart(true) {
+            self.app_config.auto_start = true;
+        }
+        self.app_config.autostart_asked = true;
+        self.app_config.onboarded = true;
+        self.temp_app_config = self.app_config.clone();
+        if let Err(e) = self.app_config.save() {
+            let err = e.to_string();
+            error!("{}", self.i18n.tr("log.save_app_config_fail", &[("err", err.as_str())]));
+        }
+    }
+
+    /// 模拟键盘输入文本
+    fn type_text(&mut self) {
+        if self.app_config.confirm_edge_whitespace {
+            let text = self.state.get_clipboard_text();
+            if text_transform::has_edge_whitespace(&text) {
+                self.pending_whitespace_confirm = Some(text);
+                return;
+            }
+        }
+        self.state.execute_typing_request(TypingRequest {
+            refocus: true,
+            ..Default::default()
+        });
+    }
+
+    /// 输入已确认（或已去除首尾空白）的文本
+    fn type_confirmed_text(&mut self, text: String) {
+        self.pending_whitespace_confirm = None;
+        self.state.execute_typing_request(TypingRequest {
+            text: Some(text),
+            refocus: true,
+            ..Default::default()
+        });
+    }
+
+    /// 在系统文件管理器中打开配置目录
+    fn open_config_folder(&self) {
+        let Some(dir) = AppConfig::config_dir() else {
+            self.state.set_status(&self.i18n.t("status.config_folder_unavailable"));
+            return;
+        };
+        if let Err(e) = std::fs::create_dir_all(&dir).and_then(|_| open_in_file_manager(&dir)) {
+            let err = e.to_string();
+            error!("{}", self.i18n.tr("log.open_config_folder_fail", &[("err", err.as_str())]));
+            self.state
+                .set_status(&self.i18n.tr("status.open_config_folder_fail", &[("err", err.as_str())]));
+        }
+    }
+
+    /// 选择文件并把其中的片段导入历史
+    fn import_snippets(&self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Text / JSON", &["txt", "json"])
+            .pick_file()
+        else {
+            return;
+        };
+        match self.state.import_history(&path) {
+            Ok(count) => {
+                let count = count.to_string();
+                info!("{}", self.i18n.tr("log.snippets_imported", &[("count", count.as_str())]));
+                self.state
+                    .set_status(&self.i18n.tr("status.snippets_imported", &[("count", count.as_str())]));
+            }
+            Err(e) => {
+                let err = e.to_string();
+                error!("{}", self.i18n.tr("log.import_snippets_fail", &[("err", err.as_str())]));
+                self.state
+                    .set_status(&self.i18n.tr("status.import_snippets_fail", &[("err", err.as_str())]));
+            }
+        }
+    }
+
+    /// 打开历史选择列表：窗口置顶并移动到鼠标附近
+    fn open_history_picker(&mut self, ctx: &egui::Context) {
+        self.show_history_picker = true;
+        self.history_picker_selected = 0;
+        self.history_picker_focused = false;
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(egui::WindowLevel::AlwaysOnTop));
+
+        let (outer_rect, pixels_per_point) =
+            ctx.input(|i| (i.viewport().outer_rect, i.viewport().native_pixels_per_point));
+        if let (Some((x, y)), Some(outer)) = (cursor_position(), outer_rect) {
+            let scale = pixels_per_point.unwrap_or(1.0);
+            self.history_picker_restore_pos = Some(outer.min);
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(
+                x as f32 / scale,
+                y as f32 / scale,
+            )));
+        }
+    }
+
+    /// 关闭历史选择列表，取消置顶并恢复窗口位置，打开前窗口是隐藏的则重新隐藏
+    fn close_history_picker(&mut self, ctx: &egui::Context) {
+        self.show_history_picker = false;
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(egui::WindowLevel::Normal));
+        if let Some(position) = self.history_picker_restore_pos.take() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position));
+        }
+        if self.state.history_picker_hide_after.swap(false, Ordering::SeqCst) {
+            self.state.window_visible.store(false, Ordering::SeqCst);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        }
+    }
+
+    /// 是否有对话框或需要完整窗口的界面打开
+    fn dialog_open(&self) -> bool {
+        self.show_hotkey_settings
+            || self.show_app_settings
+            || self.show_permission_warning
+            || self.show_startup_hotkey_error
+            || self.show_log_viewer
+            || self.show_hotkey_diagnostics
+            || self.show_health_summary
+            || self.show_macro_editor
+            || self.show_registers
+            || self.show_onboarding
+            || self.show_history_unlock
+            || self.pending_whitespace_confirm.is_some()
+            || self.state.pending_command_confirm.lock().unwrap().is_some()
+            || self.show_history_picker
+    }
+
+    /// 汇总运行状态（权限、快捷键、剪贴板监听、配置路径、版本），每项为 (名称, 值, 是否正常)
+    fn health_report(&self) -> Vec<(String, String, bool)> {
+        let i18n = &self.i18n;
+        let yes_no = |ok: bool| {
+            if ok {
+                i18n.t("ui.label_health_ok")
+            } else {
+                i18n.t("ui.label_health_problem")
+            }
+        };
+        let mut rows = Vec::new();
+
+        rows.push((
+            i18n.t("ui.label_health_version"),
+            format!("{} ({} {})", env!("CARGO_PKG_VERSION"), std::env::consts::OS, std::env::consts::ARCH),
+            true,
+        ));
+
+        let permissions_ok = self.permission_status.all_granted();
+        let permissions = if permissions_ok {
+            yes_no(true)
+        } else {
+            format!("{}: {}", yes_no(false), self.permission_status.issues.join(", "))
+        };
+        rows.push((i18n.t("ui.label_health_permissions"), permissions, permissions_ok));
+
+        let manager_ok = self.hotkey_manager.is_some();
+        rows.push((i18n.t("ui.label_health_hotkey_manager"), yes_no(manager_ok), manager_ok));
+
+        let main_registered =
+            self.current_hotkey.is_some() && self.current_hotkey == self.hotkey_config.to_global_hotkey();
+        rows.push((
+            i18n.t("ui.label_main_hotkey"),
+            format!("{} - {}", self.hotkey_config.display(), yes_no(main_registered)),
+            main_registered,
+        ));
+
+        let expected_extra = self
+            .app_config
+            .action_hotkeys
+            .iter()
+            .filter(|(_, config)| config.to_global_hotkey().is_some())
+            .count()
+            + self
+                .app_config
+                .macros
+                .iter()
+                .filter(|m| m.hotkey.as_ref().and_then(|c| c.to_global_hotkey()).is_some())
+                .count();
+        let registered_extra = self.action_hotkeys.len() + self.macro_hotkeys.len();
+        rows.push((
+            i18n.t("ui.label_health_extra_hotkeys"),
+            format!("{}/{}", registered_extra, expected_extra),
+            registered_extra >= expected_extra,
+        ));
+
+        let monitor_running = self.state.clipboard_monitor_running.load(Ordering::SeqCst);
+        rows.push((i18n.t("ui.label_health_clipboard_monitor"), yes_no(monitor_running), monitor_running));
+
+        let (autostart, autostart_ok) = match autostart::is_registered() {
+            Ok(registered) => {
+                let os = if registered {
+                    i18n.t("ui.label_health_autostart_registered")
+                } else {
+                    i18n.t("ui.label_health_autostart_not_registered")
+                };
+                let config = if self.app_config.auto_start {
+                    i18n.t("ui.label_health_autostart_on")
+                } else {
+                    i18n.t("ui.label_health_autostart_off")
+                };
+                (
+                    i18n.tr("ui.label_health_autostart_value", &[("os", os.as_str()), ("config", config.as_str())]),
+                    registered == self.app_config.auto_start,
+                )
+            }
+            Err(e) => (format!("{}: {}", i18n.t("ui.label_health_unknown"), e), false),
+        };
+        rows.push((i18n.t("ui.label_health_autostart"), autostart, autostart_ok));
+
+        let config_path = AppConfig::config_path();
+        rows.push((
+            i18n.t("ui.label_health_config_path"),
+            config_path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| i18n.t("ui.label_health_unknown")),
+            config_path.is_some(),
+        ));
+
+        rows
+    }
+
+    /// 处理关闭窗口请求，按设置隐藏到托盘或退出
+    fn handle_close_request(&self, ctx: &egui::Context) {
+        let i18n = &self.i18n;
+        if ctx.input(|i| i.viewport().close_requested()) {
+            if !self.state.request_exit.load(Ordering::SeqCst) {
+                match self.app_config.close_action {
+                    CloseAction::MinimizeToTray => {
+                        // 取消关闭，改为隐藏
+                        ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                        self.state.window_visible.store(false, Ordering::SeqCst);
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+                        info!("{}", i18n.t("log.window_minimized_to_tray"));
+                    }
+                    CloseAction::ExitApp => {
+                        // 允许关闭
+                        info!("{}", i18n.t("log.app_exit"));
+                    }
+                }
+            }
+        }
+    }
+
+    /// 切换迷你模式并保存
+    fn set_mini_mode(&mut self, ctx: &egui::Context, enabled: bool) {
+        self.mini_mode = enabled;
+        self.app_config.mini_mode = enabled;
+        self.temp_app_config.mini_mode = enabled;
+        let (size, min_size, level) = if enabled {
+            (MINI_WINDOW_SIZE, MINI_WINDOW_SIZE, egui::WindowLevel::AlwaysOnTop)
+        } else {
+            (WINDOW_SIZE, WINDOW_MIN_SIZE, egui::WindowLevel::Normal)
+        };
+        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(!enabled));
+        ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(min_size));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+        if let Err(e) = self.app_config.save() {
+            let err = e.to_string();
+            error!("{}", self.i18n.tr("log.save_app_config_fail", &[("err", err.as_str())]));
+        }
+    }
+
+    /// 迷你部件：输入按钮和展开按钮，拖动空白处移动窗口
+    fn show_mini_widget(&mut self, ctx: &egui::Context) {
+        let i18n = self.i18n.clone();
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let drag = ui.interact(ui.max_rect(), ui.id().with("mini_drag"), egui::Sense::drag());
+            if drag.drag_started() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+            }
+
+            ui.horizontal_centered(|ui| {
+                let text = self.state.get_clipboard_text();
+                let preview = if text.is_empty() {
+                    i18n.t("ui.label_empty")
+                } else {
+                    truncate_text(&text, 80)
+                };
+                let can_type = self.state.is_enabled() && !self.state.is_typing() && !text.is_empty();
+                if ui
+                    .add_enabled(can_type, egui::Button::new("⌨"))
+                    .on_hover_text(preview)
+                    .on_disabled_hover_text(i18n.t("ui.tip_mini_type_unavailable"))
+                    .clicked()
+                {
+                    self.type_text();
+                }
+                if self.state.is_typing() {
+                    ui.spinner();
+                } else if ui
+                    .button("⤢")
+                    .on_hover_text(i18n.t("ui.button_exit_mini_mode"))
+                    .clicked()
+                {
+                    self.set_mini_mode(ctx, false);
+                }
+            });
+        });
+    }
+
+    /// 根据当前状态选择重绘间隔
+    fn repaint_interval(&self) -> Duration {
+        if self.state.is_typing() || self.dialog_open() {
+            REPAINT_INTERVAL_ACTIVE
+        } else if self.state.window_visible.load(Ordering::SeqCst) {
+            REPAINT_INTERVAL_IDLE
+        } else {
+            REPAINT_INTERVAL_HIDDEN
+        }
+    }
+
+    /// 处理快捷键事件
+    fn handle_hotkey_events(&self) {
+        // 快捷键事件现在由后台线程处理
+    }
+
+}
+
+impl eframe::App for CopyTypeApp {
+    /// 视口背景透明，由面板背景色决定窗口的不透明度
+    fn clear_color(&self, _visuals: &egui::Visuals) -> [f32; 4] {
+        [0.0; 4]
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let i18n = self.i18n.clone();
+        // 处理快捷键事件
+        self.handle_hotkey_events();
+
+        // 窗口不透明度，设置窗口打开时实时预览
+        let opacity = if self.show_app_settings {
+            self.temp_app_config.window_opacity
+        } else {
+            self.app_config.window_opacity
+        };
+        if opacity != self.applied_opacity {
+            apply_window_opacity(ctx, opacity);
+            self.applied_opacity = opacity;
+        }
+
+        // 请求持续重绘以处理事件，空闲时降低频率以节省 CPU
+        ctx.request_repaint_after(self.repaint_interval());
+
+        // 捕获到新内容时更新托盘提示
+        if let Some(preview) = self.state.capture_notice.lock().unwrap().take() {
+            if let Some(tray_context) = &self.tray_context {
+                let tooltip = format!("{}\n{}", i18n.t("tray.tooltip"), preview);
+                if let Err(e) = tray_context.tray.set_tooltip(Some(tooltip)) {
+                    let err = e.to_string();
+                    warn!("{}", i18n.tr("log.tray_tooltip_fail", &[("err", err.as_str())]));
+                }
+            }
+        }
+
+        // 定时启用状态变化时更新托盘提示
+        let schedule_state = self.state.schedule_state();
+        if schedule_state != self.tray_schedule_state {
+            self.tray_schedule_state = schedule_state;
+            if let Some(tray_context) = &self.tray_context {
+                let tooltip = match schedule_state {
+                    Some(true) => format!("{}\n{}", i18n.t("tray.tooltip"), i18n.t("ui.label_schedule_active")),
+                    Some(false) => format!("{}\n{}", i18n.t("tray.tooltip"), i18n.t("ui.label_schedule_inactive")),
+                    None => i18n.t("tray.tooltip"),
+                };
+                if let Err(e) = tray_context.tray.set_tooltip(Some(tooltip)) {
+                    let err = e.to_string();
+                    warn!("{}", i18n.tr("log.tray_tooltip_fail", &[("err", err.as_str())]));
+                }
+            }
+        }
+
+        // 输入完成后按设置隐藏窗口（迷你部件始终保留在屏幕上）
+        if self.state.hide_window_requested.swap(false, Ordering::SeqCst) && !self.mini_mode {
+            self.state.window_visible.store(false, Ordering::SeqCst);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        }
+
+        // 输入完成后按设置把本程序窗口切回前台
+        if self.state.refocus_self_requested.swap(false, Ordering::SeqCst) {
+            show_main_window(ctx, *self.state.own_window.lock().unwrap());
+        }
+
+        // 手动切换的启用状态可能已由托盘或主界面写入配置文件，保持内存中的配置一致，
+        // 避免之后保存其他设置时覆盖；小睡和定时切换不写入配置
+        if self.app_config.persist_enabled_state {
+            self.app_config.start_enabled = *self.state.toggled_enabled.lock().unwrap();
+        }
+
+        // 恢复上次的停靠位置（首帧时显示器尺寸可能还不可用）
+        if let Some(corner) = self.pending_dock {
+            if dock_window(ctx, corner) {
+                self.pending_dock = None;
+            }
+        }
+
+        // 迷你模式：有窗口需要显示时恢复完整界面，否则只显示迷你部件
+        if self.mini_mode {
+            if self.dialog_open() || self.state.history_picker_requested.load(Ordering::SeqCst) {
+                self.set_mini_mode(ctx, false);
+            } else {
+                self.show_mini_widget(ctx);
+                self.handle_close_request(ctx);
+                return;
+            }
+        }
+
+        // 权限警告窗口
+        if self.show_permission_warning {
+            egui::Window::new(i18n.t("ui.title_permission_warning"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(i18n.t("ui.label_permission_issues"));
+                    ui.add_space(10.0);
+
+                    if let Some(msg) = self.permission_status.get_warning_message(&i18n) {
+                        ui.label(msg);
+                    }
+
+                    ui.add_space(10.0);
+                    ui.separator();
+                    ui.add_space(10.0);
+
+                    ui.collapsing(i18n.t("ui.label_fix_suggestions"), |ui| {
+                        ui.label(get_permission_fix_instructions(&i18n));
+                    });
+
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button(i18n.t("ui.button_acknowledge")).clicked() {
+                            self.show_permission_warning = false;
+                        }
+                        if ui.button(i18n.t("ui.button_exit")).clicked() {
+                            self.state.request_exit.store(true, Ordering::SeqCst);
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        }
+                    });
+                });
+        }
+
+        // 启动时快捷键错误警告窗口
+        if self.show_startup_hotkey_error {
+            egui::Window::new(i18n.t("ui.title_hotkey_error"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(i18n.t("ui.label_hotkey_conflict_startup"));
+                    ui.add_space(10.0);
+
+                    if let Some(error) = &self.startup_hotkey_error {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 100, 100),
+                            error
+                        );
+                    }
+
+                    ui.add_space(10.0);
+                    ui.label(i18n.t("ui.label_hotkey_conflict_suggestion"));
+                    ui.add_space(10.0);
+                    ui.separator();
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button(i18n.t("ui.button_open_settings")).clicked() {
+                            self.show_startup_hotkey_error = false;
+                            self.show_hotkey_settings = true;
+                            self.temp_hotkey_config = self.hotkey_config.clone();
+                            self.temp_action_hotkeys = self.app_config.action_hotkeys.clone();
+                        }
+                        if ui.button(i18n.t("ui.button_retry_hotkey")).clicked() {
+                            self.retry_hotkey_registration();
+                        }
+                        if ui.button(i18n.t("ui.button_acknowledge")).clicked() {
+                            self.show_startup_hotkey_error = false;
+                        }
+                    });
+                });
+        }
+
+        // 历史选择列表
+        if self.state.history_picker_requested.swap(false, Ordering::SeqCst) {
+            self.open_history_picker(ctx);
+        }
+        if self.show_history_picker {
+            let entries: Vec<(usize, String)> = {
+                let history = self.state.clipboard_history.lock().unwrap();
+                self.app_config
+                    .history_sort
+                    .order(&history)
+                    .into_iter()
+                    .take(HISTORY_PICKER_MAX_ITEMS)
+                    .map(|i| (i, truncate_text(&history[i].text, 60)))
+                    .collect()
+            };
+
+            // 方向键移动选中项，回车输入选中项，数字键 1-9 直接输入对应条目
+            let (up, down, enter, escape, number, focused) = ctx.input(|i| {
+                (
+                    i.key_pressed(egui::Key::ArrowUp),
+                    i.key_pressed(egui::Key::ArrowDown),
+                    i.key_pressed(egui::Key::Enter),
+                    i.key_pressed(egui::Key::Escape),
+                    HISTORY_PICKER_NUMBER_KEYS.iter().position(|key| i.key_pressed(*key)),
+                    i.viewport().focused,
+                )
+            });
+            let last = entries.len().saturating_sub(1);
+            if up {
+                self.history_picker_selected = self.history_picker_selected.saturating_sub(1);
+            }
+            if down {
+                self.history_picker_selected = (self.history_picker_selected + 1).min(last);
+            }
+            self.history_picker_selected = self.history_picker_selected.min(last);
+
+            let mut chosen: Option<(usize, bool)> = None;
+            if enter {
+                chosen = entries.get(self.history_picker_selected).map(|(i, _)| (*i, true));
+            }
+            if let Some(number) = number {
+                chosen = entries.get(number).map(|(i, _)| (*i, true));
+            }
+
+            let mut open = true;
+            let anchor = if self.history_picker_restore_pos.is_some() {
+                (egui::Align2::LEFT_TOP, [8.0, 8.0])
+            } else {
+                (egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            };
+            egui::Window::new(i18n.t("ui.window_history_picker"))
+                .open(&mut open)
+                .collapsible(false)
+                .resizable(false)
+                .anchor(anchor.0, anchor.1)
+                .show(ctx, |ui| {
+                    if entries.is_empty() {
+                        ui.label(egui::RichText::new(i18n.t("ui.label_empty")).italics().weak());
+                    }
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for (row, (index, preview)) in entries.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let number = if row < HISTORY_PICKER_NUMBER_KEYS.len() {
+                                    (row + 1).to_string()
+                                } else {
+                                    String::new()
+                                };
+                                ui.label(egui::RichText::new(format!("{:>2}", number)).monospace().weak());
+                                if ui
+                                    .small_button("📋")
+                                    .on_hover_text(i18n.t("ui.button_copy_history_item"))
+                                    .clicked()
+                                {
+                                    chosen = Some((*index, false));
+                                }
+                                let label = ui.selectable_label(row == self.history_picker_selected, preview);
+                                if row == self.history_picker_selected && (up || down) {
+                                    label.scroll_to_me(None);
+                                }
+                                if label.clicked() {
+                                    chosen = Some((*index, true));
+                                }
+                            });
+                        }
+                    });
+                    ui.add_space(6.0);
+                    ui.label(egui::RichText::new(i18n.t("ui.history_picker_tip")).small().weak());
+                });
+
+            // 窗口获得过焦点后又失去焦点时关闭
+            let lost_focus = match focused {
+                Some(true) => {
+                    self.history_picker_focused = true;
+                    false
+                }
+                Some(false) => self.history_picker_focused,
+                None => false,
+            };
+            let text = chosen.and_then(|(index, type_it)| {
+                let history = self.state.clipboard_history.lock().unwrap();
+                history.get(index).map(|item| (item.text.clone(), type_it))
+            });
+            if !open || escape || lost_focus || text.is_some() {
+                self.close_history_picker(ctx);
+            }
+            match text {
+                Some((text, true)) => self.state.execute_typing_request(TypingRequest {
+                    text: Some(text),
+                    refocus: true,
+                    from_history: true,
+                    ..Default::default()
+                }),
+                Some((text, false)) => {
+                    ctx.copy_text(text);
+                    self.state.set_status(&i18n.t("status.history_item_copied"));
+                }
+                None => {}
+            }
+        }
+
+        // 首尾空白确认窗口
+        if let Some(text) = self.pending_whitespace_confirm.clone() {
+            egui::Window::new(i18n.t("ui.window_edge_whitespace"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(i18n.t("ui.label_edge_whitespace"));
+                    ui.add_space(10.0);
+
+                    let first_line = text.lines().next().unwrap_or_default();
+                    let last_line = text.lines().last().unwrap_or_default();
+                    let head = truncate_text(&text_transform::visualize_whitespace(first_line), 60);
+                    let tail_chars = last_line.chars().count();
+                    let tail: String = last_line.chars().skip(tail_chars.saturating_sub(60)).collect();
+                    let tail = text_transform::visualize_whitespace(&tail);
+                    ui.horizontal(|ui| {
+                        ui.label(i18n.t("ui.label_text_start"));
+                        ui.code(head);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(i18n.t("ui.label_text_end"));
+                        ui.code(if tail_chars > 60 { format!("...{}", tail) } else { tail });
+                    });
+
+                    ui.add_space(10.0);
+                    ui.separator();
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button(i18n.t("ui.button_trim_and_type")).clicked() {
+                            let trimmed = text_transform::trim_edge_whitespace(&text).to_string();
+                            self.type_confirmed_text(trimmed);
+                        }
+                        if ui.button(i18n.t("ui.button_type_as_is")).clicked() {
+                            self.type_confirmed_text(text.clone());
+                        }
+                        if ui.button(i18n.t("ui.button_cancel")).clicked() {
+                            self.pending_whitespace_confirm = None;
+                        }
+                    });
+                });
+        }
+
+        // 疑似命令确认窗口
+        let command_confirm = self.state.pending_command_confirm.lock().unwrap().clone();
+        if let Some(confirm) = command_confirm {
+            let mut decision = None;
+            let title = match &confirm.kind {
+                ConfirmKind::ShellCommands => i18n.t("ui.window_shell_commands"),
+                ConfirmKind::CrossApp { .. } => i18n.t("ui.window_cross_app"),
+                ConfirmKind::WatchedFile { .. } => i18n.t("ui.window_watch_file"),
+            };
+            egui::Window::new(title)
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    match &confirm.kind {
+                        ConfirmKind::ShellCommands => {
+                            ui.label(i18n.t("ui.label_shell_commands"));
+                        }
+                        ConfirmKind::CrossApp { source, target } => {
+                            ui.label(i18n.tr(
+                                "ui.label_cross_app",
+                                &[("source", source.as_str()), ("target", target.as_str())],
+                            ));
+                        }
+                        ConfirmKind::WatchedFile { file } => {
+                            ui.label(i18n.tr("ui.label_watch_file", &[("file", file.as_str())]));
+                        }
+                    }
+                    ui.add_space(10.0);
+
+                    let clipboard_text;
+                    let text = match &confirm.request.text {
+                        Some(text) => text.as_str(),
+                        None => {
+                            clipboard_text = self.state.get_clipboard_text();
+                            clipboard_text.as_str()
+                        }
+                    };
+                    let lines: Vec<&str> = text.lines().collect();
+                    for line in lines.iter().take(SHELL_CONFIRM_PREVIEW_LINES) {
+                        ui.code(truncate_text(line, 80));
+                    }
+                    if lines.len() > SHELL_CONFIRM_PREVIEW_LINES {
+                        let more = (lines.len() - SHELL_CONFIRM_PREVIEW_LINES) as u64;
+                        ui.label(egui::RichText::new(i18n.tr_plural("ui.label_more_lines", more)).weak());
+                    }
+
+                    ui.add_space(10.0);
+                    ui.separator();
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button(i18n.t("ui.button_type_anyway")).clicked() {
+                            decision = Some(true);
+                        }
+                        if ui.button(i18n.t("ui.button_cancel")).clicked() {
+                            decision = Some(false);
+                        }
+                    });
+                });
+            if let Some(accepted) = decision {
+                *self.state.pending_command_confirm.lock().unwrap() = None;
+                if confirm.hide_after {
+                    self.state.window_visible.store(false, Ordering::SeqCst);
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+                }
+                if accepted {
+                    self.state.execute_typing_request(TypingRequest {
+                        refocus: true,
+                        confirmed: true,
+                        ..confirm.request
+                    });
+                } else {
+                    self.state.set_status(&i18n.t("status.input_cancelled"));
+                }
+            }
+        }
+
+        // 首次启动引导窗口
+        if self.show_onboarding {
+            egui::Window::new(i18n.t("ui.onboarding.title"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    let hotkey = self.hotkey_config.display();
+                    ui.label(i18n.tr("ui.onboarding.label_how_it_works", &[("hotkey", hotkey.as_str())]));
+                    ui.add_space(6.0);
+                    ui.label(i18n.tr("ui.onboarding.label_pause", &[("hotkey", hotkey.as_str())]));
+                    ui.label(egui::RichText::new(i18n.t("ui.onboarding.label_change_hotkey")).small().weak());
+
+                    ui.add_space(10.0);
+                    ui.separator();
+                    ui.add_space(10.0);
+
+                    ui.checkbox(&mut self.onboarding_autostart, i18n.t("ui.onboarding.checkbox_autostart"));
+
+                    ui.add_space(10.0);
+                    if self.permission_status.all_granted() {
+                        ui.label(i18n.t("ui.onboarding.label_permissions_ok"));
+                    } else {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 165, 0),
+                            format!("⚠ {}", i18n.t("ui.onboarding.label_permissions_issue")),
+                        );
+                        if let Some(msg) = self.permission_status.get_warning_message(&i18n) {
+                            ui.label(msg);
+                        }
+                        ui.collapsing(i18n.t("ui.label_fix_suggestions"), |ui| {
+                            ui.label(get_permission_fix_instructions(&i18n));
+                        });
+                    }
+                    if ui.button(i18n.t("ui.menu_check_permissions")).clicked() {
+                        self.permission_status = check_permissions(&i18n);
+                    }
+
+                    ui.add_space(10.0);
+                    ui.separator();
+                    ui.add_space(10.0);
+
+                    if ui.button(i18n.t("ui.onboarding.button_get_started")).clicked() {
+                        self.finish_onboarding();
+                    }
+                });
+        }
+
+        // 顶部菜单栏
+        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                ui.menu_button(i18n.t("ui.menu_file"), |ui| {
+                    if ui.button(i18n.t("ui.menu_minimize_to_tray")).clicked() {
+                        self.state.window_visible.store(false, Ordering::SeqCst);
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+                        ui.close_menu();
+                    }
+                    ui.menu_button(i18n.t("ui.menu_dock"), |ui| {
+                        for corner in DockCorner::all() {
+                            if ui.button(i18n.t(corner.i18n_key())).clicked() {
+                                dock_window(ctx, corner);
+                                self.app_config.dock_corner = Some(corner);
+                                self.temp_app_config.dock_corner = Some(corner);
+                                if let Err(e) = self.app_config.save() {
+                                    let err = e.to_string();
+                                    error!(
+                                        "{}",
+                                        i18n.tr("log.save_app_config_fail", &[("err", err.as_str())])
+                                    );
+                                }
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    if ui.button(i18n.t("ui.menu_registers")).clicked() {
+                        self.show_registers = true;
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n.t("ui.menu_mini_mode")).clicked() {
+                        self.set_mini_mode(ctx, true);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button(i18n.t("ui.menu_exit")).clicked() {
+                        self.state.request_exit.store(true, Ordering::SeqCst);
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    }
+                });
+                ui.menu_button(i18n.t("ui.menu_settings"), |ui| {
+                    if ui.button(i18n.t("ui.menu_hotkey_settings")).clicked() {
+                        self.show_hotkey_settings = true;
+                        self.temp_hotkey_config = self.hotkey_config.clone();
+                        self.temp_action_hotkeys = self.app_config.action_hotkeys.clone();
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n.t("ui.menu_app_settings")).clicked() {
+                        self.show_app_settings = true;
+                        self.temp_app_config = self.app_config.clone();
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n.t("ui.menu_macros")).clicked() {
+                        self.show_macro_editor = true;
+                        self.temp_macros = self.app_config.macros.clone();
+                        self.macro_error = None;
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button(i18n.t("ui.menu_help"), |ui| {
+                    if ui.button(i18n.t("ui.menu_check_permissions")).clicked() {
+                        self.permission_status = check_permissions(&i18n);
+                        self.show_permission_warning = !self.permission_status.all_granted();
+                        if self.permission_status.all_granted() {
+                            self.state.set_status(&i18n.t("status.permissions_ok"));
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n.t("ui.menu_retry_hotkey")).clicked() {
+                        self.retry_hotkey_registration();
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n.t("ui.menu_hotkey_diagnostics")).clicked() {
+                        self.show_hotkey_diagnostics = true;
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n.t("ui.menu_health_summary")).clicked() {
+                        self.show_health_summary = true;
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n.t("ui.menu_view_logs")).clicked() {
+                        self.show_log_viewer = true;
+                        ui.close_menu();
+                    }
+                    if ui.button(i18n.t("ui.menu_open_config_folder")).clicked() {
+                        self.open_config_folder();
+                        ui.close_menu();
+                    }
+                });
+            });
+        });
+
+        // 底部状态栏
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let status = self.state.get_status();
+                ui.label(i18n.tr("ui.label_status", &[("status", status.as_str())]));
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if self.state.is_typing() {
+                        ui.spinner();
+                    }
+                    if let Some(active) = self.state.schedule_state() {
+                        let key = if active { "ui.label_schedule_active" } else { "ui.label_schedule_inactive" };
+                        ui.label(i18n.t(key));
+                    }
+                    if let Some(remaining) = self.state.snooze_remaining() {
+                        let secs = remaining.as_secs();
+                        let time = format!("{:02}:{:02}", secs / 60, secs % 60);
+                        ui.label(i18n.tr("ui.label_snooze_remaining", &[("time", time.as_str())]));
+                    }
+                    // 权限状态指示
+                    if !self.permission_status.all_granted() {
+                        ui.label(
+                            egui::RichText::new(i18n.t("ui.label_permission_problem"))
+                                .color(egui::Color32::YELLOW),
+                        );
+                    }
+                });
+            });
+        });
+
+        // 主面板
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(i18n.t("ui.title_main"));
+            ui.add_space(10.0);
+
+            // 启用/禁用开关
+            ui.horizontal(|ui| {
+                ui.label(i18n.t("ui.label_app_status"));
+                let mut enabled = self.state.is_enabled();
+                let label = if enabled {
+                    i18n.t("ui.label_enabled")
+                } else {
+                    i18n.t("ui.label_disabled")
+                };
+                if ui.toggle_value(&mut enabled, label).changed() {
+                    self.state.set_enabled(enabled);
+                    let status = if enabled {
+                        i18n.t("status.enabled")
+                    } else {
+                        i18n.t("status.disabled")
+                    };
+                    self.state.set_status(&status);
+                }
+
+                ui.menu_button(i18n.t("ui.menu_snooze"), |ui| {
+                    for minutes in SNOOZE_MINUTES {
+                        let minutes_str = minutes.to_string();
+                        if ui
+                            .button(i18n.tr("ui.snooze_minutes", &[("minutes", minutes_str.as_str())]))
+                            .clicked()
+                        {
+                            self.state.snooze(Duration::from_secs(minutes * 60));
+                            ui.close_menu();
+                        }
+                    }
+                });
+            });
+
+            ui.add_space(10.0);
+            ui.separator();
+            ui.add_space(10.0);
+
+            // 快捷键显示
+            ui.horizontal(|ui| {
+                ui.label(i18n.t("ui.label_current_hotkey"));
+                ui.code(self.hotkey_config.display());
+                if !self.app_config.hotkey_enabled {
+                    ui.label(egui::RichText::new(i18n.t("ui.label_hotkey_disabled")).weak());
+                }
+                if ui.button(i18n.t("ui.button_modify")).clicked() {
+                    self.show_hotkey_settings = true;
+                    self.temp_hotkey_config = self.hotkey_config.clone();
+                    self.temp_action_hotkeys = self.app_config.action_hotkeys.clone();
+                }
+            });
+
+            // 本次运行的按键延迟，调整后不写入配置文件
+            ui.horizontal(|ui| {
+                ui.label(i18n.t("ui.label_session_delay"));
+                let mut delay = *self.state.typing_delay.lock().unwrap();
+                if ui
+                    .add(egui::Slider::new(&mut delay, 0..=SESSION_DELAY_SLIDER_MAX).text("ms"))
+                    .changed()
+                {
+                    *self.state.typing_delay.lock().unwrap() = delay;
+                }
+                if delay != self.app_config.typing_delay {
+                    if ui.link(i18n.t("ui.link_save_as_default")).clicked() {
+                        self.save_session_delay(delay);
+                    }
+                    if ui.link(i18n.t("ui.link_reset_delay")).clicked() {
+                        *self.state.typing_delay.lock().unwrap() = self.app_config.typing_delay;
+                    }
+                }
+            });
+
+            ui.add_space(10.0);
+            ui.separator();
+            ui.add_space(10.0);
+
+            // 剪贴板内容预览
+            let clipboard_text = self.state.get_clipboard_text();
+            let history_enabled = *self.state.history_enabled.lock().unwrap();
+            let non_text_content = *self.state.non_text_content.lock().unwrap();
+
+            if !self.state.clipboard_monitor_running.load(Ordering::SeqCst) {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 100, 100),
+                        format!("⚠ {}", i18n.t("ui.label_clipboard_monitor_stopped")),
+                    );
+                    if ui.button(i18n.t("ui.button_restart_clipboard_monitor")).clicked() {
+                        self.start_clipboard_monitor();
+                    }
+                });
+                ui.add_space(10.0);
+            } else if *self.state.on_battery.lock().unwrap() == Some(true) {
+                let seconds = (self.app_config.battery_poll_interval_ms as f64 / 1000.0).to_string();
+                ui.label(
+                    egui::RichText::new(i18n.tr("ui.label_on_battery", &[("seconds", seconds.as_str())]))
+                        .small()
+                        .weak(),
+                );
+            }
+
+            if history_enabled {
+                ui.horizontal(|ui| {
+                    ui.label(i18n.t("ui.label_history_list"));
+                    ui.menu_button(i18n.t("ui.menu_clear_older_than"), |ui| {
+                        for (key, age) in HISTORY_CLEAR_AGES {
+                            if ui.button(i18n.t(key)).clicked() {
+                                let removed = self.state.clear_history_older_than(age).to_string();
+                                self.state.set_status(
+                                    &i18n.tr("status.history_items_cleared", &[("count", removed.as_str())]),
+                                );
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.separator();
+                    let mut skip_next = self.state.skip_next_record.load(Ordering::SeqCst);
+                    if ui
+                        .checkbox(&mut skip_next, i18n.t("ui.checkbox_skip_next_record"))
+                        .on_hover_text(i18n.t("ui.tip_skip_next_record"))
+                        .changed()
+                    {
+                        self.state.skip_next_record.store(skip_next, Ordering::SeqCst);
+                    }
+                    if ui.button(i18n.t("ui.button_import_snippets")).clicked() {
+                        self.import_snippets();
+                    }
+                    ui.separator();
+                    for sort in HistorySort::all() {
+                        if ui
+                            .selectable_value(&mut self.app_config.history_sort, sort, i18n.t(sort.i18n_key()))
+                            .changed()
+                        {
+                            self.temp_app_config.history_sort = sort;
+                            if let Err(e) = self.app_config.save() {
+                                let err = e.to_string();
+                                error!(
+                                    "{}",
+                                    i18n.tr("log.save_app_config_fail", &[("err", err.as_str())])
+                                );
+                            }
+                        }
+                    }
+                    let sources = history::sources(&self.state.clipboard_history.lock().unwrap());
+                    // 该来源的记录都已删除时恢复显示全部
+                    if self.history_source_filter.as_ref().is_some_and(|app| !sources.contains(app)) {
+                        self.history_source_filter = None;
+                    }
+                    if !sources.is_empty() {
+                        ui.separator();
+                        let filter = &mut self.history_source_filter;
+                        let selected = filter.clone().unwrap_or_else(|| i18n.t("ui.history_source_all"));
+                        egui::ComboBox::from_id_salt("history_source_filter")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(filter, None, i18n.t("ui.history_source_all"));
+                                for source in sources {
+                                    let label = source.clone();
+                                    ui.selectable_value(filter, Some(source), label);
+                                }
+                            });
+                    }
+                });
+                let mut type_from_history = None;
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        let history = self.state.clipboard_history.lock().unwrap();
+                        if history.is_empty() {
+                            ui.label(egui::RichText::new(i18n.t("ui.label_empty")).italics().weak());
+                        } else {
+                            let source_filter = self.history_source_filter.as_deref();
+                            let order: Vec<usize> = self
+                                .app_config
+                                .history_sort
+                                .order(&history)
+                                .into_iter()
+                                .filter(|&i| source_filter.is_none() || history[i].source.as_deref() == source_filter)
+                                .collect();
+                            let history_len = order.len();
+                            for (index, item) in order.into_iter().map(|i| &history[i]).enumerate() {
+                                egui::Frame::none()
+                                    .fill(ui.style().visuals.extreme_bg_color)
+                                    .inner_margin(8.0)
+                                    .rounding(4.0)
+                                    .show(ui, |ui| {
+                                        ui.set_min_width(ui.available_width());
+                                        ui.horizontal(|ui| {
+                                            let copied_at = item.copied_at();
+                                            let time_label = i18n.tr(
+                                                "ui.label_copied_time",
+                                                &[("time", copied_at.as_str())],
+                                            );
+                                            ui.label(egui::RichText::new(time_label).small().weak())
+                                                .on_hover_text(i18n.format_relative_time(item.age().as_secs()));
+                                            let chars = item.char_count() as u64;
+                                            ui.label(
+                                                egui::RichText::new(i18n.tr_plural("ui.label_history_char_count", chars))
+                                                .small()
+                                                .weak(),
+                                            );
+                                            if let Some(source) = &item.source {
+                                                ui.label(
+                                                    egui::RichText::new(
+                                                        i18n.tr("ui.label_history_source", &[("app", source.as_str())]),
+                                                    )
+                                                    .small()
+                                                    .weak(),
+                                                );
+                                            }
+                                            if item.count > 0 {
+                                                let count = item.count.to_string();
+                                                ui.label(
+                                                    egui::RichText::new(
+                                                        i18n.tr("ui.label_history_use_count", &[("count", count.as_str())]),
+                                                    )
+                                                    .small()
+                                                    .weak(),
+                                                );
+                                            }
+                                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                                if ui
+                                                    .small_button("⌨")
+                                                    .on_hover_text(i18n.t("ui.button_type_history_item"))
+                                                    .clicked()
+                                                {
+                                                    type_from_history = Some(item.text.clone());
+                                                }
+                                            });
+                                        });
+                                        ui.label(&item.text);
+                                    });
+                                if index + 1 < history_len {
+                                    ui.add_space(6.0);
+                                }
+                            }
+                        }
+                    });
+                if let Some(text) = type_from_history {
+                    self.state.execute_typing_request(TypingRequest {
+                        text: Some(text),
+                        refocus: true,
+                        from_history: true,
+                        ..Default::default()
+                    });
+                }
+            } else {
+                ui.label(i18n.t("ui.label_waiting_text"));
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        egui::Frame::none()
+                            .fill(ui.style().visuals.extreme_bg_color)
+                            .inner_margin(8.0)
+                            .rounding(4.0)
+                            .show(ui, |ui| {
+                                ui.set_min_width(ui.available_width());
+                                if let Some(content) = non_text_content {
+                                    ui.label(egui::RichText::new(i18n.t(content.i18n_key())).italics().weak());
+                                } else if clipboard_text.is_empty() {
+                                    ui.label(egui::RichText::new(i18n.t("ui.label_empty")).italics().weak());
+                                } else {
+                                    ui.label(&clipboard_text);
+                                }
+                            });
+                    });
+            }
+
+            ui.add_space(10.0);
+
+            if let Some(content) = non_text_content {
+                if history_enabled {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 165, 0),
+                        format!("⚠ {}", i18n.t(content.i18n_key())),
+                    );
+                }
+                if !clipboard_text.is_empty() {
+                    ui.label(egui::RichText::new(i18n.t("ui.tip_non_text_types_last")).small().weak());
+                }
+            }
+
+            // 文本信息
+            if !clipboard_text.is_empty() {
+                ui.horizontal(|ui| {
+                    let (char_count, line_count) = self.state.clipboard_counts();
+                    let char_count = char_count.to_string();
+                    let line_count = line_count.to_string();
+                    ui.label(i18n.tr("ui.label_char_count", &[("count", char_count.as_str())]));
+                    ui.label(i18n.tr("ui.label_line_count", &[("count", line_count.as_str())]));
+                });
+            }
+
+            // 累积模式
+            ui.horizontal(|ui| {
+                let mut accumulate = self.state.accumulate.load(Ordering::SeqCst);
+                if ui
+                    .checkbox(&mut accumulate, i18n.t("ui.checkbox_accumulate"))
+                    .on_hover_text(i18n.t("ui.tip_accumulate"))
+                    .changed()
+                {
+                    self.state.set_accumulate(accumulate);
+                }
+                if accumulate {
+                    let count = self.state.accumulated_count.load(Ordering::SeqCst).to_string();
+                    ui.label(
+                        egui::RichText::new(i18n.tr("ui.label_accumulated_count", &[("count", count.as_str())]))
+                            .weak(),
+                    );
+                    if ui.button(i18n.t("ui.button_reset_accumulated")).clicked() {
+                        self.state.reset_accumulated();
+                    }
+                }
+            });
+
+            ui.add_space(10.0);
+
+            // 手动触发按钮
+            ui.horizontal(|ui| {
+                let typing = self.state.is_typing();
+                let enabled = self.state.is_enabled();
+
+                if ui
+                    .add_enabled(
+                        enabled && !typing && !clipboard_text.is_empty(),
+                        egui::Button::new(i18n.t("ui.button_manual_type")),
+                    )
+                    .clicked()
+                {
+                    self.type_text();
+                }
+
+                let has_last = self.state.last_typed_text.lock().unwrap().is_some();
+                if ui
+                    .add_enabled(enabled && !typing && has_last, egui::Button::new(i18n.t("ui.button_repeat_last")))
+                    .clicked()
+                {
+                    self.state.repeat_last_typing(true);
+                }
+
+                let has_remaining = self.state.interrupted_tokens.lock().unwrap().is_some();
+                if has_remaining
+                    && ui
+                        .add_enabled(enabled && !typing, egui::Button::new(i18n.t("ui.button_type_remaining")))
+                        .clicked()
+                {
+                    self.state.execute_typing_request(TypingRequest {
+                        refocus: true,
+                        resume: true,
+                        ..Default::default()
+                    });
+                }
+
+                if typing && ui.button(i18n.t("ui.button_stop_typing")).clicked() {
+                    self.state.cancel_typing();
+                }
+
+                if ui.button(i18n.t("ui.button_clear")).clicked() {
+                    self.state.set_clipboard_text(String::new());
+                    self.state.set_status(&i18n.t("status.cleared"));
+                }
+            });
+
+            ui.add_space(10.0);
+
+            // 便签
+            egui::CollapsingHeader::new(i18n.t("ui.label_scratchpad"))
+                .id_salt("scratchpad")
+                .show(ui, |ui| {
+                    let is_empty = {
+                        let mut scratchpad = self.state.scratchpad.lock().unwrap();
+                        let response = ui.add(
+                            egui::TextEdit::multiline(&mut *scratchpad)
+                                .desired_rows(3)
+                                .desired_width(f32::INFINITY)
+                                .hint_text(i18n.t("ui.hint_scratchpad")),
+                        );
+                        if response.changed() {
+                            if let Err(e) = scratchpad::save(&scratchpad) {
+                                let err = e.to_string();
+                                error!("{}", i18n.tr("log.save_scratchpad_fail", &[("err", err.as_str())]));
+                            }
+                        }
+                        scratchpad.is_empty()
+                    };
+                    let typing = self.state.is_typing();
+                    let enabled = self.state.is_enabled();
+                    if ui
+                        .add_enabled(
+                            enabled && !typing && !is_empty,
+                            egui::Button::new(i18n.t("ui.button_type_scratchpad")),
+                        )
+                        .clicked()
+                    {
+                        self.state.type_scratchpad(true);
+                    }
+                });
+        });
+
+        // 快捷键设置窗口
+        if self.show_hotkey_settings {
+            egui::Window::new(i18n.t("ui.window_hotkey_settings"))
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    if !self.app_config.hotkey_enabled {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 165, 0),
+                            format!("⚠ {}", i18n.t("ui.warning_hotkey_disabled"))
+                        );
+                        ui.add_space(10.0);
+                    }
+
+                    hotkey_editor(ui, &mut self.temp_hotkey_config, "main", &i18n);
+
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        ui.label(i18n.t("ui.label_preview"));
+                        ui.code(self.temp_hotkey_config.display());
+                    });
+
+                    ui.add_space(10.0);
+                    ui.separator();
+
+                    // 附加操作快捷键
+                    ui.label(i18n.t("ui.label_action_hotkeys"));
+                    for action in HotkeyAction::all() {
+                        let mut bound = self.temp_action_hotkeys.contains_key(&action);
+                        if ui.checkbox(&mut bound, i18n.t(action.i18n_key())).changed() {
+                            if bound {
+                                self.temp_action_hotkeys.insert(action, action.default_hotkey());
+                            } else {
+                                self.temp_action_hotkeys.remove(&action);
+                            }
+                        }
+                        if let Some(config) = self.temp_action_hotkeys.get_mut(&action) {
+                            ui.indent(action.i18n_key(), |ui| {
+                                hotkey_editor(ui, config, action.i18n_key(), &i18n);
+                                ui.horizontal(|ui| {
+                                    ui.label(i18n.t("ui.label_preview"));
+                                    ui.code(config.display());
+                                });
+                            });
+                        }
+                    }
+
+                    ui.add_space(10.0);
+
+                    // 验证快捷键
+                    let actions_changed = self.temp_action_hotkeys != self.app_config.action_hotkeys;
+                    let is_valid = self.temp_hotkey_config.is_valid()
+                        && self.temp_action_hotkeys.values().all(|config| config.is_valid());
+                    let is_same = self.temp_hotkey_config.conflicts_with(&self.hotkey_config)
+                        && !actions_changed;
+                    let conflict =
+                        hotkey_config::find_conflict(&self.temp_hotkey_config, &self.temp_action_hotkeys);
+                    let can_save =
+                        is_valid && !is_same && conflict.is_none() && self.app_config.hotkey_enabled;
+
+                    // 显示警告
+                    if !is_valid {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 100, 100),
+                            format!("⚠ {}", i18n.t("ui.error_no_modifier_key"))
+                        );
+                        ui.add_space(10.0);
+                    } else if let Some(conflict) = conflict {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 100, 100),
+                            format!("⚠ {}", hotkey_conflict_message(&i18n, conflict))
+                        );
+                        ui.add_space(10.0);
+                    } else if is_same {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 165, 0),
+                            format!("⚠ {}", i18n.t("ui.warning_same_hotkey"))
+                        );
+                        ui.add_space(10.0);
+                    }
+
+                    // 显示注册错误（如果有）
+                    if let Some(error) = &self.hotkey_register_error {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 100, 100),
+                            format!("⚠ {}: {}", i18n.t("ui.error_hotkey_conflict"), error)
+                        );
+                        ui.add_space(10.0);
+                    }
+
+                    // 测试已注册的主快捷键
                     ui.horizontal(|ui| {
-                        if ui.button(i18n.t("ui.button_acknowledge")).clicked() {
-                            self.show_permission_warning = false;
+                        let display = self.hotkey_config.display();
+                        ui.label(i18n.tr("ui.label_test_hotkey", &[("hotkey", display.as_str())]));
+                        let mut test = self.state.hotkey_test.lock().unwrap();
+                        if let Some(HotkeyTest::Waiting(since)) = *test {
+                            if since.elapsed() >= HOTKEY_TEST_TIMEOUT {
+                                *test = Some(HotkeyTest::TimedOut);
+                            }
                         }
-                        if ui.button(i18n.t("ui.button_exit")).clicked() {
-                            self.state.request_exit.store(true, Ordering::SeqCst);
-                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        let waiting = matches!(*test, Some(HotkeyTest::Waiting(_)));
+                        let registered = self.current_hotkey.is_some();
+                        if ui
+                            .add_enabled(registered && !waiting, egui::Button::new(i18n.t("ui.button_test_hotkey")))
+                            .clicked()
+                        {
+                            *test = Some(HotkeyTest::Waiting(Instant::now()));
+                        }
+                        match *test {
+                            Some(HotkeyTest::Waiting(since)) => {
+                                ui.spinner();
+                                let remaining = HOTKEY_TEST_TIMEOUT.saturating_sub(since.elapsed()).as_secs() + 1;
+                                let remaining = remaining.to_string();
+                                ui.label(i18n.tr("ui.label_hotkey_test_waiting", &[("seconds", remaining.as_str())]));
+                            }
+                            Some(HotkeyTest::Detected) => {
+                                ui.colored_label(
+                                    egui::Color32::from_rgb(100, 200, 100),
+                                    format!("✓ {}", i18n.t("ui.label_hotkey_test_detected")),
+                                );
+                            }
+                            Some(HotkeyTest::TimedOut) => {
+                                ui.colored_label(
+                                    egui::Color32::from_rgb(255, 100, 100),
+                                    format!("✗ {}", i18n.t("ui.label_hotkey_test_timeout")),
+                                );
+                            }
+                            None if !registered => {
+                                ui.label(egui::RichText::new(i18n.t("ui.label_hotkey_not_registered")).weak());
+                            }
+                            None => {}
+                        }
+                    });
+
+                    ui.add_space(10.0);
+                    ui.separator();
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        // 如果无效或相同，禁用保存按钮
+                        ui.add_enabled_ui(can_save, |ui| {
+                            if ui.button(i18n.t("ui.button_save")).clicked() {
+                                self.update_hotkey();
+                                // 只有在没有错误时才关闭窗口
+                                if self.hotkey_register_error.is_none() {
+                                    self.show_hotkey_settings = false;
+                                    *self.state.hotkey_test.lock().unwrap() = None;
+                                }
+                            }
+                            if ui.button(i18n.t("ui.button_apply")).clicked() {
+                                self.update_hotkey();
+                            }
+                        });
+                        if ui.button(i18n.t("ui.button_cancel")).clicked() {
+                            self.hotkey_register_error = None;
+                            self.show_hotkey_settings = false;
+                            *self.state.hotkey_test.lock().unwrap() = None;
                         }
                     });
                 });
         }
 
-        // 启动时快捷键错误警告窗口
-        if self.show_startup_hotkey_error {
-            egui::Window::new(i18n.t("ui.title_hotkey_error"))
+        // 应用设置窗口
+        if self.show_app_settings {
+            egui::Window::new(i18n.t("ui.window_app_settings"))
                 .collapsible(false)
                 .resizable(false)
-                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
                 .show(ctx, |ui| {
-                    ui.label(i18n.t("ui.label_hotkey_conflict_startup"));
-                    ui.add_space(10.0);
+                    ui.label(i18n.t("ui.app.label_close_window_action"));
 
-                    if let Some(error) = &self.startup_hotkey_error {
-                        ui.colored_label(
-                            egui::Color32::from_rgb(255, 100, 100),
-                            error
+                    ui.horizontal(|ui| {
+                        ui.radio_value(
+                            &mut self.temp_app_config.close_action,
+                            CloseAction::MinimizeToTray,
+                            i18n.t("ui.app.close_action_minimize_to_tray"),
                         );
-                    }
+                        ui.radio_value(
+                            &mut self.temp_app_config.close_action,
+                            CloseAction::ExitApp,
+                            i18n.t("ui.app.close_action_exit"),
+                        );
+                    });
 
                     ui.add_space(10.0);
-                    ui.label(i18n.t("ui.label_hotkey_conflict_suggestion"));
-                    ui.add_space(10.0);
-                    ui.separator();
+
+                    ui.checkbox(
+                        &mut self.temp_app_config.auto_start,
+                        i18n.t("ui.app.checkbox_auto_start"),
+                    );
+                    ui.checkbox(
+                        &mut self.temp_app_config.start_minimized,
+                        i18n.t("ui.app.checkbox_start_minimized"),
+                    );
+                    ui.add_enabled(
+                        !self.temp_app_config.persist_enabled_state,
+                        egui::Checkbox::new(
+                            &mut self.temp_app_config.start_enabled,
+                            i18n.t("ui.app.checkbox_start_enabled"),
+                        ),
+                    );
+                    ui.checkbox(
+                        &mut self.temp_app_config.persist_enabled_state,
+                        i18n.t("ui.app.checkbox_persist_enabled_state"),
+                    );
+                    ui.checkbox(
+                        &mut self.temp_app_config.hotkey_enabled,
+                        i18n.t("ui.app.checkbox_hotkey_enabled"),
+                    );
+                    if !self.temp_app_config.hotkey_enabled {
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_hotkey_disabled")).small().weak());
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(i18n.t("ui.app.label_mouse_trigger"));
+                        let trigger = &mut self.temp_app_config.mouse_trigger;
+                        let selected = trigger.map_or_else(|| i18n.t("ui.mouse_trigger.none"), |t| i18n.t(t.i18n_key()));
+                        let supported = cfg!(target_os = "windows");
+                        ui.add_enabled_ui(supported && self.temp_app_config.hotkey_enabled, |ui| {
+                            egui::ComboBox::from_id_salt("mouse_trigger_select")
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(trigger, None, i18n.t("ui.mouse_trigger.none"));
+                                    for button in MouseTrigger::all() {
+                                        ui.selectable_value(trigger, Some(button), i18n.t(button.i18n_key()));
+                                    }
+                                });
+                        });
+                    });
+                    #[cfg(not(target_os = "windows"))]
+                    ui.label(egui::RichText::new(i18n.t("ui.app.tip_mouse_trigger_unsupported")).small().weak());
+                    ui.horizontal(|ui| {
+                        ui.label(i18n.t("ui.app.label_main_hotkey_action"));
+                        let action = &mut self.temp_app_config.main_hotkey_action;
+                        egui::ComboBox::from_id_salt("main_hotkey_action_select")
+                            .selected_text(i18n.t(action.i18n_key()))
+                            .show_ui(ui, |ui| {
+                                for option in MainHotkeyAction::all() {
+                                    ui.selectable_value(action, option, i18n.t(option.i18n_key()));
+                                }
+                            });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(i18n.t("ui.app.label_paused_hotkey_action"));
+                        let action = &mut self.temp_app_config.paused_hotkey_action;
+                        egui::ComboBox::from_id_salt("paused_hotkey_action_select")
+                            .selected_text(i18n.t(action.i18n_key()))
+                            .show_ui(ui, |ui| {
+                                for option in PausedHotkeyAction::all() {
+                                    ui.selectable_value(action, option, i18n.t(option.i18n_key()));
+                                }
+                            });
+                    });
+                    ui.label(egui::RichText::new(i18n.t("ui.app.tip_paused_hotkey_action")).small().weak());
+                    ui.horizontal(|ui| {
+                        ui.label(i18n.t("ui.app.label_double_cancel_quit_ms"));
+                        ui.add(
+                            egui::DragValue::new(&mut self.temp_app_config.double_cancel_quit_ms)
+                                .range(0..=2000)
+                                .speed(10.0)
+                                .suffix(" ms"),
+                        );
+                    });
+                    ui.label(egui::RichText::new(i18n.t("ui.app.tip_double_cancel_quit")).small().weak());
+
                     ui.add_space(10.0);
 
                     ui.horizontal(|ui| {
-                        if ui.button(i18n.t("ui.button_open_settings")).clicked() {
-                            self.show_startup_hotkey_error = false;
-                            self.show_hotkey_settings = true;
-                            self.temp_hotkey_config = self.hotkey_config.clone();
+                        ui.label(i18n.t("ui.app.label_language"));
+                        let selected_label = i18n
+                            .available_languages()
+                            .iter()
+                            .find(|(code, _)| *code == self.temp_app_config.language.as_str())
+                            .map(|(_, name)| (*name).to_string())
+                            .unwrap_or_else(|| self.temp_app_config.language.clone());
+
+                        egui::ComboBox::from_id_salt("language_select")
+                            .selected_text(selected_label)
+                            .show_ui(ui, |ui| {
+                                for (code, name) in i18n.available_languages() {
+                                    ui.selectable_value(
+                                        &mut self.temp_app_config.language,
+                                        code.to_string(),
+                                        format!("{} ({})", name, code),
+                                    );
+                                }
+                            });
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(i18n.t("ui.app.label_window_opacity"));
+                        ui.add(
+                            egui::Slider::new(&mut self.temp_app_config.window_opacity, MIN_WINDOW_OPACITY..=1.0)
+                                .custom_formatter(|value, _| format!("{:.0}%", value * 100.0)),
+                        );
+                    });
+
+                    ui.add_space(10.0);
+
+                    ui.label(i18n.t("ui.app.group_typing_settings"));
+                    ui.group(|ui| {
+                        ui.checkbox(&mut self.temp_app_config.delay_from_wpm, i18n.t("ui.app.checkbox_delay_from_wpm"));
+                        if self.temp_app_config.delay_from_wpm {
+                            ui.horizontal(|ui| {
+                                ui.label(i18n.t("ui.app.label_target_wpm"));
+                                ui.add(egui::Slider::new(&mut self.temp_app_config.target_wpm, 10..=300).text("WPM"));
+                            });
+                            self.temp_app_config.typing_delay = AppConfig::wpm_to_delay_ms(self.temp_app_config.target_wpm);
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_base_delay_ms"));
+                            ui.add_enabled(
+                                !self.temp_app_config.delay_from_wpm,
+                                egui::Slider::new(&mut self.temp_app_config.typing_delay, 0..=2000).text("ms"),
+                            );
+                            
+                            // 计算并显示字每分钟
+                            let speed_text = match estimated_cpm(
+                                self.temp_app_config.typing_delay,
+                                self.temp_app_config.typing_variance,
+                            ) {
+                                Some(chars_per_minute) => {
+                                    let cpm = chars_per_minute.to_string();
+                                    i18n.tr("ui.app.typing_speed", &[("cpm", cpm.as_str())])
+                                }
+                                None => i18n.t("ui.app.typing_speed_infinite"),
+                            };
+                            
+                            ui.label(egui::RichText::new(speed_text).weak());
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_variance_ms"));
+                            ui.add(egui::Slider::new(&mut self.temp_app_config.typing_variance, 0..=1000).text("ms"));
+                        });
+
+                        ui.horizontal(|ui| {
+                            let mut fixed_seed = self.temp_app_config.variance_seed.is_some();
+                            if ui.checkbox(&mut fixed_seed, i18n.t("ui.app.checkbox_variance_seed")).changed() {
+                                self.temp_app_config.variance_seed = fixed_seed.then_some(0);
+                            }
+                            if let Some(seed) = &mut self.temp_app_config.variance_seed {
+                                ui.add(egui::DragValue::new(seed));
+                            }
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_variance_seed")).small().weak());
+
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_line_delay_ms"));
+                            ui.add(egui::Slider::new(&mut self.temp_app_config.line_delay_ms, 0..=2000).text("ms"));
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut self.temp_app_config.split_long_lines, i18n.t("ui.app.label_split_long_lines"));
+                            ui.add_enabled(
+                                self.temp_app_config.split_long_lines,
+                                egui::DragValue::new(&mut self.temp_app_config.split_long_lines_at).range(1..=10000),
+                            );
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_split_long_lines")).small().weak());
+
+                        ui.checkbox(
+                            &mut self.temp_app_config.char_class_delays.enabled,
+                            i18n.t("ui.app.checkbox_char_class_delays"),
+                        );
+                        ui.add_enabled_ui(self.temp_app_config.char_class_delays.enabled, |ui| {
+                            egui::Grid::new("char_class_delays_grid").show(ui, |ui| {
+                                for class in CharClass::all() {
+                                    ui.label(i18n.t(class.i18n_key()));
+                                    ui.add(
+                                        egui::Slider::new(self.temp_app_config.char_class_delays.get_mut(class), 0..=500)
+                                            .text("ms"),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_char_class_delays")).small().weak());
+
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_min_enforced_delay_ms"));
+                            ui.add(egui::Slider::new(&mut self.temp_app_config.min_enforced_delay_ms, 0..=200).text("ms"));
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_max_type_duration_ms"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.temp_app_config.max_type_duration_ms)
+                                    .speed(1000.0)
+                                    .suffix(" ms"),
+                            );
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_max_type_duration")).small().weak());
+
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_post_type_cooldown_ms"));
+                            ui.add(
+                                egui::DragValue::new(&mut self.temp_app_config.post_type_cooldown_ms)
+                                    .range(0..=60000)
+                                    .speed(50.0)
+                                    .suffix(" ms"),
+                            );
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_post_type_cooldown")).small().weak());
+
+                         ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_presets"));
+                            for preset in SPEED_PRESETS {
+                                if ui.button(i18n.t(&preset.i18n_key())).clicked() {
+                                    self.temp_app_config.delay_from_wpm = false;
+                                    self.temp_app_config.typing_delay = preset.delay;
+                                    self.temp_app_config.typing_variance = preset.variance;
+                                }
+                            }
+                        });
+
+
+                        ui.checkbox(
+                            &mut self.temp_app_config.key_tokens_enabled,
+                            i18n.t("ui.app.checkbox_key_tokens"),
+                        );
+                        ui.label(egui::RichText::new(i18n.t("ui.app.key_tokens_tip")).small().weak());
+
+                        ui.checkbox(
+                            &mut self.temp_app_config.refocus_external_window,
+                            i18n.t("ui.app.checkbox_refocus_external_window"),
+                        );
+                        if ui
+                            .checkbox(
+                                &mut self.temp_app_config.auto_hide_after_type,
+                                i18n.t("ui.app.checkbox_auto_hide_after_type"),
+                            )
+                            .changed()
+                            && self.temp_app_config.auto_hide_after_type
+                        {
+                            self.temp_app_config.refocus_self_after_type = false;
+                        }
+                        if ui
+                            .checkbox(
+                                &mut self.temp_app_config.refocus_self_after_type,
+                                i18n.t("ui.app.checkbox_refocus_self_after_type"),
+                            )
+                            .changed()
+                            && self.temp_app_config.refocus_self_after_type
+                        {
+                            self.temp_app_config.auto_hide_after_type = false;
+                        }
+                        ui.checkbox(
+                            &mut self.temp_app_config.restore_clipboard_to_typed,
+                            i18n.t("ui.app.checkbox_restore_clipboard_to_typed"),
+                        );
+                        ui.checkbox(
+                            &mut self.temp_app_config.confirm_edge_whitespace,
+                            i18n.t("ui.app.checkbox_confirm_edge_whitespace"),
+                        );
+                        ui.add_enabled_ui(cfg!(target_os = "windows"), |ui| {
+                            ui.checkbox(
+                                &mut self.temp_app_config.confirm_cross_app,
+                                i18n.t("ui.app.checkbox_confirm_cross_app"),
+                            );
+                        });
+                        #[cfg(not(target_os = "windows"))]
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_confirm_cross_app_unsupported")).small().weak());
+                        ui.checkbox(
+                            &mut self.temp_app_config.notify_on_capture,
+                            i18n.t("ui.app.checkbox_notify_on_capture"),
+                        );
+                        ui.checkbox(
+                            &mut self.temp_app_config.capture_on_startup,
+                            i18n.t("ui.app.checkbox_capture_on_startup"),
+                        );
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_accumulate_separator"));
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.temp_app_config.accumulate_separator)
+                                    .desired_width(80.0),
+                            );
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.accumulate_separator_tip")).small().weak());
+
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_on_session_lock"));
+                            let on_session_lock = &mut self.temp_app_config.on_session_lock;
+                            ui.add_enabled_ui(cfg!(target_os = "windows"), |ui| {
+                                egui::ComboBox::from_id_salt("on_session_lock_select")
+                                    .selected_text(i18n.t(on_session_lock.i18n_key()))
+                                    .show_ui(ui, |ui| {
+                                        for action in SessionLockAction::all() {
+                                            ui.selectable_value(on_session_lock, action, i18n.t(action.i18n_key()));
+                                        }
+                                    });
+                            });
+                        });
+                        #[cfg(not(target_os = "windows"))]
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_session_lock_unsupported")).small().weak());
+                        ui.checkbox(&mut self.temp_app_config.sound_cues, i18n.t("ui.app.checkbox_sound_cues"));
+                        if self.temp_app_config.sound_cues {
+                            ui.indent("sound_cue_events", |ui| {
+                                ui.horizontal(|ui| {
+                                    for cue in SoundCue::all() {
+                                        ui.checkbox(
+                                            self.temp_app_config.sound_cue_events.get_mut(cue),
+                                            i18n.t(cue.i18n_key()),
+                                        );
+                                    }
+                                });
+                            });
+                        }
+                        ui.checkbox(
+                            &mut self.temp_app_config.block_password_fields,
+                            i18n.t("ui.app.checkbox_block_password_fields"),
+                        );
+                        #[cfg(not(target_os = "windows"))]
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_block_password_fields_unsupported")).small().weak());
+                        ui.add_enabled_ui(cfg!(target_os = "windows"), |ui| {
+                            ui.horizontal(|ui| {
+                                ui.checkbox(
+                                    &mut self.temp_app_config.wait_for_click_before_type,
+                                    i18n.t("ui.app.checkbox_wait_for_click"),
+                                );
+                                ui.add_enabled(
+                                    self.temp_app_config.wait_for_click_before_type,
+                                    egui::DragValue::new(&mut self.temp_app_config.click_wait_timeout_ms)
+                                        .range(1000..=120000)
+                                        .speed(100.0)
+                                        .suffix(" ms"),
+                                );
+                            });
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_wait_for_click")).small().weak());
+
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_normalize_unicode"));
+                            let normalize = &mut self.temp_app_config.transform.normalize_unicode;
+                            egui::ComboBox::from_id_salt("normalize_unicode_select")
+                                .selected_text(i18n.t(normalize.i18n_key()))
+                                .show_ui(ui, |ui| {
+                                    for mode in UnicodeNormalization::all() {
+                                        ui.selectable_value(normalize, mode, i18n.t(mode.i18n_key()));
+                                    }
+                                });
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.normalize_unicode_tip")).small().weak());
+
+                        ui.checkbox(
+                            &mut self.temp_app_config.transform.strip_invisible,
+                            i18n.t("ui.app.checkbox_strip_invisible"),
+                        );
+                        ui.checkbox(
+                            &mut self.temp_app_config.transform.strip_code_fences,
+                            i18n.t("ui.app.checkbox_strip_code_fences"),
+                        );
+                        ui.checkbox(
+                            &mut self.temp_app_config.transform.strip_trailing_newline,
+                            i18n.t("ui.app.checkbox_strip_trailing_newline"),
+                        );
+                        ui.checkbox(
+                            &mut self.temp_app_config.transform.terminal_safe,
+                            i18n.t("ui.app.checkbox_terminal_safe"),
+                        );
+                        if self.temp_app_config.transform.terminal_safe {
+                            ui.indent("terminal_safe", |ui| {
+                                ui.checkbox(
+                                    &mut self.temp_app_config.confirm_shell_commands,
+                                    i18n.t("ui.app.checkbox_confirm_shell_commands"),
+                                );
+                            });
+                        }
+                        ui.label(egui::RichText::new(i18n.t("ui.app.terminal_safe_tip")).small().weak());
+
+                        ui.horizontal(|ui| {
+                            let transform = &mut self.temp_app_config.transform;
+                            let mut expand = transform.tabs_to_spaces.is_some();
+                            if ui.checkbox(&mut expand, i18n.t("ui.app.checkbox_tabs_to_spaces")).changed() {
+                                transform.tabs_to_spaces = expand.then_some(4);
+                                if expand {
+                                    transform.spaces_to_tabs = None;
+                                }
+                            }
+                            if let Some(width) = &mut transform.tabs_to_spaces {
+                                ui.add(egui::DragValue::new(width).range(1..=16));
+                                ui.checkbox(&mut transform.tab_column_aware, i18n.t("ui.app.checkbox_tab_column_aware"));
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            let transform = &mut self.temp_app_config.transform;
+                            let mut merge = transform.spaces_to_tabs.is_some();
+                            if ui.checkbox(&mut merge, i18n.t("ui.app.checkbox_spaces_to_tabs")).changed() {
+                                transform.spaces_to_tabs = merge.then_some(4);
+                                if merge {
+                                    transform.tabs_to_spaces = None;
+                                }
+                            }
+                            if let Some(width) = &mut transform.spaces_to_tabs {
+                                ui.add(egui::DragValue::new(width).range(1..=16));
+                            }
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_tabs_spaces")).small().weak());
+
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_restrict_charset"));
+                            let restriction = &mut self.temp_app_config.transform.restrict_charset;
+                            egui::ComboBox::from_id_salt("restrict_charset_select")
+                                .selected_text(i18n.t(restriction.i18n_key()))
+                                .show_ui(ui, |ui| {
+                                    for charset in CharsetRestriction::all() {
+                                        ui.selectable_value(restriction, charset, i18n.t(charset.i18n_key()));
+                                    }
+                                });
+                        });
+                        if self.temp_app_config.transform.restrict_charset == CharsetRestriction::Custom {
+                            ui.horizontal(|ui| {
+                                ui.label(i18n.t("ui.app.label_custom_charset"));
+                                ui.text_edit_singleline(&mut self.temp_app_config.transform.custom_charset);
+                            });
                         }
-                        if ui.button(i18n.t("ui.button_acknowledge")).clicked() {
-                            self.show_startup_hotkey_error = false;
+                        if self.temp_app_config.transform.restrict_charset != CharsetRestriction::None {
+                            ui.label(egui::RichText::new(i18n.t("ui.app.restrict_charset_tip")).small().weak());
                         }
-                    });
-                });
-        }
 
-        // 顶部菜单栏
-        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-            egui::menu::bar(ui, |ui| {
-                ui.menu_button(i18n.t("ui.menu_file"), |ui| {
-                    if ui.button(i18n.t("ui.menu_minimize_to_tray")).clicked() {
-                        self.state.window_visible.store(false, Ordering::SeqCst);
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
-                        ui.close_menu();
-                    }
-                    ui.separator();
-                    if ui.button(i18n.t("ui.menu_exit")).clicked() {
-                        self.state.request_exit.store(true, Ordering::SeqCst);
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                    }
-                });
-                ui.menu_button(i18n.t("ui.menu_settings"), |ui| {
-                    if ui.button(i18n.t("ui.menu_hotkey_settings")).clicked() {
-                        self.show_hotkey_settings = true;
-                        self.temp_hotkey_config = self.hotkey_config.clone();
-                        ui.close_menu();
-                    }
-                    if ui.button(i18n.t("ui.menu_app_settings")).clicked() {
-                        self.show_app_settings = true;
-                        self.temp_app_config = self.app_config.clone();
-                        ui.close_menu();
-                    }
-                });
-                ui.menu_button(i18n.t("ui.menu_help"), |ui| {
-                    if ui.button(i18n.t("ui.menu_check_permissions")).clicked() {
-                        self.permission_status = check_permissions(&i18n);
-                        self.show_permission_warning = !self.permission_status.all_granted();
-                        if self.permission_status.all_granted() {
-                            self.state.set_status(&i18n.t("status.permissions_ok"));
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_preprocess_command"));
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.temp_app_config.preprocess_command)
+                                    .hint_text(i18n.t("ui.app.hint_preprocess_command"))
+                                    .desired_width(200.0),
+                            );
+                        });
+                        if !self.temp_app_config.preprocess_command.trim().is_empty() {
+                            ui.horizontal(|ui| {
+                                ui.label(i18n.t("ui.app.label_preprocess_timeout_ms"));
+                                ui.add(
+                                    egui::DragValue::new(&mut self.temp_app_config.preprocess_timeout_ms)
+                                        .range(100..=60000)
+                                        .speed(100.0)
+                                        .suffix(" ms"),
+                                );
+                            });
+                            ui.colored_label(
+                                egui::Color32::from_rgb(255, 165, 0),
+                                format!("⚠ {}", i18n.t("ui.app.warning_preprocess_command")),
+                            );
                         }
-                        ui.close_menu();
-                    }
-                });
-            });
-        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.preprocess_command_tip")).small().weak());
 
-        // 底部状态栏
-        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
-            ui.horizontal(|ui| {
-                let status = self.state.get_status();
-                ui.label(i18n.tr("ui.label_status", &[("status", status.as_str())]));
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_watch_file"));
+                            let file = match &self.temp_app_config.watch_file {
+                                Some(path) => path.display().to_string(),
+                                None => i18n.t("ui.app.watch_file_none"),
+                            };
+                            ui.label(egui::RichText::new(file).monospace());
+                            if ui.button(i18n.t("ui.app.button_choose_watch_file")).clicked() {
+                                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                                    self.temp_app_config.watch_file = Some(path);
+                                }
+                            }
+                            if self.temp_app_config.watch_file.is_some()
+                                && ui.button(i18n.t("ui.app.button_clear_watch_file")).clicked()
+                            {
+                                self.temp_app_config.watch_file = None;
+                            }
+                        });
+                        if self.temp_app_config.watch_file.is_some() {
+                            ui.checkbox(
+                                &mut self.temp_app_config.watch_file_confirm,
+                                i18n.t("ui.app.checkbox_watch_file_confirm"),
+                            );
+                            ui.horizontal(|ui| {
+                                ui.label(i18n.t("ui.app.label_watch_file_debounce_ms"));
+                                ui.add(
+                                    egui::DragValue::new(&mut self.temp_app_config.watch_file_debounce_ms)
+                                        .range(100..=10000)
+                                        .speed(50.0)
+                                        .suffix(" ms"),
+                                );
+                            });
+                            if !self.temp_app_config.watch_file_confirm {
+                                ui.colored_label(
+                                    egui::Color32::from_rgb(255, 165, 0),
+                                    format!("⚠ {}", i18n.t("ui.app.warning_watch_file_no_confirm")),
+                                );
+                            }
+                        }
+                        ui.label(egui::RichText::new(i18n.t("ui.app.watch_file_tip")).small().weak());
 
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    if self.state.is_typing() {
-                        ui.spinner();
-                    }
-                    // 权限状态指示
-                    if !self.permission_status.all_granted() {
-                        ui.label(
-                            egui::RichText::new(i18n.t("ui.label_permission_problem"))
-                                .color(egui::Color32::YELLOW),
+                        ui.checkbox(
+                            &mut self.temp_app_config.column_paste,
+                            i18n.t("ui.app.checkbox_column_paste"),
                         );
-                    }
-                });
-            });
-        });
-
-        // 主面板
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading(i18n.t("ui.title_main"));
-            ui.add_space(10.0);
-
-            // 启用/禁用开关
-            ui.horizontal(|ui| {
-                ui.label(i18n.t("ui.label_app_status"));
-                let mut enabled = self.state.is_enabled();
-                let label = if enabled {
-                    i18n.t("ui.label_enabled")
-                } else {
-                    i18n.t("ui.label_disabled")
-                };
-                if ui.toggle_value(&mut enabled, label).changed() {
-                    self.state.set_enabled(enabled);
-                    let status = if enabled {
-                        i18n.t("status.enabled")
-                    } else {
-                        i18n.t("status.disabled")
-                    };
-                    self.state.set_status(&status);
-                }
-            });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.column_paste_tip")).small().weak());
+                        ui.checkbox(
+                            &mut self.temp_app_config.line_by_line,
+                            i18n.t("ui.app.checkbox_line_by_line"),
+                        );
+                        ui.label(egui::RichText::new(i18n.t("ui.app.line_by_line_tip")).small().weak());
 
-            ui.add_space(10.0);
-            ui.separator();
-            ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_emoji_mode"));
+                            let emoji_mode = &mut self.temp_app_config.emoji_mode;
+                            egui::ComboBox::from_id_salt("emoji_mode_select")
+                                .selected_text(i18n.t(emoji_mode.i18n_key()))
+                                .show_ui(ui, |ui| {
+                                    for mode in EmojiMode::all() {
+                                        ui.selectable_value(emoji_mode, mode, i18n.t(mode.i18n_key()));
+                                    }
+                                });
+                        });
+                        if self.temp_app_config.emoji_mode == EmojiMode::PasteFallback {
+                            ui.label(egui::RichText::new(i18n.t("ui.app.emoji_paste_fallback_tip")).small().weak());
+                        }
 
-            // 快捷键显示
-            ui.horizontal(|ui| {
-                ui.label(i18n.t("ui.label_current_hotkey"));
-                ui.code(self.hotkey_config.display());
-                if ui.button(i18n.t("ui.button_modify")).clicked() {
-                    self.show_hotkey_settings = true;
-                    self.temp_hotkey_config = self.hotkey_config.clone();
-                }
-            });
+                        ui.checkbox(
+                            &mut self.temp_app_config.paste_rich,
+                            i18n.t("ui.app.checkbox_paste_rich"),
+                        );
+                        ui.label(egui::RichText::new(i18n.t("ui.app.paste_rich_tip")).small().weak());
 
-            ui.add_space(10.0);
-            ui.separator();
-            ui.add_space(10.0);
+                        if self.temp_app_config.typing_delay.max(self.temp_app_config.min_enforced_delay_ms) == 0 {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(255, 165, 0),
+                                format!("⚠ {}", i18n.t("ui.app.warning_zero_delay"))
+                            );
+                        }
 
-            // 剪贴板内容预览
-            let clipboard_text = self.state.get_clipboard_text();
-            let history_enabled = *self.state.history_enabled.lock().unwrap();
+                        ui.label(egui::RichText::new(i18n.t("ui.app.typing_tip")).small().weak());
+                    });
 
-            if history_enabled {
-                ui.label(i18n.t("ui.label_history_list"));
-                egui::ScrollArea::vertical()
-                    .max_height(200.0)
-                    .show(ui, |ui| {
-                        let history = self.state.clipboard_history.lock().unwrap();
-                        if history.is_empty() {
-                            ui.label(egui::RichText::new(i18n.t("ui.label_empty")).italics().weak());
-                        } else {
-                            let history_len = history.len();
-                            for (index, item) in history.iter().rev().enumerate() {
-                                egui::Frame::none()
-                                    .fill(ui.style().visuals.extreme_bg_color)
-                                    .inner_margin(8.0)
-                                    .rounding(4.0)
-                                    .show(ui, |ui| {
-                                        ui.set_min_width(ui.available_width());
-                                        let time_label = i18n.tr(
-                                            "ui.label_copied_time",
-                                            &[("time", item.copied_at.as_str())],
+                    ui.add_space(10.0);
+                    ui.label(i18n.t("ui.app.group_history_settings"));
+                    ui.group(|ui| {
+                        ui.checkbox(
+                            &mut self.temp_app_config.history_enabled,
+                            i18n.t("ui.app.checkbox_history_enabled"),
+                        );
+                        ui.checkbox(
+                            &mut self.temp_app_config.pause_monitor_while_typing,
+                            i18n.t("ui.app.checkbox_pause_monitor_while_typing"),
+                        );
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_history_max_items"));
+                            let max_items = &mut self.temp_app_config.history_max_items;
+                            if *max_items <= HISTORY_SLIDER_MAX {
+                                ui.add_enabled(
+                                    self.temp_app_config.history_enabled,
+                                    egui::Slider::new(max_items, 1..=HISTORY_SLIDER_MAX).show_value(false),
+                                );
+                            }
+                            ui.add_enabled(
+                                self.temp_app_config.history_enabled,
+                                egui::DragValue::new(max_items)
+                                    .range(1..=HISTORY_MAX_ITEMS_LIMIT)
+                                    .suffix(format!(" {}", i18n.t("ui.app.history_item_unit"))),
+                            );
+                        });
+                        if self.temp_app_config.history_enabled
+                            && self.temp_app_config.history_max_items > HISTORY_SLIDER_MAX
+                            && !self.temp_app_config.persist_history
+                        {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(255, 165, 0),
+                                format!("⚠ {}", i18n.t("ui.app.warning_large_history_memory")),
+                            );
+                        }
+                        ui.add_enabled(
+                            self.temp_app_config.history_enabled,
+                            egui::Checkbox::new(
+                                &mut self.temp_app_config.persist_history,
+                                i18n.t("ui.app.checkbox_persist_history"),
+                            ),
+                        );
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_persist_history")).small().weak());
+                        ui.add_enabled_ui(
+                            history_crypto::SUPPORTED
+                                && self.temp_app_config.history_enabled
+                                && self.temp_app_config.persist_history,
+                            |ui| {
+                                ui.checkbox(
+                                    &mut self.temp_app_config.encrypt_history,
+                                    i18n.t("ui.app.checkbox_encrypt_history"),
+                                );
+                                if self.temp_app_config.encrypt_history {
+                                    ui.horizontal(|ui| {
+                                        ui.label(i18n.t("ui.app.label_history_passphrase"));
+                                        ui.add(
+                                            egui::TextEdit::singleline(&mut self.new_history_passphrase)
+                                                .password(true),
                                         );
-                                        ui.label(egui::RichText::new(time_label).small().weak());
-                                        ui.label(&item.text);
                                     });
-                                if index + 1 < history_len {
-                                    ui.add_space(6.0);
+                                    ui.label(
+                                        egui::RichText::new(i18n.t("ui.app.tip_history_passphrase")).small().weak(),
+                                    );
                                 }
-                            }
+                            },
+                        );
+                        if !history_crypto::SUPPORTED {
+                            ui.label(
+                                egui::RichText::new(i18n.t("ui.app.tip_encrypt_history_unsupported")).small().weak(),
+                            );
+                        } else if self.app_config.encrypt_history
+                            && self.state.history_key.lock().unwrap().is_none()
+                            && ui.button(i18n.t("ui.app.button_unlock_history")).clicked()
+                        {
+                            self.show_history_unlock = true;
                         }
                     });
-            } else {
-                ui.label(i18n.t("ui.label_waiting_text"));
-                egui::ScrollArea::vertical()
-                    .max_height(200.0)
-                    .show(ui, |ui| {
-                        egui::Frame::none()
-                            .fill(ui.style().visuals.extreme_bg_color)
-                            .inner_margin(8.0)
-                            .rounding(4.0)
-                            .show(ui, |ui| {
-                                ui.set_min_width(ui.available_width());
-                                if clipboard_text.is_empty() {
-                                    ui.label(egui::RichText::new(i18n.t("ui.label_empty")).italics().weak());
-                                } else {
-                                    ui.label(&clipboard_text);
+
+                    ui.add_space(10.0);
+                    ui.label(i18n.t("ui.app.group_tray_menu"));
+                    ui.group(|ui| {
+                        let entries = &mut self.temp_app_config.tray_menu;
+                        let len = entries.len();
+                        let mut move_up = None;
+                        let mut move_down = None;
+                        let mut remove = None;
+                        for (index, entry) in entries.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("{}. {}", index + 1, i18n.t(entry.i18n_key())));
+                                if ui.add_enabled(index > 0, egui::Button::new("↑")).clicked() {
+                                    move_up = Some(index);
+                                }
+                                if ui.add_enabled(index + 1 < len, egui::Button::new("↓")).clicked() {
+                                    move_down = Some(index);
+                                }
+                                if ui.add_enabled(len > 1, egui::Button::new("✕")).clicked() {
+                                    remove = Some(index);
                                 }
                             });
-                    });
-            }
-
-            ui.add_space(10.0);
-
-            // 文本信息
-            if !clipboard_text.is_empty() {
-                ui.horizontal(|ui| {
-                    let char_count = clipboard_text.chars().count().to_string();
-                    let line_count = clipboard_text.lines().count().to_string();
-                    ui.label(i18n.tr("ui.label_char_count", &[("count", char_count.as_str())]));
-                    ui.label(i18n.tr("ui.label_line_count", &[("count", line_count.as_str())]));
-                });
-            }
+                        }
+                        if let Some(index) = move_up {
+                            entries.swap(index, index - 1);
+                        }
+                        if let Some(index) = move_down {
+                            entries.swap(index, index + 1);
+                        }
+                        if let Some(index) = remove {
+                            entries.remove(index);
+                        }
 
-            ui.add_space(10.0);
+                        let missing: Vec<TrayMenuEntry> = TrayMenuEntry::all()
+                            .into_iter()
+                            .filter(|entry| !entries.contains(entry))
+                            .collect();
+                        if !missing.is_empty() {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(i18n.t("ui.app.label_add_tray_item"));
+                                for entry in missing {
+                                    if ui.button(i18n.t(entry.i18n_key())).clicked() {
+                                        entries.push(entry);
+                                    }
+                                }
+                            });
+                        }
+                        if ui.link(i18n.t("ui.app.link_reset_tray_menu")).clicked() {
+                            *entries = TrayMenuEntry::default_menu();
+                        }
+                    });
 
-            // 手动触发按钮
-            ui.horizontal(|ui| {
-                let typing = self.state.is_typing();
-                let enabled = self.state.is_enabled();
+                    ui.add_space(10.0);
+                    ui.label(i18n.t("ui.app.group_clipboard_formats"));
+                    ui.group(|ui| {
+                        let priority = &mut self.temp_app_config.clipboard_format_priority;
+                        let len = priority.len();
+                        let mut move_up = None;
+                        let mut move_down = None;
+                        let mut remove = None;
+                        for (index, format) in priority.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("{}. {}", index + 1, i18n.t(format.i18n_key())));
+                                if ui.add_enabled(index > 0, egui::Button::new("↑")).clicked() {
+                                    move_up = Some(index);
+                                }
+                                if ui.add_enabled(index + 1 < len, egui::Button::new("↓")).clicked() {
+                                    move_down = Some(index);
+                                }
+                                if ui.add_enabled(len > 1, egui::Button::new("✕")).clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                        }
+                        if let Some(index) = move_up {
+                            priority.swap(index, index - 1);
+                        }
+                        if let Some(index) = move_down {
+                            priority.swap(index, index + 1);
+                        }
+                        if let Some(index) = remove {
+                            priority.remove(index);
+                        }
 
-                if ui
-                    .add_enabled(
-                        enabled && !typing && !clipboard_text.is_empty(),
-                        egui::Button::new(i18n.t("ui.button_manual_type")),
-                    )
-                    .clicked()
-                {
-                    self.type_text();
-                }
+                        let missing: Vec<ClipboardFormat> = ClipboardFormat::all()
+                            .into_iter()
+                            .filter(|format| !priority.contains(format))
+                            .collect();
+                        if !missing.is_empty() {
+                            ui.horizontal(|ui| {
+                                ui.label(i18n.t("ui.app.label_add_format"));
+                                for format in missing {
+                                    if ui.button(i18n.t(format.i18n_key())).clicked() {
+                                        priority.push(format);
+                                    }
+                                }
+                            });
+                        }
+                        ui.label(egui::RichText::new(i18n.t("ui.app.clipboard_formats_tip")).small().weak());
 
-                if ui.button(i18n.t("ui.button_clear")).clicked() {
-                    *self.state.clipboard_text.lock().unwrap() = String::new();
-                    self.state.set_status(&i18n.t("status.cleared"));
-                }
-            });
-        });
+                        ui.checkbox(
+                            &mut self.temp_app_config.markdown_mode,
+                            i18n.t("ui.app.checkbox_markdown_mode"),
+                        );
+                        ui.label(egui::RichText::new(i18n.t("ui.app.markdown_mode_tip")).small().weak());
 
-        // 快捷键设置窗口
-        if self.show_hotkey_settings {
-            egui::Window::new(i18n.t("ui.window_hotkey_settings"))
-                .collapsible(false)
-                .resizable(false)
-                .show(ctx, |ui| {
-                    ui.label(i18n.t("ui.label_modifiers"));
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_capture_selection"));
+                            let selection = &mut self.temp_app_config.capture_selection;
+                            ui.add_enabled_ui(cfg!(target_os = "linux"), |ui| {
+                                egui::ComboBox::from_id_salt("capture_selection_select")
+                                    .selected_text(i18n.t(selection.i18n_key()))
+                                    .show_ui(ui, |ui| {
+                                        for option in CaptureSelection::all() {
+                                            ui.selectable_value(selection, option, i18n.t(option.i18n_key()));
+                                        }
+                                    });
+                            });
+                        });
+                        #[cfg(not(target_os = "linux"))]
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_capture_selection_unsupported")).small().weak());
 
-                    ui.horizontal(|ui| {
-                        ui.checkbox(&mut self.temp_hotkey_config.ctrl, "Ctrl");
-                        ui.checkbox(&mut self.temp_hotkey_config.shift, "Shift");
-                        ui.checkbox(&mut self.temp_hotkey_config.alt, "Alt");
-                        #[cfg(target_os = "macos")]
-                        ui.checkbox(&mut self.temp_hotkey_config.meta, "Cmd");
-                        #[cfg(not(target_os = "macos"))]
-                        ui.checkbox(&mut self.temp_hotkey_config.meta, "Win");
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_battery_poll_interval_ms"));
+                            ui.add(
+                                egui::Slider::new(&mut self.temp_app_config.battery_poll_interval_ms, 500..=10000)
+                                    .text("ms"),
+                            );
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_battery_poll_interval")).small().weak());
+
+                        ui.horizontal(|ui| {
+                            ui.label(i18n.t("ui.app.label_non_text_clipboard"));
+                            let non_text = &mut self.temp_app_config.non_text_clipboard;
+                            egui::ComboBox::from_id_salt("non_text_clipboard_select")
+                                .selected_text(i18n.t(non_text.i18n_key()))
+                                .show_ui(ui, |ui| {
+                                    for option in NonTextClipboard::all() {
+                                        ui.selectable_value(non_text, option, i18n.t(option.i18n_key()));
+                                    }
+                                });
+                        });
                     });
 
                     ui.add_space(10.0);
+                    ui.label(i18n.t("ui.app.group_source_filter"));
+                    ui.group(|ui| {
+                        let filter = &mut self.temp_app_config.source_filter;
+                        ui.horizontal(|ui| {
+                            for mode in SourceFilterMode::all() {
+                                ui.radio_value(&mut filter.mode, mode, i18n.t(mode.i18n_key()));
+                            }
+                        });
 
-                    ui.horizontal(|ui| {
-                        ui.label(i18n.t("ui.label_keys"));
-                        egui::ComboBox::from_label("")
-                            .selected_text(self.temp_hotkey_config.key.display())
-                            .show_ui(ui, |ui| {
-                                for key in KeyCode::all() {
-                                    ui.selectable_value(
-                                        &mut self.temp_hotkey_config.key,
-                                        key.clone(),
-                                        key.display(),
-                                    );
+                        let mut remove = None;
+                        for (index, app) in filter.apps.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(app);
+                                if ui.button("✕").clicked() {
+                                    remove = Some(index);
                                 }
                             });
-                    });
+                        }
+                        if let Some(index) = remove {
+                            filter.apps.remove(index);
+                        }
 
-                    ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.new_source_app)
+                                    .hint_text("notepad.exe")
+                                    .desired_width(150.0),
+                            );
+                            let name = self.new_source_app.trim().to_string();
+                            let can_add = !name.is_empty()
+                                && !filter.apps.iter().any(|app| app.eq_ignore_ascii_case(&name));
+                            if ui.add_enabled(can_add, egui::Button::new(i18n.t("ui.app.button_add_source_app"))).clicked() {
+                                filter.apps.push(name);
+                                self.new_source_app.clear();
+                            }
+                        });
 
-                    ui.horizontal(|ui| {
-                        ui.label(i18n.t("ui.label_preview"));
-                        ui.code(self.temp_hotkey_config.display());
+                        if let Some(source) = self.state.last_clipboard_source.lock().unwrap().clone() {
+                            ui.label(
+                                egui::RichText::new(i18n.tr("ui.app.label_last_source", &[("app", source.as_str())]))
+                                    .small()
+                                    .weak(),
+                            );
+                        }
+                        ui.label(egui::RichText::new(i18n.t("ui.app.source_filter_tip")).small().weak());
                     });
 
                     ui.add_space(10.0);
+                    ui.label(i18n.t("ui.app.group_capture_blocklist"));
+                    ui.group(|ui| {
+                        let blocklist = &mut self.temp_app_config.capture_blocklist;
+                        let mut remove = None;
+                        for (index, app) in blocklist.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(app);
+                                if ui.button("✕").clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                        }
+                        if let Some(index) = remove {
+                            blocklist.remove(index);
+                        }
 
-                    // 验证快捷键
-                    let is_valid = self.temp_hotkey_config.is_valid();
-                    let is_same = self.temp_hotkey_config.conflicts_with(&self.hotkey_config);
-                    let can_save = is_valid && !is_same;
-
-                    // 显示警告
-                    if !is_valid {
-                        ui.colored_label(
-                            egui::Color32::from_rgb(255, 100, 100),
-                            format!("⚠ {}", i18n.t("ui.error_no_modifier_key"))
-                        );
-                        ui.add_space(10.0);
-                    } else if is_same {
-                        ui.colored_label(
-                            egui::Color32::from_rgb(255, 165, 0),
-                            format!("⚠ {}", i18n.t("ui.warning_same_hotkey"))
-                        );
-                        ui.add_space(10.0);
-                    }
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.new_blocked_app)
+                                    .hint_text("KeePassXC.exe")
+                                    .desired_width(150.0),
+                            );
+                            let name = self.new_blocked_app.trim().to_string();
+                            let can_add = !name.is_empty()
+                                && !blocklist.iter().any(|app| app.eq_ignore_ascii_case(&name));
+                            if ui.add_enabled(can_add, egui::Button::new(i18n.t("ui.app.button_add_source_app"))).clicked() {
+                                blocklist.push(name);
+                                self.new_blocked_app.clear();
+                            }
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.capture_blocklist_tip")).small().weak());
+                    });
 
-                    // 显示注册错误（如果有）
-                    if let Some(error) = &self.hotkey_register_error {
-                        ui.colored_label(
-                            egui::Color32::from_rgb(255, 100, 100),
-                            format!("⚠ {}: {}", i18n.t("ui.error_hotkey_conflict"), error)
+                    ui.add_space(10.0);
+                    ui.label(i18n.t("ui.app.group_schedule"));
+                    ui.group(|ui| {
+                        let schedule = &mut self.temp_app_config.schedule;
+                        ui.checkbox(&mut schedule.enabled, i18n.t("ui.app.checkbox_schedule"));
+                        ui.add_enabled_ui(schedule.enabled, |ui| {
+                            let mut remove = None;
+                            for (index, window) in schedule.windows.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    for (day, key) in window.days.iter_mut().zip(WEEKDAY_KEYS) {
+                                        ui.checkbox(day, i18n.t(key));
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    time_of_day_editor(ui, &mut window.start_minute);
+                                    ui.label("–");
+                                    time_of_day_editor(ui, &mut window.end_minute);
+                                    if ui.button("✕").clicked() {
+                                        remove = Some(index);
+                                    }
+                                });
+                            }
+                            if let Some(index) = remove {
+                                schedule.windows.remove(index);
+                            }
+                            if ui.button(i18n.t("ui.app.button_add_schedule_window")).clicked() {
+                                schedule.windows.push(ScheduleWindow::default());
+                            }
+                        });
+                        ui.label(egui::RichText::new(i18n.t("ui.app.tip_schedule")).small().weak());
+                    });
+                    
+                    #[cfg(target_os = "windows")]
+                    {
+                        ui.add_space(5.0);
+                        ui.checkbox(
+                            &mut self.temp_app_config.show_console,
+                            i18n.t("ui.app.checkbox_show_console"),
                         );
-                        ui.add_space(10.0);
+                        ui.label(egui::RichText::new(i18n.t("ui.app.label_restart_required")).small().weak());
                     }
 
+                    ui.add_space(10.0);
                     ui.separator();
                     ui.add_space(10.0);
 
                     ui.horizontal(|ui| {
-                        // 如果无效或相同，禁用保存按钮
-                        ui.add_enabled_ui(can_save, |ui| {
-                            if ui.button(i18n.t("ui.button_save")).clicked() {
-                                self.update_hotkey();
-                                // 只有在没有错误时才关闭窗口
-                                if self.hotkey_register_error.is_none() {
-                                    self.show_hotkey_settings = false;
-                                }
-                            }
-                        });
+                        if ui.button(i18n.t("ui.button_save")).clicked() {
+                            self.apply_app_settings();
+                            self.show_app_settings = false;
+                        }
+                        if ui.button(i18n.t("ui.button_apply")).clicked() {
+                            self.apply_app_settings();
+                        }
                         if ui.button(i18n.t("ui.button_cancel")).clicked() {
-                            self.hotkey_register_error = None;
-                            self.show_hotkey_settings = false;
+                            self.show_app_settings = false;
                         }
                     });
                 });
         }
 
-        // 应用设置窗口
-        if self.show_app_settings {
-            egui::Window::new(i18n.t("ui.window_app_settings"))
-                .collapsible(false)
-                .resizable(false)
+        // 日志查看窗口
+        if self.show_log_viewer {
+            let mut open = true;
+            egui::Window::new(i18n.t("ui.window_logs"))
+                .open(&mut open)
+                .default_size([520.0, 320.0])
                 .show(ctx, |ui| {
-                    ui.label(i18n.t("ui.app.label_close_window_action"));
-
                     ui.horizontal(|ui| {
-                        ui.radio_value(
-                            &mut self.temp_app_config.close_action,
-                            CloseAction::MinimizeToTray,
-                            i18n.t("ui.app.close_action_minimize_to_tray"),
-                        );
-                        ui.radio_value(
-                            &mut self.temp_app_config.close_action,
-                            CloseAction::ExitApp,
-                            i18n.t("ui.app.close_action_exit"),
-                        );
+                        if ui.button(i18n.t("ui.button_copy_logs")).clicked() {
+                            let logs = self.log_buffer.lock().unwrap();
+                            let text = logs.iter().cloned().collect::<Vec<_>>().join("\n");
+                            ctx.copy_text(text);
+                            self.state.set_status(&i18n.t("status.logs_copied"));
+                        }
+                        if ui.button(i18n.t("ui.button_clear_logs")).clicked() {
+                            self.log_buffer.lock().unwrap().clear();
+                        }
                     });
+                    ui.separator();
+                    egui::ScrollArea::both()
+                        .auto_shrink([false, false])
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            let logs = self.log_buffer.lock().unwrap();
+                            if logs.is_empty() {
+                                ui.label(egui::RichText::new(i18n.t("ui.label_empty")).italics().weak());
+                            }
+                            for line in logs.iter() {
+                                ui.label(egui::RichText::new(line).monospace().small());
+                            }
+                        });
+                });
+            if !open {
+                self.show_log_viewer = false;
+            }
+        }
 
-                    ui.add_space(10.0);
-
-                    ui.checkbox(
-                        &mut self.temp_app_config.start_minimized,
-                        i18n.t("ui.app.checkbox_start_minimized"),
-                    );
-
-                    ui.add_space(10.0);
-
-                    ui.horizontal(|ui| {
-                        ui.label(i18n.t("ui.app.label_language"));
-                        let selected_label = i18n
-                            .available_languages()
-                            .iter()
-                            .find(|(code, _)| *code == self.temp_app_config.language.as_str())
-                            .map(|(_, name)| (*name).to_string())
-                            .unwrap_or_else(|| self.temp_app_config.language.clone());
+        // 快捷键诊断窗口
+        if self.show_hotkey_diagnostics {
+            let mut open = true;
+            let mut reregister = false;
+            egui::Window::new(i18n.t("ui.window_hotkey_diagnostics"))
+                .open(&mut open)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    if self.hotkey_manager.is_none() {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 165, 0),
+                            format!("⚠ {}", i18n.t("ui.error_hotkey_manager_init_failed")),
+                        );
+                    }
 
-                        egui::ComboBox::from_id_salt("language_select")
-                            .selected_text(selected_label)
-                            .show_ui(ui, |ui| {
-                                for (code, name) in i18n.available_languages() {
-                                    ui.selectable_value(
-                                        &mut self.temp_app_config.language,
-                                        code.to_string(),
-                                        format!("{} ({})", name, code),
-                                    );
-                                }
-                            });
-                    });
+                    let registered_label = |registered: bool| {
+                        if registered {
+                            i18n.t("ui.label_hotkey_registered")
+                        } else {
+                            i18n.t("ui.label_hotkey_not_registered")
+                        }
+                    };
 
-                    ui.add_space(10.0);
+                    egui::Grid::new("hotkey_diagnostics_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            let main_registered = self.current_hotkey.is_some()
+                                && self.current_hotkey == self.hotkey_config.to_global_hotkey();
+                            ui.label(i18n.t("ui.label_main_hotkey"));
+                            ui.code(self.hotkey_config.display());
+                            ui.label(registered_label(main_registered));
+                            ui.end_row();
+
+                            for (action, config) in &self.app_config.action_hotkeys {
+                                let expected = config.to_global_hotkey();
+                                let registered = self
+                                    .action_hotkeys
+                                    .iter()
+                                    .any(|(a, hotkey)| a == action && Some(*hotkey) == expected);
+                                ui.label(i18n.t(action.i18n_key()));
+                                ui.code(config.display());
+                                ui.label(registered_label(registered));
+                                ui.end_row();
+                            }
 
-                    ui.label(i18n.t("ui.app.group_typing_settings"));
-                    ui.group(|ui| {
-                        ui.horizontal(|ui| {
-                            ui.label(i18n.t("ui.app.label_base_delay_ms"));
-                            ui.add(egui::Slider::new(&mut self.temp_app_config.typing_delay, 0..=2000).text("ms"));
-                            
-                            // 计算并显示字每分钟
-                            let chars_per_minute = if self.temp_app_config.typing_delay > 0 {
-                                let avg_delay = self.temp_app_config.typing_delay as f64 
-                                    + (self.temp_app_config.typing_variance as f64 / 2.0);
-                                (60000.0 / avg_delay) as u32
-                            } else {
-                                9999 // 极速模式显示为 9999+
-                            };
-                            
-                            let speed_text = if self.temp_app_config.typing_delay == 0 {
-                                i18n.t("ui.app.typing_speed_infinite")
-                            } else {
-                                let cpm = chars_per_minute.to_string();
-                                i18n.tr("ui.app.typing_speed", &[("cpm", cpm.as_str())])
-                            };
-                            
-                            ui.label(egui::RichText::new(speed_text).weak());
+                            for macro_def in &self.app_config.macros {
+                                let Some(config) = &macro_def.hotkey else {
+                                    continue;
+                                };
+                                let expected = config.to_global_hotkey();
+                                let registered = self.macro_hotkeys.iter().any(|hotkey| Some(*hotkey) == expected);
+                                ui.label(i18n.tr("ui.label_macro_hotkey", &[("name", macro_def.name.as_str())]));
+                                ui.code(config.display());
+                                ui.label(registered_label(registered));
+                                ui.end_row();
+                            }
                         });
 
-                        ui.horizontal(|ui| {
-                            ui.label(i18n.t("ui.app.label_variance_ms"));
-                            ui.add(egui::Slider::new(&mut self.temp_app_config.typing_variance, 0..=1000).text("ms"));
-                        });
+                    ui.add_space(10.0);
+                    if ui.button(i18n.t("ui.button_reregister_hotkeys")).clicked() {
+                        reregister = true;
+                    }
+                    ui.label(egui::RichText::new(i18n.t("ui.hotkey_diagnostics_tip")).small().weak());
+                });
+            if reregister {
+                self.reregister_all_hotkeys();
+            }
+            if !open {
+                self.show_hotkey_diagnostics = false;
+            }
+        }
 
-                         ui.horizontal(|ui| {
-                            ui.label(i18n.t("ui.app.label_presets"));
-                             if ui.button(i18n.t("ui.app.preset_ultra")).clicked() {
-                                self.temp_app_config.typing_delay = 0;
-                                self.temp_app_config.typing_variance = 0;
-                            }
-                            if ui.button(i18n.t("ui.app.preset_fast")).clicked() {
-                                self.temp_app_config.typing_delay = 10;
-                                self.temp_app_config.typing_variance = 5;
-                            }
-                            if ui.button(i18n.t("ui.app.preset_normal")).clicked() {
-                                self.temp_app_config.typing_delay = 50;
-                                self.temp_app_config.typing_variance = 30;
-                            }
-                             if ui.button(i18n.t("ui.app.preset_slow")).clicked() {
-                                self.temp_app_config.typing_delay = 150;
-                                self.temp_app_config.typing_variance = 50;
+        // 运行状态概览窗口
+        if self.show_health_summary {
+            let mut open = true;
+            let report = self.health_report();
+            egui::Window::new(i18n.t("ui.window_health_summary"))
+                .open(&mut open)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    egui::Grid::new("health_summary_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (name, value, ok) in &report {
+                                ui.label(name);
+                                if *ok {
+                                    ui.label(value);
+                                } else {
+                                    ui.colored_label(egui::Color32::from_rgb(255, 165, 0), value);
+                                }
+                                ui.end_row();
                             }
                         });
 
+                    ui.add_space(10.0);
+                    if ui.button(i18n.t("ui.button_copy_diagnostics")).clicked() {
+                        let text = report
+                            .iter()
+                            .map(|(name, value, _)| format!("{}: {}", name, value))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        ctx.copy_text(text);
+                        self.state.set_status(&i18n.t("status.diagnostics_copied"));
+                    }
+                    // 系统中的自启注册与设置不一致时（例如被外部删除），允许按设置重新同步
+                    let autostart_drift =
+                        autostart::is_registered().is_ok_and(|registered| registered != self.app_config.auto_start);
+                    if autostart_drift
+                        && ui.button(i18n.t("ui.button_sync_autostart")).clicked()
+                        && self.apply_autostart(self.app_config.auto_start)
+                    {
+                        info!("{}", i18n.t("log.autostart_synced"));
+                        self.state.set_status(&i18n.t("status.autostart_synced"));
+                    }
+                    ui.label(egui::RichText::new(i18n.t("ui.health_summary_tip")).small().weak());
+                });
+            if !open {
+                self.show_health_summary = false;
+            }
+        }
 
-                        ui.label(egui::RichText::new(i18n.t("ui.app.typing_tip")).small().weak());
+        // 加密历史的解锁窗口
+        if self.show_history_unlock {
+            let mut unlock = false;
+            let mut skip = false;
+            egui::Window::new(i18n.t("ui.window_history_unlock"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(i18n.t("ui.label_history_unlock"));
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.history_unlock_passphrase).password(true),
+                    );
+                    if ui.memory(|m| m.focused().is_none()) {
+                        response.request_focus();
+                    }
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        unlock = true;
+                    }
+                    if let Some(err) = &self.history_unlock_error {
+                        ui.colored_label(egui::Color32::from_rgb(255, 165, 0), format!("⚠ {}", err));
+                    }
+                    ui.label(egui::RichText::new(i18n.t("ui.tip_history_unlock")).small().weak());
+                    ui.horizontal(|ui| {
+                        if ui.button(i18n.t("ui.button_unlock")).clicked() {
+                            unlock = true;
+                        }
+                        if ui.button(i18n.t("ui.button_skip")).clicked() {
+                            skip = true;
+                        }
                     });
+                });
+            if unlock {
+                let passphrase = std::mem::take(&mut self.history_unlock_passphrase);
+                match history::load_encrypted(&passphrase) {
+                    Ok((entries, key)) => {
+                        let count = entries.len().to_string();
+                        self.state.unlock_history(entries, key);
+                        info!("{}", i18n.tr("log.history_unlocked", &[("count", count.as_str())]));
+                        self.state.set_status(&i18n.tr("status.history_unlocked", &[("count", count.as_str())]));
+                        self.history_unlock_error = None;
+                        self.show_history_unlock = false;
+                    }
+                    Err(CryptoError::WrongPassphrase) => {
+                        warn!("{}", i18n.t("log.history_wrong_passphrase"));
+                        self.history_unlock_error = Some(i18n.t("ui.history_wrong_passphrase"));
+                    }
+                    Err(e) => {
+                        let err = e.to_string();
+                        error!("{}", i18n.tr("log.history_unlock_fail", &[("err", err.as_str())]));
+                        self.history_unlock_error = Some(err);
+                    }
+                }
+            } else if skip {
+                // 保持空的历史，且不保存，已加密的历史保留在磁盘上
+                self.history_unlock_passphrase.clear();
+                self.history_unlock_error = None;
+                self.show_history_unlock = false;
+                self.state.set_status(&i18n.t("status.history_locked"));
+            }
+        }
 
-                    ui.add_space(10.0);
-                    ui.label(i18n.t("ui.app.group_history_settings"));
-                    ui.group(|ui| {
-                        ui.checkbox(
-                            &mut self.temp_app_config.history_enabled,
-                            i18n.t("ui.app.checkbox_history_enabled"),
-                        );
-                        ui.horizontal(|ui| {
-                            ui.label(i18n.t("ui.app.label_history_max_items"));
-                            ui.add_enabled(
-                                self.temp_app_config.history_enabled,
-                                egui::Slider::new(&mut self.temp_app_config.history_max_items, 1..=100)
-                                    .text(i18n.t("ui.app.history_item_unit")),
-                            );
+        // 宏编辑窗口
+        if self.show_macro_editor {
+            let mut open = true;
+            let mut save = false;
+            let mut cancel = false;
+            let mut run = None;
+            egui::Window::new(i18n.t("ui.window_macros"))
+                .open(&mut open)
+                .default_size([480.0, 420.0])
+                .show(ctx, |ui| {
+                    egui::ScrollArea::vertical()
+                        .auto_shrink([false, true])
+                        .max_height(360.0)
+                        .show(ui, |ui| {
+                            if self.temp_macros.is_empty() {
+                                ui.label(egui::RichText::new(i18n.t("ui.label_no_macros")).italics().weak());
+                            }
+                            let mut remove_macro = None;
+                            for (index, macro_def) in self.temp_macros.iter_mut().enumerate() {
+                                ui.push_id(index, |ui| {
+                                    ui.group(|ui| {
+                                        ui.horizontal(|ui| {
+                                            ui.label(i18n.t("ui.label_macro_name"));
+                                            ui.add(egui::TextEdit::singleline(&mut macro_def.name).desired_width(160.0));
+                                            let can_run = !macro_def.steps.is_empty() && !self.state.is_typing();
+                                            if ui.add_enabled(can_run, egui::Button::new(i18n.t("ui.button_run_macro"))).clicked() {
+                                                run = Some(macro_def.clone());
+                                            }
+                                            if ui.button(i18n.t("ui.button_delete_macro")).clicked() {
+                                                remove_macro = Some(index);
+                                            }
+                                        });
+
+                                        let len = macro_def.steps.len();
+                                        let mut move_up = None;
+                                        let mut move_down = None;
+                                        let mut remove = None;
+                                        for (step_index, step) in macro_def.steps.iter_mut().enumerate() {
+                                            ui.horizontal(|ui| {
+                                                ui.label(format!("{}. {}", step_index + 1, i18n.t(step.i18n_key())));
+                                                match step {
+                                                    MacroStep::TypeText(text) => {
+                                                        ui.add(egui::TextEdit::singleline(text).desired_width(180.0));
+                                                    }
+                                                    MacroStep::Key(key) => {
+                                                        egui::ComboBox::from_id_salt(("macro_step_key", step_index))
+                                                            .selected_text(key.display())
+                                                            .show_ui(ui, |ui| {
+                                                                for option in SpecialKey::all() {
+                                                                    ui.selectable_value(key, option, option.display());
+                                                                }
+                                                            });
+                                                    }
+                                                    MacroStep::Delay(ms) => {
+                                                        ui.add(egui::DragValue::new(ms).range(0..=60_000).suffix(" ms"));
+                                                    }
+                                                }
+                                                if ui.add_enabled(step_index > 0, egui::Button::new("↑")).clicked() {
+                                                    move_up = Some(step_index);
+                                                }
+                                                if ui.add_enabled(step_index + 1 < len, egui::Button::new("↓")).clicked() {
+                                                    move_down = Some(step_index);
+                                                }
+                                                if ui.button("✕").clicked() {
+                                                    remove = Some(step_index);
+                                                }
+                                            });
+                                        }
+                                        if let Some(step_index) = move_up {
+                                            macro_def.steps.swap(step_index, step_index - 1);
+                                        }
+                                        if let Some(step_index) = move_down {
+                                            macro_def.steps.swap(step_index, step_index + 1);
+                                        }
+                                        if let Some(step_index) = remove {
+                                            macro_def.steps.remove(step_index);
+                                        }
+
+                                        ui.horizontal(|ui| {
+                                            ui.label(i18n.t("ui.label_add_macro_step"));
+                                            for template in MacroStep::templates() {
+                                                if ui.button(i18n.t(template.i18n_key())).clicked() {
+                                                    macro_def.steps.push(template);
+                                                }
+                                            }
+                                        });
+
+                                        let mut bound = macro_def.hotkey.is_some();
+                                        if ui.checkbox(&mut bound, i18n.t("ui.checkbox_macro_hotkey")).changed() {
+                                            macro_def.hotkey = bound.then(HotkeyConfig::default);
+                                        }
+                                        if let Some(config) = &mut macro_def.hotkey {
+                                            ui.indent("macro_hotkey", |ui| {
+                                                hotkey_editor(ui, config, &format!("macro_{}", index), &i18n);
+                                                ui.horizontal(|ui| {
+                                                    ui.label(i18n.t("ui.label_preview"));
+                                                    ui.code(config.display());
+                                                });
+                                            });
+                                        }
+                                    });
+                                });
+                            }
+                            if let Some(index) = remove_macro {
+                                self.temp_macros.remove(index);
+                            }
                         });
-                    });
-                    
-                    #[cfg(target_os = "windows")]
-                    {
-                        ui.add_space(5.0);
-                        ui.checkbox(
-                            &mut self.temp_app_config.show_console,
-                            i18n.t("ui.app.checkbox_show_console"),
+
+                    if ui.button(i18n.t("ui.button_add_macro")).clicked() {
+                        let name = i18n.tr("ui.macro_default_name", &[("n", (self.temp_macros.len() + 1).to_string().as_str())]);
+                        self.temp_macros.push(Macro::new(name));
+                    }
+                    ui.label(egui::RichText::new(i18n.t("ui.macros_tip")).small().weak());
+
+                    if let Some(err) = &self.macro_error {
+                        ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                    }
+                    if !self.app_config.hotkey_enabled && self.temp_macros.iter().any(|m| m.hotkey.is_some()) {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 165, 0),
+                            format!("⚠ {}", i18n.t("ui.warning_macro_hotkeys_disabled")),
                         );
-                        ui.label(egui::RichText::new(i18n.t("ui.app.label_restart_required")).small().weak());
                     }
 
-                    ui.add_space(10.0);
                     ui.separator();
-                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        let changed = self.temp_macros != self.app_config.macros;
+                        if ui.add_enabled(changed, egui::Button::new(i18n.t("ui.button_save"))).clicked() {
+                            save = true;
+                        }
+                        if ui.button(i18n.t("ui.button_cancel")).clicked() {
+                            cancel = true;
+                        }
+                    });
+                });
+            if let Some(macro_def) = run {
+                self.state.run_macro(&macro_def, true);
+            }
+            if save {
+                self.save_macros();
+                if self.macro_error.is_none() {
+                    self.show_macro_editor = false;
+                }
+            }
+            if cancel || !open {
+                self.show_macro_editor = false;
+                self.temp_macros = self.app_config.macros.clone();
+            }
+        }
 
+        // 寄存器窗口
+        if self.show_registers {
+            let mut open = true;
+            let mut type_register = None;
+            egui::Window::new(i18n.t("ui.window_registers"))
+                .open(&mut open)
+                .default_size([420.0, 360.0])
+                .show(ctx, |ui| {
                     ui.horizontal(|ui| {
-                        if ui.button(i18n.t("ui.button_save")).clicked() {
-                            #[cfg(target_os = "windows")]
-                            {
-                                let console_changed = self.app_config.show_console != self.temp_app_config.show_console;
-                                if console_changed {
-                                    if self.temp_app_config.show_console {
-                                        show_console_window();
-                                    } else {
-                                        hide_console_window();
-                                    }
+                        ui.label(i18n.t("ui.label_store_register"));
+                        egui::ComboBox::from_id_salt("register_target")
+                            .selected_text(self.register_target.to_string())
+                            .width(40.0)
+                            .show_ui(ui, |ui| {
+                                for name in 'a'..='z' {
+                                    ui.selectable_value(&mut self.register_target, name, name.to_string());
                                 }
-                            }
+                            });
+                        let text = self.state.get_clipboard_text();
+                        if ui
+                            .add_enabled(!text.is_empty(), egui::Button::new(i18n.t("ui.button_store_clipboard")))
+                            .clicked()
+                        {
+                            self.state.set_register(self.register_target, text);
+                        }
+                    });
+                    ui.separator();
 
-                            self.temp_app_config.history_max_items =
-                                self.temp_app_config.history_max_items.clamp(1, 100);
-                            
-                            self.app_config = self.temp_app_config.clone();
-                            // 更新 state 中的配置
-                            *self.state.typing_delay.lock().unwrap() = self.app_config.typing_delay;
-                            *self.state.typing_variance.lock().unwrap() = self.app_config.typing_variance;
-                            *self.state.typing_variance_enabled.lock().unwrap() = self.app_config.typing_variance_enabled;
-                            *self.state.history_enabled.lock().unwrap() = self.app_config.history_enabled;
-                            *self.state.history_max_items.lock().unwrap() = self.app_config.history_max_items;
-                            if self.app_config.history_enabled {
-                                self.state.trim_history();
-                            } else {
-                                self.state.clear_history();
-                            }
-                            self.i18n.set_language(&self.app_config.language);
-                            
-                            // 保存时包含当前的快捷键配置
-                            self.app_config.hotkey = self.hotkey_config.clone();
-                            if let Err(e) = self.app_config.save() {
-                                let err = e.to_string();
-                                error!(
-                                    "{}",
-                                    i18n.tr("log.save_app_config_fail", &[("err", err.as_str())])
-                                );
-                            } else {
-                                self.state.set_status(&i18n.t("status.app_settings_saved"));
-                            }
-                            self.show_app_settings = false;
+                    let registers = self.state.registers.lock().unwrap().clone();
+                    egui::ScrollArea::vertical().max_height(280.0).show(ui, |ui| {
+                        if registers.is_empty() {
+                            ui.label(egui::RichText::new(i18n.t("ui.label_no_registers")).italics().weak());
                         }
-                        if ui.button(i18n.t("ui.button_cancel")).clicked() {
-                            self.show_app_settings = false;
+                        for name in 'a'..='z' {
+                            let Some(text) = registers.get(&name) else {
+                                continue;
+                            };
+                            ui.horizontal(|ui| {
+                                ui.code(name.to_string());
+                                let can_type = self.state.is_enabled() && !self.state.is_typing();
+                                if ui
+                                    .add_enabled(can_type, egui::Button::new("⌨"))
+                                    .on_hover_text(i18n.t("ui.button_type_register"))
+                                    .clicked()
+                                {
+                                    type_register = Some(name);
+                                }
+                                if ui.button("✕").clicked() {
+                                    self.state.set_register(name, String::new());
+                                }
+                                ui.label(truncate_text(text, 60));
+                            });
                         }
                     });
+                    ui.label(egui::RichText::new(i18n.t("ui.tip_registers")).small().weak());
                 });
+            if let Some(name) = type_register {
+                self.state.type_register(name, true);
+            }
+            if !open {
+                self.show_registers = false;
+            }
         }
 
         // 检查关闭请求
-        if ctx.input(|i| i.viewport().close_requested()) {
-            if !self.state.request_exit.load(Ordering::SeqCst) {
-                match self.app_config.close_action {
-                    CloseAction::MinimizeToTray => {
-                        // 取消关闭，改为隐藏
-                        ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
-                        self.state.window_visible.store(false, Ordering::SeqCst);
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
-                        info!("{}", i18n.t("log.window_minimized_to_tray"));
-                    }
-                    CloseAction::ExitApp => {
-                        // 允许关闭
-                        info!("{}", i18n.t("log.app_exit"));
-                    }
-                }
-            }
-        }
+        self.handle_close_request(ctx);
     }
 }
 
-/// 设置中文字体
-fn setup_fonts(ctx: &egui::Context) {
-    let mut fonts = egui::FontDefinitions::default();
-
-    // 在 Windows 上使用微软雅黑字体
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(font_data) = std::fs::read("C:\\Windows\\Fonts\\msyh.ttc") {
-            fonts.font_data.insert(
-                "msyh".to_owned(),
-                std::sync::Arc::new(egui::FontData::from_owned(font_data)),
-            );
+/// 按平均按键延迟（基础延迟加一半的随机波动）估算每分钟输入的字符数，延迟为 0 时返回 None
+fn estimated_cpm(delay: u64, variance: u64) -> Option<u32> {
+    if delay == 0 {
+        return None;
+    }
+    let avg_delay = delay as f64 + variance as f64 / 2.0;
+    Some((60000.0 / avg_delay) as u32)
+}
 
-            fonts
-                .families
-                .entry(egui::FontFamily::Proportional)
-                .or_default()
-                .insert(0, "msyh".to_owned());
+/// 重复快捷键的提示文本
+fn hotkey_conflict_message(i18n: &I18n, conflict: HotkeyConflict) -> String {
+    let name = |action: Option<HotkeyAction>| match action {
+        Some(action) => i18n.t(action.i18n_key()),
+        None => i18n.t("ui.label_main_hotkey"),
+    };
+    let first = name(conflict.first);
+    let second = name(conflict.second);
+    i18n.tr(
+        "ui.error_hotkey_duplicate",
+        &[("first", first.as_str()), ("second", second.as_str())],
+    )
+}
 
-            fonts
-                .families
-                .entry(egui::FontFamily::Monospace)
-                .or_default()
-                .insert(0, "msyh".to_owned());
-        }
+/// 快捷键编辑控件（修饰键 + 按键）
+fn hotkey_editor(ui: &mut egui::Ui, config: &mut HotkeyConfig, id_salt: &str, i18n: &I18n) {
+    ui.label(i18n.t("ui.label_modifiers"));
+
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut config.ctrl, "Ctrl");
+        ui.checkbox(&mut config.shift, "Shift");
+        ui.checkbox(&mut config.alt, "Alt");
+        #[cfg(target_os = "macos")]
+        ui.checkbox(&mut config.meta, "Cmd");
+        #[cfg(not(target_os = "macos"))]
+        ui.checkbox(&mut config.meta, "Win");
+    });
+    if config.meta {
+        ui.label(egui::RichText::new(i18n.t(META_KEY_TIP)).small().weak());
     }
 
-    // 在 macOS 上使用苹方字体
-    #[cfg(target_os = "macos")]
-    {
-        if let Ok(font_data) = std::fs::read("/System/Library/Fonts/PingFang.ttc") {
-            fonts.font_data.insert(
-                "pingfang".to_owned(),
-                std::sync::Arc::new(egui::FontData::from_owned(font_data)),
-            );
+    ui.add_space(10.0);
 
-            fonts
-                .families
-                .entry(egui::FontFamily::Proportional)
-                .or_default()
-                .insert(0, "pingfang".to_owned());
-        }
-    }
+    ui.horizontal(|ui| {
+        ui.label(i18n.t("ui.label_keys"));
 
-    // 在 Linux 上使用 Noto Sans CJK
-    #[cfg(target_os = "linux")]
-    {
-        let font_paths = [
-            "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
-            "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
-            "/usr/share/fonts/truetype/noto/NotoSansCJK-Regular.ttc",
-        ];
-
-        for path in &font_paths {
-            if let Ok(font_data) = std::fs::read(path) {
-                fonts.font_data.insert(
-                    "noto".to_owned(),
-                    std::sync::Arc::new(egui::FontData::from_owned(font_data)),
+        // 下拉框打开期间的搜索文本，保存在 egui 的临时数据中
+        let filter_id = ui.make_persistent_id(format!("hotkey_key_filter_{}", id_salt));
+        let mut filter: String = ui.data_mut(|d| d.get_temp(filter_id).unwrap_or_default());
+
+        let popup = egui::ComboBox::from_id_salt(format!("hotkey_key_{}", id_salt))
+            .selected_text(config.key.display())
+            .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+            .show_ui(ui, |ui| {
+                let search = ui.add(
+                    egui::TextEdit::singleline(&mut filter).hint_text(i18n.t("ui.hint_search_key")),
                 );
+                if ui.memory(|m| m.focused().is_none()) {
+                    search.request_focus();
+                }
+                ui.separator();
 
-                fonts
-                    .families
-                    .entry(egui::FontFamily::Proportional)
-                    .or_default()
-                    .insert(0, "noto".to_owned());
-                break;
-            }
+                let needle = filter.trim().to_lowercase();
+                let matches: Vec<KeyCode> = KeyCode::all()
+                    .into_iter()
+                    .filter(|key| key.display().to_lowercase().contains(&needle))
+                    .collect();
+
+                // 回车选中第一个匹配项
+                let enter = search.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let mut chosen = if enter { matches.first().cloned() } else { None };
+
+                if matches.is_empty() {
+                    ui.label(egui::RichText::new(i18n.t("ui.label_no_matching_key")).italics().weak());
+                }
+                for key in matches {
+                    if ui.selectable_label(config.key == key, key.display()).clicked() {
+                        chosen = Some(key);
+                    }
+                }
+
+                if let Some(key) = chosen {
+                    config.key = key;
+                    ui.memory_mut(|m| m.close_popup());
+                }
+            });
+
+        // 下拉框关闭后清空搜索文本
+        if popup.inner.is_none() {
+            filter.clear();
         }
+        ui.data_mut(|d| d.insert_temp(filter_id, filter));
+    });
+}
+
+/// 将窗口移动到当前显示器的指定角落
+///
+/// 显示器或窗口尺寸尚不可用时返回 false。
+fn dock_window(ctx: &egui::Context, corner: DockCorner) -> bool {
+    let (monitor_size, outer_rect) = ctx.input(|i| (i.viewport().monitor_size, i.viewport().outer_rect));
+    let (Some(monitor), Some(outer)) = (monitor_size, outer_rect) else {
+        return false;
+    };
+
+    let left = DOCK_MARGIN;
+    let right = (monitor.x - outer.width() - DOCK_MARGIN).max(0.0);
+    let top = DOCK_MARGIN;
+    let bottom = (monitor.y - outer.height() - DOCK_BOTTOM_MARGIN).max(0.0);
+    let position = match corner {
+        DockCorner::TopLeft => egui::pos2(left, top),
+        DockCorner::TopRight => egui::pos2(right, top),
+        DockCorner::BottomLeft => egui::pos2(left, bottom),
+        DockCorner::BottomRight => egui::pos2(right, bottom),
+    };
+    ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position));
+    true
+}
+
+/// 按不透明度设置面板背景色，弹出的窗口保持不透明以便阅读
+fn apply_window_opacity(ctx: &egui::Context, opacity: f32) {
+    ctx.all_styles_mut(|style| {
+        let base = if style.visuals.dark_mode {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        };
+        style.visuals.panel_fill = base.panel_fill.gamma_multiply(opacity);
+    });
+}
+
+/// 设置中文字体，返回是否找到了可用的字体
+///
+/// 依次尝试配置中指定的字体、系统常见字体路径，最后使用编译时嵌入的备用字体
+/// （`embedded-cjk-font` 特性）。
+fn setup_fonts(ctx: &egui::Context, custom_path: &str) -> bool {
+    let mut fonts = egui::FontDefinitions::default();
+
+    let font_data = (!custom_path.is_empty())
+        .then_some(custom_path)
+        .into_iter()
+        .chain(CJK_FONT_PATHS.iter().copied())
+        .find_map(|path| std::fs::read(path).ok())
+        .or_else(embedded_cjk_font);
+    let found = font_data.is_some();
+
+    if let Some(font_data) = font_data {
+        fonts.font_data.insert(
+            "cjk".to_owned(),
+            std::sync::Arc::new(egui::FontData::from_owned(font_data)),
+        );
+
+        fonts
+            .families
+            .entry(egui::FontFamily::Proportional)
+            .or_default()
+            .insert(0, "cjk".to_owned());
+
+        // 等宽字体只作为后备，英文仍使用默认的等宽字体
+        fonts
+            .families
+            .entry(egui::FontFamily::Monospace)
+            .or_default()
+            .push("cjk".to_owned());
     }
 
     ctx.set_fonts(fonts);
+    found
+}
+
+/// 编译时嵌入的备用中文字体，字体文件由环境变量 `COPY_TYPE_CJK_FONT` 指定
+#[cfg(feature = "embedded-cjk-font")]
+fn embedded_cjk_font() -> Option<Vec<u8>> {
+    Some(include_bytes!(env!("COPY_TYPE_CJK_FONT")).to_vec())
+}
+
+#[cfg(not(feature = "embedded-cjk-font"))]
+fn embedded_cjk_font() -> Option<Vec<u8>> {
+    None
 }
 
 /// Windows: 显示控制台窗口
@@ -1526,48 +6711,69 @@ fn hide_console_window() {
     }
 }
 
-/// 创建系统托盘图标
-fn create_tray_context(i18n: &I18n, icon: tray_icon::Icon) -> Option<TrayContext> {
-    // 创建托盘菜单
-    let menu = Menu::new();
-
-    let show_text = i18n.t("tray.menu_show");
-    let toggle_text = i18n.t("tray.menu_toggle");
-    let exit_text = i18n.t("tray.menu_exit");
-
-    let show_item = MenuItem::with_id(MENU_SHOW, &show_text, true, None);
-    let toggle_item = MenuItem::with_id(MENU_TOGGLE, &toggle_text, true, None);
-    let separator = PredefinedMenuItem::separator();
-    let exit_item = MenuItem::with_id(MENU_EXIT, &exit_text, true, None);
-
-    if let Err(e) = menu.append(&show_item) {
-        let err = e.to_string();
-        error!("{}", i18n.tr("tray.log.add_show_fail", &[("err", err.as_str())]));
-    }
-    if let Err(e) = menu.append(&toggle_item) {
-        let err = e.to_string();
-        error!(
-            "{}",
-            i18n.tr("tray.log.add_toggle_fail", &[("err", err.as_str())])
-        );
+/// 创建“按速度输入”子菜单
+fn speed_submenu(i18n: &I18n) -> Submenu {
+    let speed_menu = Submenu::new(i18n.t("tray.menu_type_with_speed"), true);
+    for preset in SPEED_PRESETS.iter() {
+        let text = format!("{} ({} ms)", i18n.t(&preset.i18n_key()), preset.delay);
+        let item = MenuItem::with_id(format!("{}{}", MENU_SPEED_PREFIX, preset.name), text, true, None);
+        if let Err(e) = speed_menu.append(&item) {
+            let err = e.to_string();
+            error!("{}", i18n.tr("tray.log.add_speed_fail", &[("err", err.as_str())]));
+        }
     }
-    if let Err(e) = menu.append(&separator) {
-        let err = e.to_string();
-        error!("{}", i18n.tr("tray.log.add_sep_fail", &[("err", err.as_str())]));
+    speed_menu
+}
+
+/// 创建“小睡”子菜单
+fn snooze_submenu(i18n: &I18n) -> Submenu {
+    let snooze_menu = Submenu::new(i18n.t("tray.menu_snooze"), true);
+    for minutes in SNOOZE_MINUTES.iter() {
+        let minutes_str = minutes.to_string();
+        let text = i18n.tr("ui.snooze_minutes", &[("minutes", minutes_str.as_str())]);
+        let item = MenuItem::with_id(format!("{}{}", MENU_SNOOZE_PREFIX, minutes), text, true, None);
+        if let Err(e) = snooze_menu.append(&item) {
+            let err = e.to_string();
+            error!("{}", i18n.tr("tray.log.add_snooze_fail", &[("err", err.as_str())]));
+        }
     }
-    if let Err(e) = menu.append(&exit_item) {
-        let err = e.to_string();
-        error!(
-            "{}",
-            i18n.tr("tray.log.add_exit_fail", &[("err", err.as_str())])
-        );
+    snooze_menu
+}
+
+/// 按配置的项目和顺序构建托盘菜单，同时返回需要保持存活的菜单项
+fn build_tray_menu(i18n: &I18n, entries: &[TrayMenuEntry]) -> (Menu, Vec<Box<dyn IsMenuItem>>) {
+    let menu = Menu::new();
+    let mut items: Vec<Box<dyn IsMenuItem>> = Vec::new();
+    for entry in entries {
+        let text = i18n.t(entry.i18n_key());
+        let item: Box<dyn IsMenuItem> = match entry {
+            TrayMenuEntry::Show => Box::new(MenuItem::with_id(MENU_SHOW, &text, true, None)),
+            TrayMenuEntry::Toggle => Box::new(MenuItem::with_id(MENU_TOGGLE, &text, true, None)),
+            TrayMenuEntry::TypeNow => Box::new(MenuItem::with_id(MENU_TYPE_NOW, &text, true, None)),
+            TrayMenuEntry::History => Box::new(MenuItem::with_id(MENU_HISTORY, &text, true, None)),
+            TrayMenuEntry::Snooze => Box::new(snooze_submenu(i18n)),
+            TrayMenuEntry::TypeWithSpeed => Box::new(speed_submenu(i18n)),
+            TrayMenuEntry::Separator => Box::new(PredefinedMenuItem::separator()),
+            TrayMenuEntry::Exit => Box::new(MenuItem::with_id(MENU_EXIT, &text, true, None)),
+        };
+        if let Err(e) = menu.append(item.as_ref()) {
+            let err = e.to_string();
+            error!(
+                "{}",
+                i18n.tr("tray.log.add_item_fail", &[("item", text.as_str()), ("err", err.as_str())])
+            );
+        }
+        items.push(item);
     }
-    
-    info!(
-        "{}",
-        i18n.tr("tray.log.menu_created", &[("count", "3")])
-    );
 
+    let count = items.len().to_string();
+    info!("{}", i18n.tr("tray.log.menu_created", &[("count", count.as_str())]));
+    (menu, items)
+}
+
+/// 创建系统托盘图标
+fn create_tray_context(i18n: &I18n, icon: tray_icon::Icon, entries: &[TrayMenuEntry]) -> Option<TrayContext> {
+    let (menu, items) = build_tray_menu(i18n, entries);
     let tooltip = i18n.t("tray.tooltip");
 
     match TrayIconBuilder::new()
@@ -1579,13 +6785,7 @@ fn create_tray_context(i18n: &I18n, icon: tray_icon::Icon) -> Option<TrayContext
         Ok(tray) => {
             info!("{}", i18n.t("tray.log.created"));
             // 将所有相关对象包含在上下文中返回
-            Some(TrayContext {
-                tray,
-                show_item,
-                toggle_item,
-                exit_item,
-                separator
-            })
+            Some(TrayContext { tray, items })
         }
         Err(e) => {
             let err = e.to_string();
@@ -1611,6 +6811,70 @@ fn get_window_hwnd(_cc: &eframe::CreationContext<'_>) -> Option<isize> {
     None
 }
 
+/// 获取当前前台窗口句柄
+#[cfg(target_os = "windows")]
+fn foreground_window() -> Option<isize> {
+    use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.is_invalid() {
+        None
+    } else {
+        Some(hwnd.0 as isize)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn foreground_window() -> Option<isize> {
+    None
+}
+
+/// 获取鼠标在屏幕上的位置（物理像素）
+#[cfg(target_os = "windows")]
+fn cursor_position() -> Option<(i32, i32)> {
+    use windows::Win32::Foundation::POINT;
+    use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+    let mut point = POINT::default();
+    unsafe { GetCursorPos(&mut point) }.ok()?;
+    Some((point.x, point.y))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn cursor_position() -> Option<(i32, i32)> {
+    None
+}
+
+/// 将指定窗口切换到前台，窗口已不存在或切换失败时返回 false
+#[cfg(target_os = "windows")]
+fn focus_window(hwnd: isize) -> bool {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{IsWindow, SetForegroundWindow};
+
+    unsafe {
+        let hwnd = HWND(hwnd as *mut std::ffi::c_void);
+        IsWindow(hwnd).as_bool() && SetForegroundWindow(hwnd).as_bool()
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn focus_window(_hwnd: isize) -> bool {
+    false
+}
+
+/// 用系统文件管理器打开目录
+fn open_in_file_manager(path: &std::path::Path) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let program = "explorer";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let program = "xdg-open";
+
+    std::process::Command::new(program).arg(path).spawn()?;
+    Ok(())
+}
+
 fn show_main_window(ctx: &egui::Context, window_hwnd: Option<isize>) {
     #[cfg(target_os = "windows")]
     {
@@ -1689,6 +6953,77 @@ fn load_icon() -> (Option<tray_icon::Icon>, Option<egui::IconData>) {
 }
 
 
+/// 处理分隔符中的 `\n`、`\t`、`\\` 转义
+fn unescape_separator(separator: &str) -> String {
+    let mut result = String::new();
+    let mut chars = separator.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// 编辑从 0 点起的分钟数，按时、分分别输入
+fn time_of_day_editor(ui: &mut egui::Ui, minutes: &mut u32) {
+    let mut hour = *minutes / 60;
+    let mut minute = *minutes % 60;
+    let hour_changed = ui.add(egui::DragValue::new(&mut hour).range(0..=23)).changed();
+    ui.label(":");
+    let minute_changed = ui
+        .add(egui::DragValue::new(&mut minute).range(0..=59).custom_formatter(|v, _| format!("{:02}", v)))
+        .changed();
+    if hour_changed || minute_changed {
+        *minutes = (hour * 60 + minute).min(MINUTES_PER_DAY - 1);
+    }
+}
+
+/// 打开剪贴板，失败时按退避时间重试
+///
+/// 启动时剪贴板可能暂时被其他程序占用，首次打开容易失败。
+fn open_clipboard(state: &SharedState, attempts: u32, mut backoff: u64) -> Result<Clipboard, arboard::Error> {
+    let mut attempt = 1;
+    loop {
+        match Clipboard::new() {
+            Ok(clipboard) => return Ok(clipboard),
+            Err(e) if attempt < attempts => {
+                let err = e.to_string();
+                let attempt_str = attempt.to_string();
+                let attempts_str = attempts.to_string();
+                let backoff_str = backoff.to_string();
+                warn!(
+                    "{}",
+                    state.tr(
+                        "log.clipboard_init_retry",
+                        &[
+                            ("attempt", attempt_str.as_str()),
+                            ("max", attempts_str.as_str()),
+                            ("err", err.as_str()),
+                            ("delay", backoff_str.as_str())
+                        ]
+                    )
+                );
+                thread::sleep(Duration::from_millis(backoff));
+                backoff = backoff.saturating_mul(2);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 /// 截断文本用于日志显示
 fn truncate_text(text: &str, max_len: usize) -> String {
     if text.len() <= max_len {
@@ -1708,15 +7043,9 @@ fn truncate_text(text: &str, max_len: usize) -> String {
     }
 }
 
-fn format_history_timestamp() -> String {
-    Local::now().format("%H:%M:%S").to_string()
-}
-
 fn main() -> eframe::Result<()> {
-    // 初始化日志
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
-        .format_timestamp_secs()
-        .init();
+    // 初始化日志（同时保存到内存供日志窗口查看）
+    let log_buffer = log_buffer::init();
 
     info!("=================================");
     let startup_config = AppConfig::load();
@@ -1734,12 +7063,28 @@ fn main() -> eframe::Result<()> {
         );
     }
 
+    // 以 --rpc 启动时不显示界面，通过标准输入输出接收命令
+    if std::env::args().skip(1).any(|arg| arg == "--rpc") {
+        rpc::run(&startup_config, startup_i18n);
+        return Ok(());
+    }
+
     // 加载图标
     let (tray_icon, window_icon) = load_icon();
 
-    let mut viewport = egui::ViewportBuilder::default()
-        .with_inner_size([400.0, 500.0])
-        .with_min_inner_size([350.0, 400.0]);
+    let mut viewport = if startup_config.mini_mode {
+        egui::ViewportBuilder::default()
+            .with_inner_size(MINI_WINDOW_SIZE)
+            .with_min_inner_size(MINI_WINDOW_SIZE)
+            .with_decorations(false)
+            .with_always_on_top()
+    } else {
+        egui::ViewportBuilder::default()
+            .with_inner_size(WINDOW_SIZE)
+            .with_min_inner_size(WINDOW_MIN_SIZE)
+    };
+    // 透明视口，窗口不透明度由面板背景色控制
+    viewport = viewport.with_transparent(true);
     if let Some(window_icon) = window_icon {
         viewport = viewport.with_icon(window_icon);
     }
@@ -1752,6 +7097,145 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "Copy&Type",
         options,
-        Box::new(|cc| Ok(Box::new(CopyTypeApp::new(cc, tray_icon)))),
+        Box::new(|cc| Ok(Box::new(CopyTypeApp::new(cc, tray_icon, log_buffer)))),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyboard::{RecordedCall, RecordingBackend};
+    use std::cell::{Cell, RefCell};
+
+    /// 每个字符后的固定延迟
+    const CHAR_DELAY: u64 = 10;
+
+    /// 不阻塞的输入状态，记录休眠和粘贴
+    #[derive(Default)]
+    struct TestHost {
+        sleeps: RefCell<Vec<u64>>,
+        pastes: RefCell<Vec<String>>,
+        chars_done: Cell<usize>,
+        pauses: Cell<usize>,
+    }
+
+    impl TypingHost for TestHost {
+        fn wait_if_paused(&self) -> bool {
+            true
+        }
+
+        fn sleep_with_pause(&self, ms: u64) -> bool {
+            self.sleeps.borrow_mut().push(ms);
+            true
+        }
+
+        fn paused_duration(&self) -> Duration {
+            Duration::ZERO
+        }
+
+        fn char_done(&self) {
+            self.chars_done.set(self.chars_done.get() + 1);
+        }
+
+        fn before_paste(&self, text: &str) {
+            self.pastes.borrow_mut().push(text.to_string());
+        }
+
+        fn pause_at_marker(&self) {
+            self.pauses.set(self.pauses.get() + 1);
+        }
+
+        fn tr(&self, key: &str, _args: &[(&str, &str)]) -> String {
+            key.to_string()
+        }
+    }
+
+    fn options(emoji_mode: EmojiMode) -> TypingOptions {
+        TypingOptions {
+            column_paste: false,
+            line_delay: 0,
+            split_long_lines_at: None,
+            emoji_mode,
+            progress_log_every: 100,
+            progress_log_interval: Duration::from_secs(1),
+            max_duration: None,
+        }
+    }
+
+    /// 输入文本，返回后端收到的操作和输入状态
+    fn run(text: &str, emoji_mode: EmojiMode) -> (Vec<RecordedCall>, TestHost) {
+        let mut backend = RecordingBackend::default();
+        let host = TestHost::default();
+        let tokens = tokenize(text);
+        let mut progress = TypingProgress::default();
+        let outcome = type_tokens(
+            &mut backend,
+            &host,
+            &tokens,
+            &options(emoji_mode),
+            &mut |_| CHAR_DELAY,
+            &mut progress,
+        )
+        .unwrap();
+        assert!(matches!(outcome, TypingOutcome::Completed));
+        assert_eq!(progress.token, tokens.len());
+        (backend.merged_calls(), host)
+    }
+
+    #[test]
+    fn plain_text_is_typed_char_by_char() {
+        let (calls, host) = run("hi 你好", EmojiMode::Verbatim);
+        assert_eq!(calls, vec![RecordedCall::Text("hi 你好".to_string())]);
+        assert_eq!(host.chars_done.get(), 5);
+        assert_eq!(*host.sleeps.borrow(), vec![CHAR_DELAY; 5]);
+    }
+
+    #[test]
+    fn key_tokens_press_keys() {
+        let (calls, _) = run("user<<TAB>>pass<<ENTER>>", EmojiMode::Verbatim);
+        assert_eq!(
+            calls,
+            vec![
+                RecordedCall::Text("user".to_string()),
+                RecordedCall::Key(Key::Tab),
+                RecordedCall::Text("pass".to_string()),
+                RecordedCall::Key(Key::Return),
+            ]
+        );
+    }
+
+    #[test]
+    fn delay_token_sleeps_without_typing() {
+        let (calls, host) = run("a<<DELAY:250>>b", EmojiMode::Verbatim);
+        assert_eq!(calls, vec![RecordedCall::Text("ab".to_string())]);
+        assert_eq!(*host.sleeps.borrow(), vec![CHAR_DELAY, 250, CHAR_DELAY]);
+    }
+
+    #[test]
+    fn pause_token_pauses_once() {
+        let (calls, host) = run("a<<PAUSE>>b", EmojiMode::Verbatim);
+        assert_eq!(calls, vec![RecordedCall::Text("ab".to_string())]);
+        assert_eq!(host.pauses.get(), 1);
+    }
+
+    #[test]
+    fn emoji_is_pasted_in_paste_fallback_mode() {
+        let (calls, host) = run("ok 👍 好", EmojiMode::PasteFallback);
+        assert_eq!(
+            calls,
+            vec![
+                RecordedCall::Text("ok ".to_string()),
+                RecordedCall::Paste("👍".to_string()),
+                RecordedCall::Text(" 好".to_string()),
+            ]
+        );
+        assert_eq!(*host.pastes.borrow(), vec!["👍".to_string()]);
+    }
+
+    #[test]
+    fn emoji_is_skipped_in_skip_mode() {
+        let (calls, host) = run("ok 👍", EmojiMode::Skip);
+        assert_eq!(calls, vec![RecordedCall::Text("ok ".to_string())]);
+        assert_eq!(host.chars_done.get(), 4);
+    }
+}
@@ -0,0 +1,49 @@
+//! 修饰键状态模块
+//!
+//! 通过快捷键触发输入时，用户可能还按着修饰键，此时发送的字符会变成组合键。
+//! Windows 通过 GetAsyncKeyState 轮询修饰键，全部松开后立即开始输入；
+//! 其他平台无法查询按键状态，等待固定时间。
+
+use std::thread;
+use std::time::Duration;
+
+/// 无法查询按键状态时等待的固定时间
+#[cfg(not(target_os = "windows"))]
+const FALLBACK_DELAY: Duration = Duration::from_millis(250);
+
+/// 等待 Ctrl、Shift、Alt、Win 全部松开，最多等待 `timeout`，返回是否已全部松开
+#[cfg(target_os = "windows")]
+pub fn wait_for_release(timeout: Duration) -> bool {
+    use std::time::Instant;
+
+    /// 轮询按键状态的间隔
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    let started = Instant::now();
+    while any_pressed() {
+        if started.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    true
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn wait_for_release(timeout: Duration) -> bool {
+    thread::sleep(FALLBACK_DELAY.min(timeout));
+    true
+}
+
+/// 是否有修饰键处于按下状态
+#[cfg(target_os = "windows")]
+fn any_pressed() -> bool {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        GetAsyncKeyState, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT,
+    };
+
+    [VK_CONTROL, VK_SHIFT, VK_MENU, VK_LWIN, VK_RWIN]
+        .iter()
+        // 最高位表示按键当前处于按下状态
+        .any(|key| unsafe { GetAsyncKeyState(key.0 as i32) } < 0)
+}
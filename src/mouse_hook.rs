@@ -0,0 +1,188 @@
+//! 鼠标按键触发模块
+//!
+//! 通过低级鼠标钩子 (WH_MOUSE_LL) 监听鼠标中键或侧键，按下绑定的按键时执行与主快捷键相同的操作。
+//! 另外提供等待鼠标单击的功能，用于先点击定位再开始输入。
+//! 目前仅 Windows 支持。
+
+use crate::hotkey_config::MouseTrigger;
+use std::error::Error;
+use std::time::Duration;
+
+/// 等待鼠标单击的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub enum ClickWait {
+    /// 已单击（按下并松开）
+    Clicked,
+    /// 按下 Esc 或输入被取消
+    Cancelled,
+    /// 超时未单击
+    TimedOut,
+    /// 当前平台无法检测鼠标单击
+    Unsupported,
+}
+
+/// 等待一次鼠标主按键单击，最多等待 `timeout`；期间 `cancelled` 返回 true 或按下 Esc 时放弃
+///
+/// 开始等待时主按键已按下（例如刚点击了本程序的按钮），会先等它松开，不算作单击。
+#[cfg(target_os = "windows")]
+pub fn wait_for_click(timeout: Duration, cancelled: impl Fn() -> bool) -> ClickWait {
+    use std::thread;
+    use std::time::Instant;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, VK_ESCAPE, VK_LBUTTON, VK_RBUTTON};
+    use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_SWAPBUTTON};
+
+    /// 轮询按键状态的间隔
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    // GetAsyncKeyState 按物理按键报告，左右键互换时主按键是右键
+    let primary = if unsafe { GetSystemMetrics(SM_SWAPBUTTON) } != 0 {
+        VK_RBUTTON
+    } else {
+        VK_LBUTTON
+    };
+    // 最高位表示按键当前处于按下状态
+    let is_down = |key: windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY| unsafe {
+        GetAsyncKeyState(key.0 as i32) < 0
+    };
+
+    let started = Instant::now();
+    let mut armed = !is_down(primary);
+    let mut pressed = false;
+    loop {
+        if cancelled() || is_down(VK_ESCAPE) {
+            return ClickWait::Cancelled;
+        }
+        if started.elapsed() >= timeout {
+            return ClickWait::TimedOut;
+        }
+        let down = is_down(primary);
+        if !armed {
+            armed = !down;
+        } else if down {
+            pressed = true;
+        } else if pressed {
+            return ClickWait::Clicked;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn wait_for_click(_timeout: Duration, _cancelled: impl Fn() -> bool) -> ClickWait {
+    ClickWait::Unsupported
+}
+
+/// 设置触发输入的鼠标按键，首次绑定时安装钩子；为 None 时停止响应
+///
+/// `on_trigger` 只在首次安装钩子时保存，之后的调用只更新绑定的按键。
+#[cfg(target_os = "windows")]
+pub fn set_trigger(
+    trigger: Option<MouseTrigger>,
+    on_trigger: impl Fn() + Send + Sync + 'static,
+) -> Result<(), Box<dyn Error>> {
+    platform::set_trigger(trigger, Box::new(on_trigger))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn set_trigger(
+    trigger: Option<MouseTrigger>,
+    _on_trigger: impl Fn() + Send + Sync + 'static,
+) -> Result<(), Box<dyn Error>> {
+    if trigger.is_some() {
+        return Err("mouse button triggers are only supported on Windows".into());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use crate::hotkey_config::MouseTrigger;
+    use std::error::Error;
+    use std::sync::atomic::{AtomicU8, Ordering};
+    use std::sync::{mpsc, Mutex, OnceLock};
+    use std::thread;
+    use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, GetMessageW, SetWindowsHookExW, HC_ACTION, HHOOK, MSG, MSLLHOOKSTRUCT,
+        WH_MOUSE_LL, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_XBUTTONDOWN, WM_XBUTTONUP, XBUTTON1,
+        XBUTTON2,
+    };
+
+    type Callback = Box<dyn Fn() + Send + Sync>;
+
+    /// 当前绑定的按键，0 表示未绑定
+    static TRIGGER: AtomicU8 = AtomicU8::new(0);
+    /// 按下绑定按键时执行的操作
+    static CALLBACK: OnceLock<Callback> = OnceLock::new();
+    /// 钩子是否已经安装
+    static INSTALLED: Mutex<bool> = Mutex::new(false);
+
+    fn encode(trigger: Option<MouseTrigger>) -> u8 {
+        match trigger {
+            None => 0,
+            Some(MouseTrigger::Middle) => 1,
+            Some(MouseTrigger::X1) => 2,
+            Some(MouseTrigger::X2) => 3,
+        }
+    }
+
+    pub fn set_trigger(trigger: Option<MouseTrigger>, on_trigger: Callback) -> Result<(), Box<dyn Error>> {
+        TRIGGER.store(encode(trigger), Ordering::SeqCst);
+        let mut installed = INSTALLED.lock().unwrap();
+        if trigger.is_none() || *installed {
+            return Ok(());
+        }
+
+        let _ = CALLBACK.set(on_trigger);
+        // 低级钩子需要在安装它的线程上运行消息循环
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || unsafe {
+            match SetWindowsHookExW(WH_MOUSE_LL, Some(hook_proc), HINSTANCE::default(), 0) {
+                Ok(_) => {
+                    let _ = tx.send(Ok(()));
+                    let mut msg = MSG::default();
+                    while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {}
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(e));
+                }
+            }
+        });
+        rx.recv()??;
+        *installed = true;
+        Ok(())
+    }
+
+    unsafe extern "system" fn hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        let trigger = TRIGGER.load(Ordering::SeqCst);
+        if code == HC_ACTION as i32 && trigger != 0 {
+            let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+            let xbutton = (info.mouseData >> 16) as u16;
+            let (pressed, button) = match wparam.0 as u32 {
+                WM_MBUTTONDOWN => (true, Some(MouseTrigger::Middle)),
+                WM_MBUTTONUP => (false, Some(MouseTrigger::Middle)),
+                WM_XBUTTONDOWN | WM_XBUTTONUP => {
+                    let pressed = wparam.0 as u32 == WM_XBUTTONDOWN;
+                    match xbutton {
+                        XBUTTON1 => (pressed, Some(MouseTrigger::X1)),
+                        XBUTTON2 => (pressed, Some(MouseTrigger::X2)),
+                        _ => (pressed, None),
+                    }
+                }
+                _ => (false, None),
+            };
+
+            if button.is_some() && encode(button) == trigger {
+                if pressed {
+                    if let Some(callback) = CALLBACK.get() {
+                        callback();
+                    }
+                }
+                // 拦截绑定按键的按下和松开，避免同时触发浏览器后退等默认操作
+                return LRESULT(1);
+            }
+        }
+        CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+    }
+}
@@ -0,0 +1,79 @@
+//! 密码框检测模块
+//!
+//! 判断当前获得焦点的控件是否为密码输入框，避免把文本误输入到密码框中。
+//! Windows 通过 UI Automation 的 IsPassword 属性和传统编辑框的 ES_PASSWORD 样式判断，
+//! 其他平台无法检测。
+
+/// 当前焦点控件是否为密码框，无法判断时返回 None
+#[cfg(target_os = "windows")]
+pub fn focused_is_password() -> Option<bool> {
+    match (automation_is_password(), edit_style_is_password()) {
+        (None, None) => None,
+        (automation, style) => Some(automation == Some(true) || style == Some(true)),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn focused_is_password() -> Option<bool> {
+    None
+}
+
+/// 通过 UI Automation 读取焦点元素的 IsPassword 属性（可识别浏览器等自绘控件）
+#[cfg(target_os = "windows")]
+fn automation_is_password() -> Option<bool> {
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+    };
+    use windows::Win32::UI::Accessibility::{CUIAutomation, IUIAutomation};
+
+    unsafe {
+        // 线程已按其他模式初始化 COM 时返回错误，此时仍可使用，但不能调用 CoUninitialize
+        let initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+        let result = (|| {
+            let automation: IUIAutomation =
+                CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER).ok()?;
+            let element = automation.GetFocusedElement().ok()?;
+            element.CurrentIsPassword().ok().map(|is_password| is_password.as_bool())
+        })();
+        if initialized {
+            CoUninitialize();
+        }
+        result
+    }
+}
+
+/// 检查前台窗口中获得焦点的标准编辑框是否带有 ES_PASSWORD 样式
+#[cfg(target_os = "windows")]
+fn edit_style_is_password() -> Option<bool> {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetClassNameW, GetForegroundWindow, GetGUIThreadInfo, GetWindowLongW,
+        GetWindowThreadProcessId, ES_PASSWORD, GUITHREADINFO, GWL_STYLE,
+    };
+
+    unsafe {
+        let foreground = GetForegroundWindow();
+        if foreground.is_invalid() {
+            return None;
+        }
+        let thread_id = GetWindowThreadProcessId(foreground, None);
+        let mut info = GUITHREADINFO {
+            cbSize: std::mem::size_of::<GUITHREADINFO>() as u32,
+            ..Default::default()
+        };
+        GetGUIThreadInfo(thread_id, &mut info).ok()?;
+        if info.hwndFocus.is_invalid() {
+            return None;
+        }
+
+        // ES_PASSWORD 与其他控件的样式位重叠，只对编辑框类有意义
+        let mut class_name = [0u16; 64];
+        let len = GetClassNameW(info.hwndFocus, &mut class_name);
+        let class_name = String::from_utf16_lossy(&class_name[..len.max(0) as usize]);
+        if !class_name.to_ascii_lowercase().contains("edit") {
+            return None;
+        }
+
+        let style = GetWindowLongW(info.hwndFocus, GWL_STYLE);
+        Some(style & ES_PASSWORD != 0)
+    }
+}
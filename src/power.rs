@@ -0,0 +1,45 @@
+//! 电源状态模块
+//!
+//! 检测是否正在使用电池供电，用电池时降低剪贴板轮询频率以节省电量。
+//! Windows 通过 GetSystemPowerStatus 查询，Linux 读取 `/sys/class/power_supply`，
+//! 其他平台无法查询，返回 None。
+
+/// 是否正在使用电池供电，无法确定时返回 None
+#[cfg(target_os = "windows")]
+pub fn on_battery() -> Option<bool> {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }.ok()?;
+    // ACLineStatus: 0 为电池供电，1 为接通电源，255 为未知
+    match status.ACLineStatus {
+        0 => Some(true),
+        1 => Some(false),
+        _ => None,
+    }
+}
+
+/// 是否正在使用电池供电，无法确定（例如没有交流电源信息的台式机）时返回 None
+#[cfg(target_os = "linux")]
+pub fn on_battery() -> Option<bool> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut found_mains = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        if kind.trim() != "Mains" {
+            continue;
+        }
+        found_mains = true;
+        let online = std::fs::read_to_string(path.join("online")).unwrap_or_default();
+        if online.trim() == "1" {
+            return Some(false);
+        }
+    }
+    found_mains.then_some(true)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn on_battery() -> Option<bool> {
+    None
+}
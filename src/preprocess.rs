@@ -0,0 +1,99 @@
+//! 外部命令预处理模块
+//!
+//! 输入前把文本写入用户配置的命令的标准输入，用其标准输出作为要输入的文本，
+//! 例如调用格式化工具。命令通过系统 shell 执行（Windows 为 `cmd /C`，其他平台为 `sh -c`）。
+
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 检查命令是否结束的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// 预处理失败的原因
+#[derive(Debug)]
+pub enum PreprocessError {
+    /// 无法启动命令
+    Spawn(io::Error),
+    /// 超时未结束，命令已被终止
+    Timeout,
+    /// 命令以非零状态退出
+    Failed { code: Option<i32>, stderr: String },
+    /// 输出不是有效的 UTF-8
+    InvalidOutput,
+}
+
+/// 构造通过系统 shell 执行 `command` 的命令
+#[cfg(target_os = "windows")]
+fn shell_command(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    /// 不为命令创建控制台窗口
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command).creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+/// 把 `input` 写入命令的标准输入，返回其标准输出；超过 `timeout` 仍未结束时终止命令
+pub fn run(command: &str, input: &str, timeout: Duration) -> Result<String, PreprocessError> {
+    let mut child = shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(PreprocessError::Spawn)?;
+
+    // 在单独的线程中写入和读取，避免管道缓冲区写满时互相等待
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_string();
+    let writer = thread::spawn(move || {
+        // 命令可能不读取输入就退出，忽略写入错误
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let stdout_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stdout.read_to_end(&mut buf);
+        buf
+    });
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let stderr_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf);
+        buf
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait().map_err(PreprocessError::Spawn)? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(PreprocessError::Timeout);
+            }
+            None => thread::sleep(POLL_INTERVAL),
+        }
+    };
+
+    let _ = writer.join();
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if !status.success() {
+        return Err(PreprocessError::Failed {
+            code: status.code(),
+            stderr: String::from_utf8_lossy(&stderr).trim().to_string(),
+        });
+    }
+    String::from_utf8(stdout).map_err(|_| PreprocessError::InvalidOutput)
+}
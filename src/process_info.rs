@@ -0,0 +1,70 @@
+//! 进程信息模块
+//!
+//! 获取窗口所属进程的可执行文件名，用于按来源程序或前台程序过滤剪贴板内容。
+//! 目前仅 Windows 支持，其他平台返回 None。
+
+/// 获取当前剪贴板所有者窗口所属进程的可执行文件名（如 `notepad.exe`）
+#[cfg(target_os = "windows")]
+pub fn clipboard_owner_exe() -> Option<String> {
+    use windows::Win32::System::DataExchange::GetClipboardOwner;
+
+    let hwnd = unsafe { GetClipboardOwner() }.ok()?;
+    window_process_exe(hwnd)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn clipboard_owner_exe() -> Option<String> {
+    None
+}
+
+/// 获取当前前台窗口所属进程的可执行文件名
+#[cfg(target_os = "windows")]
+pub fn foreground_exe() -> Option<String> {
+    use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.is_invalid() {
+        return None;
+    }
+    window_process_exe(hwnd)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn foreground_exe() -> Option<String> {
+    None
+}
+
+/// 获取窗口所属进程的可执行文件名
+#[cfg(target_os = "windows")]
+fn window_process_exe(hwnd: windows::Win32::Foundation::HWND) -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{CloseHandle, BOOL, MAX_PATH};
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+    unsafe {
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if pid == 0 {
+            return None;
+        }
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, BOOL::from(false), pid).ok()?;
+        let mut buffer = [0u16; MAX_PATH as usize];
+        let mut size = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut size,
+        );
+        let _ = CloseHandle(process);
+        result.ok()?;
+
+        let path = String::from_utf16_lossy(&buffer[..size as usize]);
+        path.rsplit(['\\', '/']).next().map(|name| name.to_string())
+    }
+}
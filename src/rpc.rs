@@ -0,0 +1,230 @@
+//! 标准输入输出控制模块
+//!
+//! 以 `--rpc` 启动时不显示界面，从标准输入逐行读取 JSON 命令，并把结果逐行写到标准输出，
+//! 供编辑器插件和命令行工具集成。日志仍输出到标准错误，不会混入响应。
+
+use crate::app_config::AppConfig;
+use crate::clipboard_format;
+use crate::i18n::I18n;
+use crate::{SharedState, TypingRequest};
+use arboard::Clipboard;
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use std::thread;
+use std::time::Duration;
+
+/// 等待输入完成时检查状态的间隔
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 支持的命令
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+enum Command {
+    /// 输入文本，未给出 text 时输入当前剪贴板文本
+    Type {
+        text: Option<String>,
+        /// 是否等到输入结束再返回
+        #[serde(default = "default_wait")]
+        wait: bool,
+    },
+    /// 读取当前剪贴板文本
+    GetClipboard,
+    /// 把文本存入寄存器 (a-z)，未给出 text 时存入当前剪贴板文本
+    SetRegister { register: char, text: Option<String> },
+    /// 输入寄存器中的文本
+    TypeRegister {
+        register: char,
+        #[serde(default = "default_wait")]
+        wait: bool,
+    },
+    /// 设置本次运行的按键延迟和随机偏差（不写入配置文件）
+    SetSpeed { delay: u64, variance: Option<u64> },
+    /// 查询输入状态
+    Status,
+    /// 取消正在进行的输入
+    Cancel,
+}
+
+fn default_wait() -> bool {
+    true
+}
+
+/// 命令执行失败的原因
+struct RpcError {
+    code: &'static str,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// 运行控制模式，标准输入关闭后返回
+pub fn run(app_config: &AppConfig, i18n: I18n) {
+    let state = SharedState::new(i18n);
+    state.apply_config(app_config);
+    info!("{}", state.t("log.rpc_started"));
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                let err = e.to_string();
+                warn!("{}", state.tr("log.rpc_read_fail", &[("err", err.as_str())]));
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = handle_line(&state, &line);
+        if writeln!(stdout, "{}", response).and_then(|_| stdout.flush()).is_err() {
+            break;
+        }
+    }
+
+    // 不留下在后台继续输入的线程
+    state.cancel_typing();
+    while state.is_typing() {
+        thread::sleep(WAIT_POLL_INTERVAL);
+    }
+    info!("{}", state.t("log.rpc_stopped"));
+}
+
+/// 处理一行命令并生成响应，请求中的 `id` 原样返回
+fn handle_line(state: &SharedState, line: &str) -> Value {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return error_response(Value::Null, RpcError::new("invalid_json", e.to_string())),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let command = match Command::deserialize(&request) {
+        Ok(command) => command,
+        Err(e) => return error_response(id, RpcError::new("invalid_command", e.to_string())),
+    };
+
+    match execute(state, command) {
+        Ok(mut result) => {
+            result["id"] = id;
+            result["ok"] = Value::Bool(true);
+            result
+        }
+        Err(err) => error_response(id, err),
+    }
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    json!({
+        "id": id,
+        "ok": false,
+        "error": { "code": err.code, "message": err.message },
+    })
+}
+
+fn execute(state: &SharedState, command: Command) -> Result<Value, RpcError> {
+    match command {
+        Command::Type { text, wait } => {
+            check_can_type(state)?;
+            let text = match text {
+                Some(text) => text,
+                None => read_clipboard(state)?,
+            };
+            type_text(state, text, wait)
+        }
+        Command::GetClipboard => Ok(json!({ "text": read_clipboard(state)? })),
+        Command::SetRegister { register, text } => {
+            check_register(register)?;
+            let text = match text {
+                Some(text) => text,
+                None => read_clipboard(state)?,
+            };
+            let len = text.chars().count();
+            state.set_register(register, text);
+            Ok(json!({ "register": register, "len": len }))
+        }
+        Command::TypeRegister { register, wait } => {
+            check_register(register)?;
+            check_can_type(state)?;
+            let text = state
+                .registers
+                .lock()
+                .unwrap()
+                .get(&register)
+                .cloned()
+                .ok_or_else(|| RpcError::new("register_empty", format!("register {} is empty", register)))?;
+            type_text(state, text, wait)
+        }
+        Command::SetSpeed { delay, variance } => {
+            *state.typing_delay.lock().unwrap() = delay;
+            if let Some(variance) = variance {
+                *state.typing_variance.lock().unwrap() = variance;
+                *state.typing_variance_enabled.lock().unwrap() = variance > 0;
+            }
+            let variance = *state.typing_variance.lock().unwrap();
+            Ok(json!({ "delay": delay, "variance": variance }))
+        }
+        Command::Status => Ok(json!({
+            "enabled": state.is_enabled(),
+            "typing": state.is_typing(),
+            "status": state.get_status(),
+        })),
+        Command::Cancel => {
+            let typing = state.is_typing();
+            state.cancel_typing();
+            Ok(json!({ "cancelled": typing }))
+        }
+    }
+}
+
+/// 未启用或正在输入时不能开始新的输入
+fn check_can_type(state: &SharedState) -> Result<(), RpcError> {
+    if !state.is_enabled() {
+        return Err(RpcError::new("disabled", "typing is disabled"));
+    }
+    if state.is_typing() {
+        return Err(RpcError::new("busy", "typing is already in progress"));
+    }
+    Ok(())
+}
+
+/// 寄存器名只能是 a-z
+fn check_register(register: char) -> Result<(), RpcError> {
+    if register.is_ascii_lowercase() {
+        Ok(())
+    } else {
+        Err(RpcError::new("invalid_register", "register must be a letter from a to z"))
+    }
+}
+
+/// 开始输入文本，`wait` 为 true 时等到输入结束再返回
+fn type_text(state: &SharedState, text: String, wait: bool) -> Result<Value, RpcError> {
+    state.execute_typing_request(TypingRequest {
+        text: Some(text),
+        ..Default::default()
+    });
+    if wait {
+        while state.is_typing() {
+            thread::sleep(WAIT_POLL_INTERVAL);
+        }
+    }
+    Ok(json!({ "typing": state.is_typing(), "status": state.get_status() }))
+}
+
+/// 按设置中的格式优先级读取剪贴板文本
+fn read_clipboard(state: &SharedState) -> Result<String, RpcError> {
+    let mut clipboard = Clipboard::new().map_err(|e| RpcError::new("clipboard_unavailable", e.to_string()))?;
+    let priority = state.clipboard_format_priority.lock().unwrap().clone();
+    let markdown = *state.markdown_mode.lock().unwrap();
+    clipboard_format::read_text_by_priority(&mut clipboard, &priority, markdown)
+        .ok_or_else(|| RpcError::new("clipboard_empty", "the clipboard has no text"))
+}
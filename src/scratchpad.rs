@@ -0,0 +1,31 @@
+//! 便签模块
+//!
+//! 便签是独立于剪贴板的一段常用文本，保存在配置目录下的 scratchpad.txt 中，
+//! 可以随时通过按钮或快捷键输入。
+
+use crate::app_config::AppConfig;
+use std::fs;
+use std::path::PathBuf;
+
+/// 获取便签文件路径
+fn scratchpad_path() -> Option<PathBuf> {
+    AppConfig::config_dir().map(|p| p.join("scratchpad.txt"))
+}
+
+/// 从文件加载便签内容，文件不存在时返回空字符串
+pub fn load() -> String {
+    scratchpad_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default()
+}
+
+/// 保存便签内容到文件
+pub fn save(text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(path) = scratchpad_path() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, text)?;
+    }
+    Ok(())
+}
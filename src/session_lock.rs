@@ -0,0 +1,103 @@
+//! 会话锁定检测模块
+//!
+//! 锁屏时继续输入会把按键发送到登录界面，因此需要在锁定时暂停或取消输入。
+//! Windows 通过 WTSRegisterSessionNotification 接收 WM_WTSSESSION_CHANGE 消息，
+//! 其他平台暂不支持。
+
+use std::error::Error;
+
+/// 开始监听会话锁定与解锁，`on_change` 的参数为 true 表示已锁定
+#[cfg(target_os = "windows")]
+pub fn watch(on_change: impl Fn(bool) + Send + Sync + 'static) -> Result<(), Box<dyn Error>> {
+    platform::watch(Box::new(on_change))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn watch(_on_change: impl Fn(bool) + Send + Sync + 'static) -> Result<(), Box<dyn Error>> {
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::error::Error;
+    use std::sync::{mpsc, OnceLock};
+    use std::thread;
+    use windows::core::w;
+    use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::System::RemoteDesktop::{WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW, TranslateMessage,
+        MSG, WINDOW_EX_STYLE, WINDOW_STYLE, WM_WTSSESSION_CHANGE, WNDCLASSW, WTS_SESSION_LOCK,
+        WTS_SESSION_UNLOCK,
+    };
+
+    type Callback = Box<dyn Fn(bool) + Send + Sync>;
+
+    /// 会话状态变化时执行的操作
+    static CALLBACK: OnceLock<Callback> = OnceLock::new();
+
+    pub fn watch(on_change: Callback) -> Result<(), Box<dyn Error>> {
+        if CALLBACK.set(on_change).is_err() {
+            return Ok(());
+        }
+
+        // 通知发送到接收窗口所在线程，在独立线程中创建隐藏窗口并运行消息循环
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || unsafe {
+            let result = (|| -> windows::core::Result<()> {
+                let instance = GetModuleHandleW(None)?.into();
+                let class_name = w!("CopyTypeSessionWatcher");
+                let class = WNDCLASSW {
+                    lpfnWndProc: Some(window_proc),
+                    hInstance: instance,
+                    lpszClassName: class_name,
+                    ..Default::default()
+                };
+                RegisterClassW(&class);
+                let hwnd = CreateWindowExW(
+                    WINDOW_EX_STYLE::default(),
+                    class_name,
+                    w!(""),
+                    WINDOW_STYLE::default(),
+                    0,
+                    0,
+                    0,
+                    0,
+                    None,
+                    None,
+                    instance,
+                    None,
+                )?;
+                WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION)
+            })();
+
+            let ok = result.is_ok();
+            let _ = tx.send(result);
+            if ok {
+                let mut msg = MSG::default();
+                while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {
+                    let _ = TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            }
+        });
+        rx.recv()??;
+        Ok(())
+    }
+
+    unsafe extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if msg == WM_WTSSESSION_CHANGE {
+            let locked = match wparam.0 as u32 {
+                WTS_SESSION_LOCK => Some(true),
+                WTS_SESSION_UNLOCK => Some(false),
+                _ => None,
+            };
+            if let (Some(locked), Some(callback)) = (locked, CALLBACK.get()) {
+                callback(locked);
+            }
+            return LRESULT(0);
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+}
@@ -0,0 +1,154 @@
+//! 提示音模块
+//!
+//! 在开始输入、暂停、完成和出错时播放简短的提示音，窗口隐藏时也能知道输入状态。
+//! 启用 `sound` 特性时用 rodio 播放不同音高的短音，否则使用系统提示音。
+
+use serde::{Deserialize, Serialize};
+
+/// 会播放提示音的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundCue {
+    /// 开始输入
+    Start,
+    /// 暂停输入
+    Pause,
+    /// 输入完成
+    Complete,
+    /// 输入出错
+    Error,
+}
+
+impl SoundCue {
+    /// 获取所有事件
+    pub fn all() -> Vec<SoundCue> {
+        vec![SoundCue::Start, SoundCue::Pause, SoundCue::Complete, SoundCue::Error]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            SoundCue::Start => "ui.app.sound_cue_start",
+            SoundCue::Pause => "ui.app.sound_cue_pause",
+            SoundCue::Complete => "ui.app.sound_cue_complete",
+            SoundCue::Error => "ui.app.sound_cue_error",
+        }
+    }
+}
+
+/// 各事件是否播放提示音
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SoundCueEvents {
+    #[serde(default = "default_true")]
+    pub start: bool,
+    #[serde(default = "default_true")]
+    pub pause: bool,
+    #[serde(default = "default_true")]
+    pub complete: bool,
+    #[serde(default = "default_true")]
+    pub error: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for SoundCueEvents {
+    fn default() -> Self {
+        Self {
+            start: true,
+            pause: true,
+            complete: true,
+            error: true,
+        }
+    }
+}
+
+impl SoundCueEvents {
+    /// 事件对应的开关
+    pub fn get_mut(&mut self, cue: SoundCue) -> &mut bool {
+        match cue {
+            SoundCue::Start => &mut self.start,
+            SoundCue::Pause => &mut self.pause,
+            SoundCue::Complete => &mut self.complete,
+            SoundCue::Error => &mut self.error,
+        }
+    }
+
+    /// 该事件是否播放提示音
+    pub fn is_enabled(&self, cue: SoundCue) -> bool {
+        match cue {
+            SoundCue::Start => self.start,
+            SoundCue::Pause => self.pause,
+            SoundCue::Complete => self.complete,
+            SoundCue::Error => self.error,
+        }
+    }
+}
+
+/// 在后台线程播放提示音，不阻塞调用方
+pub fn play(cue: SoundCue) {
+    std::thread::spawn(move || platform::play(cue));
+}
+
+#[cfg(feature = "sound")]
+mod platform {
+    use super::SoundCue;
+    use rodio::source::SineWave;
+    use rodio::{OutputStream, Sink, Source};
+    use std::time::Duration;
+
+    /// 每个事件的音高序列 (Hz) 和每个音的时长
+    fn tones(cue: SoundCue) -> (&'static [f32], Duration) {
+        match cue {
+            SoundCue::Start => (&[660.0, 880.0], Duration::from_millis(80)),
+            SoundCue::Pause => (&[660.0], Duration::from_millis(120)),
+            SoundCue::Complete => (&[880.0, 1320.0], Duration::from_millis(80)),
+            SoundCue::Error => (&[220.0, 220.0], Duration::from_millis(150)),
+        }
+    }
+
+    pub fn play(cue: SoundCue) {
+        let Ok((_stream, handle)) = OutputStream::try_default() else {
+            return;
+        };
+        let Ok(sink) = Sink::try_new(&handle) else {
+            return;
+        };
+        let (frequencies, duration) = tones(cue);
+        for frequency in frequencies {
+            sink.append(SineWave::new(*frequency).take_duration(duration).amplify(0.2));
+        }
+        sink.sleep_until_end();
+    }
+}
+
+#[cfg(all(not(feature = "sound"), target_os = "windows"))]
+mod platform {
+    use super::SoundCue;
+    use windows::Win32::System::Diagnostics::Debug::MessageBeep;
+    use windows::Win32::UI::WindowsAndMessaging::{MB_ICONASTERISK, MB_ICONHAND, MB_OK};
+
+    pub fn play(cue: SoundCue) {
+        let style = match cue {
+            SoundCue::Start | SoundCue::Pause => MB_OK,
+            SoundCue::Complete => MB_ICONASTERISK,
+            SoundCue::Error => MB_ICONHAND,
+        };
+        unsafe {
+            let _ = MessageBeep(style);
+        }
+    }
+}
+
+#[cfg(all(not(feature = "sound"), not(target_os = "windows")))]
+mod platform {
+    use super::SoundCue;
+    use std::io::Write;
+
+    /// 没有音频后端时输出响铃字符，由终端决定是否发声
+    pub fn play(_cue: SoundCue) {
+        let mut stderr = std::io::stderr();
+        let _ = stderr.write_all(b"\x07");
+        let _ = stderr.flush();
+    }
+}
@@ -0,0 +1,454 @@
+//! 文本转换模块
+//!
+//! 在模拟输入之前对文本做一系列可配置的处理，
+//! 每个步骤都可以单独开关，默认全部关闭以保持原有行为。
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization as _;
+use unicode_width::UnicodeWidthChar;
+
+/// Unicode 规范化方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UnicodeNormalization {
+    /// 不做处理
+    #[default]
+    None,
+    /// 组合形式 (NFC)，重音字母合并为单个字符
+    #[serde(rename = "NFC")]
+    Nfc,
+    /// 分解形式 (NFD)，重音字母拆分为基本字母加组合符号
+    #[serde(rename = "NFD")]
+    Nfd,
+}
+
+impl UnicodeNormalization {
+    /// 获取所有选项
+    pub fn all() -> Vec<UnicodeNormalization> {
+        vec![
+            UnicodeNormalization::None,
+            UnicodeNormalization::Nfc,
+            UnicodeNormalization::Nfd,
+        ]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            UnicodeNormalization::None => "ui.app.normalize_none",
+            UnicodeNormalization::Nfc => "ui.app.normalize_nfc",
+            UnicodeNormalization::Nfd => "ui.app.normalize_nfd",
+        }
+    }
+}
+
+/// 允许输入的字符集
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CharsetRestriction {
+    /// 不限制
+    #[default]
+    None,
+    /// 仅字母和数字 (ASCII)
+    Alphanumeric,
+    /// 可打印 ASCII 字符（含空格）
+    AsciiPrintable,
+    /// 自定义允许的字符
+    Custom,
+}
+
+impl CharsetRestriction {
+    /// 获取所有选项
+    pub fn all() -> Vec<CharsetRestriction> {
+        vec![
+            CharsetRestriction::None,
+            CharsetRestriction::Alphanumeric,
+            CharsetRestriction::AsciiPrintable,
+            CharsetRestriction::Custom,
+        ]
+    }
+
+    /// 对应的 i18n 键
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            CharsetRestriction::None => "ui.app.charset_none",
+            CharsetRestriction::Alphanumeric => "ui.app.charset_alphanumeric",
+            CharsetRestriction::AsciiPrintable => "ui.app.charset_ascii_printable",
+            CharsetRestriction::Custom => "ui.app.charset_custom",
+        }
+    }
+}
+
+/// 输入前的文本转换配置
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TransformConfig {
+    /// 是否去除 BOM (U+FEFF) 和零宽空格等不可见字符
+    #[serde(default)]
+    pub strip_invisible: bool,
+    /// Unicode 规范化方式
+    #[serde(default)]
+    pub normalize_unicode: UnicodeNormalization,
+    /// 是否去除首尾的 Markdown 代码围栏（```lang ... ```）
+    #[serde(default)]
+    pub strip_code_fences: bool,
+    /// 是否去除末尾的一个换行（`\n` 或 `\r\n`），避免在终端中提前执行命令
+    #[serde(default)]
+    pub strip_trailing_newline: bool,
+    /// 允许输入的字符集，集合外的字符会被跳过
+    #[serde(default)]
+    pub restrict_charset: CharsetRestriction,
+    /// `restrict_charset` 为 Custom 时允许的字符
+    #[serde(default)]
+    pub custom_charset: String,
+    /// 终端安全模式：去除换行和制表符以外的控制字符，避免触发终端转义序列或快捷键
+    #[serde(default)]
+    pub terminal_safe: bool,
+    /// 把制表符展开为空格，值为制表位宽度；None 表示保留制表符
+    #[serde(default)]
+    pub tabs_to_spaces: Option<u8>,
+    /// 展开制表符时对齐到下一个制表位，否则每个制表符固定替换为 `tabs_to_spaces` 个空格
+    #[serde(default)]
+    pub tab_column_aware: bool,
+    /// 把行首缩进中的空格合并为制表符，值为制表位宽度；None 表示不合并
+    #[serde(default)]
+    pub spaces_to_tabs: Option<u8>,
+}
+
+/// 去除的不可见字符：BOM/零宽不换行空格、零宽空格、单词连接符、蒙古文元音分隔符
+///
+/// 零宽连接符 (U+200D) 和零宽非连接符 (U+200C) 用于组合 emoji 和部分文字的字形，保留不动。
+const INVISIBLE_CHARS: &[char] = &['\u{FEFF}', '\u{200B}', '\u{2060}', '\u{180E}'];
+
+/// 命令行中常见的命令串联、管道、重定向和命令替换写法
+const SHELL_COMMAND_PATTERNS: &[&str] = &[";", "&&", "||", "|", ">", "<", "$(", "`"];
+
+/// 按配置依次对文本执行各转换步骤
+pub fn apply(text: &str, config: &TransformConfig) -> String {
+    let mut text = if config.strip_invisible {
+        normalize_unicode(&strip_invisible(text), config.normalize_unicode)
+    } else {
+        normalize_unicode(text, config.normalize_unicode)
+    };
+    if config.strip_code_fences {
+        text = strip_code_fences(&text);
+    }
+    if config.strip_trailing_newline {
+        strip_trailing_newline(&mut text);
+    }
+    if config.terminal_safe {
+        text = strip_control_chars(&text);
+    }
+    if let Some(width) = config.tabs_to_spaces.filter(|w| *w > 0) {
+        text = expand_tabs(&text, width as usize, config.tab_column_aware);
+    } else if let Some(width) = config.spaces_to_tabs.filter(|w| *w > 0) {
+        text = indent_with_tabs(&text, width as usize);
+    }
+    text
+}
+
+/// 是否像会在终端中逐行执行的命令：中间带有换行，且包含命令串联、管道等写法
+pub fn looks_like_shell_commands(text: &str) -> bool {
+    let body = text.trim_end_matches(['\r', '\n']);
+    body.contains('\n') && SHELL_COMMAND_PATTERNS.iter().any(|pattern| body.contains(pattern))
+}
+
+/// 按字符集限制过滤文本，返回过滤后的文本和被跳过的字符数
+///
+/// 只作用于要输入的文本本身，需在解析按键标记之后调用，避免 `<<TAB>>` 等标记被拆散。
+pub fn restrict_charset(text: &str, config: &TransformConfig) -> (String, usize) {
+    let allowed = |c: char| match config.restrict_charset {
+        CharsetRestriction::None => true,
+        CharsetRestriction::Alphanumeric => c.is_ascii_alphanumeric(),
+        CharsetRestriction::AsciiPrintable => c == ' ' || c.is_ascii_graphic(),
+        CharsetRestriction::Custom => config.custom_charset.contains(c),
+    };
+    let mut dropped = 0;
+    let filtered = text
+        .chars()
+        .filter(|c| {
+            let keep = allowed(*c);
+            if !keep {
+                dropped += 1;
+            }
+            keep
+        })
+        .collect();
+    (filtered, dropped)
+}
+
+/// 首尾是否带有空格或制表符
+pub fn has_edge_whitespace(text: &str) -> bool {
+    trim_edge_whitespace(text).len() != text.len()
+}
+
+/// 去除首尾的空格和制表符，换行保持不变
+pub fn trim_edge_whitespace(text: &str) -> &str {
+    text.trim_matches(|c| c == ' ' || c == '\t')
+}
+
+/// 把空格显示为 `·`、制表符显示为 `→`，便于看出多余的空白
+pub fn visualize_whitespace(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            ' ' => '·',
+            '\t' => '→',
+            c => c,
+        })
+        .collect()
+}
+
+/// 去除换行和制表符以外的控制字符（`\r\n` 变为 `\n`）
+fn strip_control_chars(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
+        .collect()
+}
+
+/// 去除 BOM 和零宽空格等不可见字符
+fn strip_invisible(text: &str) -> String {
+    text.chars().filter(|c| !INVISIBLE_CHARS.contains(c)).collect()
+}
+
+/// Unicode 规范化
+fn normalize_unicode(text: &str, mode: UnicodeNormalization) -> String {
+    match mode {
+        UnicodeNormalization::None => text.to_string(),
+        UnicodeNormalization::Nfc => text.nfc().collect(),
+        UnicodeNormalization::Nfd => text.nfd().collect(),
+    }
+}
+
+/// 去除包裹整段文本的代码围栏
+///
+/// 只有首行是开始围栏（可带语言标记）且末行是相同字符、长度不小于开始围栏的结束围栏，
+/// 中间没有其他结束围栏时才处理，其余文本原样返回。
+fn strip_code_fences(text: &str) -> String {
+    let trimmed = text.trim();
+    let (first_line, body) = match trimmed.find('\n') {
+        Some(pos) => (trimmed[..pos].trim(), &trimmed[pos + 1..]),
+        None => return text.to_string(),
+    };
+
+    let Some(fence_char) = first_line.chars().next().filter(|c| *c == '`' || *c == '~') else {
+        return text.to_string();
+    };
+    let fence_len = first_line.chars().take_while(|c| *c == fence_char).count();
+    let info = &first_line[fence_len..];
+    // 反引号围栏的语言标记中不能再出现反引号，否则是行内代码
+    if fence_len < 3 || (fence_char == '`' && info.contains('`')) {
+        return text.to_string();
+    }
+
+    let (inner, last_line) = match body.rfind('\n') {
+        Some(pos) => (&body[..pos], body[pos + 1..].trim()),
+        None => ("", body.trim()),
+    };
+    let is_closing = |line: &str| {
+        let line = line.trim();
+        line.len() >= fence_len && line.chars().all(|c| c == fence_char)
+    };
+    // 中间还有结束围栏说明是多个代码块，不是包裹整段文本的围栏
+    if !is_closing(last_line) || inner.lines().any(is_closing) {
+        return text.to_string();
+    }
+
+    inner.trim_end_matches('\r').to_string()
+}
+
+/// 把制表符替换为空格
+///
+/// `column_aware` 时按所在列补齐到下一个 `width` 的整数倍，行内对齐的制表符也能保持对齐；
+/// 否则每个制表符固定替换为 `width` 个空格。列按显示宽度计算，中文等全角字符占两列、组合符号不占列。
+fn expand_tabs(text: &str, width: usize, column_aware: bool) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut column = 0;
+    for c in text.chars() {
+        match c {
+            '\t' => {
+                let spaces = if column_aware { width - column % width } else { width };
+                result.push_str(&" ".repeat(spaces));
+                column += spaces;
+            }
+            '\n' => {
+                result.push(c);
+                column = 0;
+            }
+            _ => {
+                result.push(c);
+                column += c.width().unwrap_or(0);
+            }
+        }
+    }
+    result
+}
+
+/// 把每行行首缩进（空格和制表符混合）按 `width` 列一个制表符重新写成制表符，不足一个制表位的部分保留为空格
+///
+/// 只处理行首缩进，行内的空格可能是文本内容的一部分，保持不变。
+fn indent_with_tabs(text: &str, width: usize) -> String {
+    text.split('\n')
+        .map(|line| {
+            let body = line.trim_start_matches([' ', '\t']);
+            let indent = &line[..line.len() - body.len()];
+            let columns = indent.chars().fold(0, |column, c| match c {
+                '\t' => column + width - column % width,
+                _ => column + 1,
+            });
+            let mut result = "\t".repeat(columns / width);
+            result.push_str(&" ".repeat(columns % width));
+            result.push_str(body);
+            result
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 去除末尾的一个换行，其余空白保持不变
+fn strip_trailing_newline(text: &mut String) {
+    if text.ends_with('\n') {
+        text.pop();
+        if text.ends_with('\r') {
+            text.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_fence_with_language_is_stripped() {
+        assert_eq!(strip_code_fences("```rust\nfn main() {}\n```"), "fn main() {}");
+        assert_eq!(strip_code_fences("  ~~~ sh\necho hi\nls\n~~~~\n"), "echo hi\nls");
+        assert_eq!(strip_code_fences("```\r\na\r\n```\r\n"), "a");
+    }
+
+    #[test]
+    fn unfenced_text_is_unchanged() {
+        for text in ["plain text", "line 1\nline 2", "`inline` code", "``\nx\n``", "```a`b\nx\n```"] {
+            assert_eq!(strip_code_fences(text), text);
+        }
+    }
+
+    #[test]
+    fn unterminated_fence_is_unchanged() {
+        for text in ["```rust\nfn main() {}", "```\nx\n``", "````\nx\n```", "```\nx\n~~~"] {
+            assert_eq!(strip_code_fences(text), text);
+        }
+    }
+
+    #[test]
+    fn multiple_fence_blocks_are_unchanged() {
+        let text = "```sh\necho a\n```\nthen\n```sh\necho b\n```";
+        assert_eq!(strip_code_fences(text), text);
+    }
+
+    #[test]
+    fn longer_outer_fence_keeps_inner_fences() {
+        let text = "````md\n```sh\necho a\n```\n````";
+        assert_eq!(strip_code_fences(text), "```sh\necho a\n```");
+    }
+
+    fn charset(restriction: CharsetRestriction, custom: &str) -> TransformConfig {
+        TransformConfig {
+            restrict_charset: restriction,
+            custom_charset: custom.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn charset_none_keeps_everything() {
+        let text = "Hi 你好 👍";
+        assert_eq!(restrict_charset(text, &charset(CharsetRestriction::None, "")), (text.to_string(), 0));
+    }
+
+    #[test]
+    fn ascii_printable_strips_cjk_and_emoji() {
+        let config = charset(CharsetRestriction::AsciiPrintable, "");
+        assert_eq!(restrict_charset("Hi 你好!", &config), ("Hi !".to_string(), 2));
+        // 带肤色的 emoji 由两个字符组成，各计一次
+        assert_eq!(restrict_charset("ok 👍🏽", &config), ("ok ".to_string(), 2));
+        assert_eq!(restrict_charset("a~b", &config), ("a~b".to_string(), 0));
+    }
+
+    #[test]
+    fn alphanumeric_strips_spaces_and_punctuation() {
+        let config = charset(CharsetRestriction::Alphanumeric, "");
+        assert_eq!(restrict_charset("ab-12 密码", &config), ("ab12".to_string(), 4));
+    }
+
+    #[test]
+    fn custom_charset() {
+        let config = charset(CharsetRestriction::Custom, "0123456789中");
+        assert_eq!(restrict_charset("中国 2024", &config), ("中2024".to_string(), 2));
+        let empty = charset(CharsetRestriction::Custom, "");
+        assert_eq!(restrict_charset("abc", &empty), (String::new(), 3));
+    }
+
+    #[test]
+    fn invisible_leading_bom_is_removed() {
+        assert_eq!(strip_invisible("\u{FEFF}hello"), "hello");
+    }
+
+    #[test]
+    fn invisible_zero_width_space_is_removed() {
+        assert_eq!(strip_invisible("pass\u{200B}word\u{2060}!"), "password!");
+    }
+
+    #[test]
+    fn invisible_zero_width_joiners_are_kept() {
+        // 零宽连接符组成 emoji 序列和部分文字的字形，保留不动
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(strip_invisible(family), family);
+        assert_eq!(strip_invisible("a\u{200D}b\u{200C}c"), "a\u{200D}b\u{200C}c");
+    }
+
+    #[test]
+    fn invisible_leaves_normal_text_untouched() {
+        let text = "你好，世界\tHello  world\r\n\u{00A0}end ";
+        assert_eq!(strip_invisible(text), text);
+    }
+
+    #[test]
+    fn tabs_expand_to_fixed_width() {
+        assert_eq!(expand_tabs("\ta\tb", 4, false), "    a    b");
+        assert_eq!(expand_tabs("ab\tc\n\td", 2, false), "ab  c\n  d");
+    }
+
+    #[test]
+    fn tabs_expand_to_next_tab_stop() {
+        assert_eq!(expand_tabs("\ta\tb", 4, true), "    a   b");
+        assert_eq!(expand_tabs("abcd\te", 4, true), "abcd    e");
+        assert_eq!(expand_tabs("  \tx\n \ty", 4, true), "    x\n    y");
+    }
+
+    #[test]
+    fn tab_stops_use_display_width() {
+        // 全角字符占两列
+        assert_eq!(expand_tabs("名字\t值", 8, true), "名字    值");
+        assert_eq!(expand_tabs("ab\t值", 8, true), "ab      值");
+        // 组合符号不占列
+        assert_eq!(expand_tabs("e\u{301}\tx", 4, true), "e\u{301}   x");
+    }
+
+    #[test]
+    fn leading_spaces_become_tabs() {
+        assert_eq!(indent_with_tabs("        a\n    b\nc", 4), "\t\ta\n\tb\nc");
+        // 行内空格保持不变
+        assert_eq!(indent_with_tabs("    a    b", 4), "\ta    b");
+    }
+
+    #[test]
+    fn mixed_indent_becomes_tabs() {
+        assert_eq!(indent_with_tabs("  \tx", 4), "\tx");
+        assert_eq!(indent_with_tabs("\t    x", 4), "\t\tx");
+        assert_eq!(indent_with_tabs("\t  x", 4), "\t  x");
+    }
+
+    #[test]
+    fn indent_not_divisible_by_width_keeps_remaining_spaces() {
+        assert_eq!(indent_with_tabs("      x", 4), "\t  x");
+        assert_eq!(indent_with_tabs("   x", 4), "   x");
+        assert_eq!(indent_with_tabs("       x\n", 3), "\t\t x\n");
+    }
+}
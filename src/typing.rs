@@ -0,0 +1,222 @@
+//! 输入指令解析模块
+//!
+//! 将文本中的按键标记（如 `<<TAB>>`、`<<DELAY:500>>`、`<<PAUSE>>`）解析为输入指令，
+//! 使剪贴板片段可以作为简单的表单填写宏或演示脚本使用。
+
+use enigo::Key;
+use serde::{Deserialize, Serialize};
+
+/// 标记起始符
+const TOKEN_OPEN: &str = "<<";
+/// 标记结束符
+const TOKEN_CLOSE: &str = ">>";
+/// 单个延迟标记允许的最大毫秒数，防止误写导致长时间卡住
+const MAX_TOKEN_DELAY_MS: u64 = 60_000;
+
+/// 可由标记发送的特殊按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpecialKey {
+    Tab,
+    Enter,
+    Escape,
+}
+
+impl SpecialKey {
+    /// 获取所有按键
+    pub fn all() -> Vec<SpecialKey> {
+        vec![SpecialKey::Tab, SpecialKey::Enter, SpecialKey::Escape]
+    }
+
+    /// 显示名称
+    pub fn display(&self) -> &'static str {
+        match self {
+            SpecialKey::Tab => "Tab",
+            SpecialKey::Enter => "Enter",
+            SpecialKey::Escape => "Esc",
+        }
+    }
+
+    /// 根据标记名称查找按键（不区分大小写）
+    fn from_name(name: &str) -> Option<SpecialKey> {
+        match name.to_ascii_uppercase().as_str() {
+            "TAB" => Some(SpecialKey::Tab),
+            "ENTER" => Some(SpecialKey::Enter),
+            "ESC" => Some(SpecialKey::Escape),
+            _ => None,
+        }
+    }
+
+    /// 转换为 enigo 的 Key
+    pub fn to_enigo_key(self) -> Key {
+        match self {
+            SpecialKey::Tab => Key::Tab,
+            SpecialKey::Enter => Key::Return,
+            SpecialKey::Escape => Key::Escape,
+        }
+    }
+}
+
+/// 输入指令
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypingToken {
+    /// 逐字输入的文本
+    Text(String),
+    /// 按下并松开一个特殊按键
+    Key(SpecialKey),
+    /// 暂停指定毫秒数
+    Delay(u64),
+    /// 暂停输入，等待按快捷键继续
+    Pause,
+}
+
+/// 将文本解析为输入指令
+///
+/// 无法识别或格式错误的标记按原文保留为普通文本。
+pub fn tokenize(text: &str) -> Vec<TypingToken> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find(TOKEN_OPEN) {
+        literal.push_str(&rest[..open]);
+        let after_open = &rest[open + TOKEN_OPEN.len()..];
+
+        let parsed = after_open
+            .find(TOKEN_CLOSE)
+            .and_then(|close| parse_token(&after_open[..close]).map(|token| (token, close)));
+
+        match parsed {
+            Some((token, close)) => {
+                if !literal.is_empty() {
+                    tokens.push(TypingToken::Text(std::mem::take(&mut literal)));
+                }
+                tokens.push(token);
+                rest = &after_open[close + TOKEN_CLOSE.len()..];
+            }
+            None => {
+                // 不是有效标记，只保留一个 '<'，剩余部分继续扫描
+                literal.push('<');
+                rest = &rest[open + 1..];
+            }
+        }
+    }
+
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        tokens.push(TypingToken::Text(literal));
+    }
+    tokens
+}
+
+/// 解析 `<<` 与 `>>` 之间的标记内容
+fn parse_token(body: &str) -> Option<TypingToken> {
+    if let Some((name, value)) = body.split_once(':') {
+        if name.eq_ignore_ascii_case("DELAY") {
+            let ms: u64 = value.trim().parse().ok()?;
+            return Some(TypingToken::Delay(ms.min(MAX_TOKEN_DELAY_MS)));
+        }
+        return None;
+    }
+    if body.eq_ignore_ascii_case("PAUSE") {
+        return Some(TypingToken::Pause);
+    }
+    SpecialKey::from_name(body).map(TypingToken::Key)
+}
+
+/// 是否为 emoji 或其他常见的输入易出错的符号
+///
+/// 包括所有辅助平面字符、杂项符号与装饰符号 (U+2600–U+27BF)、
+/// 杂项符号和箭头 (U+2B00–U+2BFF)、组合键帽 (U+20E3) 以及 emoji 变体选择符 (U+FE0F)。
+pub fn is_emoji(c: char) -> bool {
+    matches!(
+        c,
+        '\u{2600}'..='\u{27BF}' | '\u{2B00}'..='\u{2BFF}' | '\u{20E3}' | '\u{FE0F}'
+    ) || c > '\u{FFFF}'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> TypingToken {
+        TypingToken::Text(s.to_string())
+    }
+
+    #[test]
+    fn special_keys() {
+        assert_eq!(tokenize("<<TAB>>"), vec![TypingToken::Key(SpecialKey::Tab)]);
+        assert_eq!(tokenize("<<ENTER>>"), vec![TypingToken::Key(SpecialKey::Enter)]);
+        assert_eq!(tokenize("<<ESC>>"), vec![TypingToken::Key(SpecialKey::Escape)]);
+        assert_eq!(tokenize("<<PAUSE>>"), vec![TypingToken::Pause]);
+    }
+
+    #[test]
+    fn names_are_case_insensitive() {
+        assert_eq!(tokenize("<<tab>>"), vec![TypingToken::Key(SpecialKey::Tab)]);
+        assert_eq!(tokenize("<<Delay:5>>"), vec![TypingToken::Delay(5)]);
+    }
+
+    #[test]
+    fn delay() {
+        assert_eq!(tokenize("<<DELAY:250>>"), vec![TypingToken::Delay(250)]);
+        assert_eq!(tokenize("<<DELAY: 250 >>"), vec![TypingToken::Delay(250)]);
+        assert_eq!(tokenize("<<DELAY:60000>>"), vec![TypingToken::Delay(60_000)]);
+        assert_eq!(tokenize("<<DELAY:999999>>"), vec![TypingToken::Delay(MAX_TOKEN_DELAY_MS)]);
+    }
+
+    #[test]
+    fn malformed_tokens_stay_literal() {
+        for input in [
+            "<<DELAY:abc>>",
+            "<<DELAY:>>",
+            "<<DELAY:-5>>",
+            "<<TAB",
+            "a <<TAB b",
+            "<<unknown>>",
+            "<<>>",
+            "<<TAB:1>>",
+        ] {
+            assert_eq!(tokenize(input), vec![text(input)], "{input}");
+        }
+    }
+
+    #[test]
+    fn nested_brackets_keep_outer_literal() {
+        assert_eq!(
+            tokenize("<<<<TAB>>>>"),
+            vec![text("<<"), TypingToken::Key(SpecialKey::Tab), text(">>")]
+        );
+    }
+
+    #[test]
+    fn adjacent_tokens() {
+        assert_eq!(
+            tokenize("<<TAB>><<DELAY:10>><<ENTER>>"),
+            vec![
+                TypingToken::Key(SpecialKey::Tab),
+                TypingToken::Delay(10),
+                TypingToken::Key(SpecialKey::Enter),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_between_tokens() {
+        assert_eq!(
+            tokenize("姓名<<TAB>>张三<<ENTER>>👍🏽 ok"),
+            vec![
+                text("姓名"),
+                TypingToken::Key(SpecialKey::Tab),
+                text("张三"),
+                TypingToken::Key(SpecialKey::Enter),
+                text("👍🏽 ok"),
+            ]
+        );
+    }
+
+    #[test]
+    fn plain_text_is_one_token() {
+        assert_eq!(tokenize("a < b >> c"), vec![text("a < b >> c")]);
+        assert_eq!(tokenize(""), Vec::<TypingToken>::new());
+    }
+}